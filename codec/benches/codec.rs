@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MPL-2.0
+//! JSON string path vs JSON value path
+//!
+//! The `encode`/`decode` wasm exports parse and print JSON strings around the
+//! codec; `encode_object`/`decode_object` hand it values directly. This
+//! measures the Rust side of that difference natively. The JS side
+//! (`JSON.stringify`/`JSON.parse`) is covered by `objects.mjs`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rescript_grpc_codec::{decode, encode, Schema};
+use serde_json::{json, Value};
+
+const SCHEMA: &str = r#"[
+    {"n": 1, "name": "name", "type": "string"},
+    {"n": 2, "name": "items", "type": "message", "repeated": true, "fields": [
+        {"n": 1, "name": "id", "type": "int32"},
+        {"n": 2, "name": "label", "type": "string"},
+        {"n": 3, "name": "score", "type": "double"},
+        {"n": 4, "name": "createdAt", "type": "int64"},
+        {"n": 5, "name": "tags", "type": "string", "repeated": true}
+    ]}
+]"#;
+
+/// A message with `items` entries of mixed field types
+fn message(items: usize) -> Value {
+    let items: Vec<Value> = (0..items)
+        .map(|i| {
+            json!({
+                "id": i,
+                "label": format!("item number {}", i),
+                "score": i as f64 * 1.5,
+                "createdAt": (1_700_000_000_000i64 + i as i64).to_string(),
+                "tags": ["alpha", "beta", "gamma"]
+            })
+        })
+        .collect();
+    json!({"name": "benchmark", "items": items})
+}
+
+fn bench_codec(c: &mut Criterion) {
+    let schema = Schema::parse(SCHEMA).unwrap();
+    let value = message(1000);
+    let text = value.to_string();
+    let bytes = encode(&schema, &value).unwrap();
+
+    let mut group = c.benchmark_group("encode 1000 items");
+    group.bench_function("json string", |b| {
+        b.iter(|| {
+            let parsed: Value = serde_json::from_str(&text).unwrap();
+            encode(&schema, &parsed).unwrap()
+        })
+    });
+    group.bench_function("json value", |b| {
+        b.iter(|| encode(&schema, &value).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("decode 1000 items");
+    group.bench_function("json string", |b| {
+        b.iter(|| serde_json::to_string(&decode(&schema, &bytes).unwrap()).unwrap())
+    });
+    group.bench_function("json value", |b| {
+        b.iter(|| decode(&schema, &bytes).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_codec);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MPL-2.0
+// Round-trip cost of the JSON string exports (encode/decode) vs the
+// JS object exports (encode_object/decode_object), including the
+// JSON.stringify/JSON.parse and base64 work on the JS side.
+//
+// Build the Node package first (see README.adoc), then:
+//   node benches/objects.mjs [items] [iterations]
+
+import { createRequire } from "node:module";
+
+const require = createRequire(import.meta.url);
+const codec = require("../pkg/rescript_grpc_codec.js");
+
+const items = Number(process.argv[2] ?? 1000);
+const iterations = Number(process.argv[3] ?? 200);
+
+const schema = JSON.stringify([
+  { n: 1, name: "name", type: "string" },
+  {
+    n: 2,
+    name: "items",
+    type: "message",
+    repeated: true,
+    fields: [
+      { n: 1, name: "id", type: "int32" },
+      { n: 2, name: "label", type: "string" },
+      { n: 3, name: "score", type: "double" },
+      { n: 4, name: "createdAt", type: "int64" },
+      { n: 5, name: "tags", type: "string", repeated: true },
+    ],
+  },
+]);
+
+const message = {
+  name: "benchmark",
+  items: Array.from({ length: items }, (_, i) => ({
+    id: i,
+    label: `item number ${i}`,
+    score: i * 1.5,
+    createdAt: String(1_700_000_000_000 + i),
+    tags: ["alpha", "beta", "gamma"],
+  })),
+};
+
+const viaStrings = () => {
+  const base64 = codec.encode(schema, JSON.stringify(message));
+  return JSON.parse(codec.decode(schema, base64));
+};
+
+const viaObjects = () => {
+  const bytes = codec.encode_object(schema, message);
+  return codec.decode_object(schema, bytes);
+};
+
+const measure = (label, roundTrip) => {
+  for (let i = 0; i < 10; i++) roundTrip(); // warm up
+  const start = performance.now();
+  for (let i = 0; i < iterations; i++) roundTrip();
+  const perOp = (performance.now() - start) / iterations;
+  console.log(`${label.padEnd(8)} ${perOp.toFixed(3)} ms per round trip`);
+  return perOp;
+};
+
+console.log(`${items} items, ${iterations} iterations`);
+const strings = measure("strings", viaStrings);
+const objects = measure("objects", viaObjects);
+console.log(`speedup  ${(strings / objects).toFixed(2)}x`);
@@ -0,0 +1,483 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Conformance testee: reads `ConformanceRequest`s from stdin and answers on stdout
+//!
+//! `conformance_test_runner` starts the `conformance-testee` binary and sends
+//! length-prefixed `ConformanceRequest`s. Payloads are decoded and re-encoded
+//! with the codec's public `decode`/`encode`, so the suite measures this crate
+//! rather than prost. See `conformance/README.adoc`.
+//!
+//! The test message schemas are vendored below, mirroring
+//! `test_messages_proto3.proto`; recursive fields are expanded to a fixed
+//! depth since codec schemas are trees.
+
+use std::io::{self, Read, Write};
+
+use prost::Message;
+use rescript_grpc_codec::{decode, encode, FieldDescriptor, FieldType, MessageDescriptor, Schema, WellKnownType};
+use serde_json::Value;
+
+/// `conformance.WireFormat`
+const WIRE_FORMAT_PROTOBUF: i32 = 1;
+const WIRE_FORMAT_JSON: i32 = 2;
+
+/// How many levels of recursive message fields the vendored schema expands
+const RECURSION_DEPTH: usize = 3;
+
+const TEST_ALL_TYPES_PROTO3: &str = "protobuf_test_messages.proto3.TestAllTypesProto3";
+const FAILURE_SET: &str = "conformance.FailureSet";
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ConformanceRequest {
+    #[prost(oneof = "Payload", tags = "1, 2, 7, 8")]
+    pub payload: Option<Payload>,
+    #[prost(int32, tag = "3")]
+    pub requested_output_format: i32,
+    #[prost(string, tag = "4")]
+    pub message_type: String,
+    #[prost(int32, tag = "5")]
+    pub test_category: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum Payload {
+    #[prost(bytes, tag = "1")]
+    ProtobufPayload(Vec<u8>),
+    #[prost(string, tag = "2")]
+    JsonPayload(String),
+    #[prost(string, tag = "7")]
+    JspbPayload(String),
+    #[prost(string, tag = "8")]
+    TextPayload(String),
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ConformanceResponse {
+    #[prost(oneof = "ConformanceResult", tags = "1, 6, 2, 3, 4, 5")]
+    pub result: Option<ConformanceResult>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum ConformanceResult {
+    #[prost(string, tag = "1")]
+    ParseError(String),
+    #[prost(string, tag = "6")]
+    SerializeError(String),
+    #[prost(string, tag = "2")]
+    RuntimeError(String),
+    #[prost(bytes, tag = "3")]
+    ProtobufPayload(Vec<u8>),
+    #[prost(string, tag = "4")]
+    JsonPayload(String),
+    #[prost(string, tag = "5")]
+    Skipped(String),
+}
+
+fn main() -> io::Result<()> {
+    serve(io::stdin().lock(), io::stdout().lock())
+}
+
+/// Answer requests until the runner closes stdin
+fn serve(mut input: impl Read, mut output: impl Write) -> io::Result<()> {
+    let schema = Schema::from(test_all_types_proto3(RECURSION_DEPTH));
+
+    loop {
+        let mut len = [0u8; 4];
+        match input.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        }
+        let mut buf = vec![0u8; u32::from_le_bytes(len) as usize];
+        input.read_exact(&mut buf)?;
+
+        let response = match ConformanceRequest::decode(buf.as_slice()) {
+            Ok(request) => handle(&schema, &request),
+            Err(err) => ConformanceResponse {
+                result: Some(ConformanceResult::RuntimeError(format!("bad ConformanceRequest: {}", err))),
+            },
+        };
+
+        let bytes = response.encode_to_vec();
+        output.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        output.write_all(&bytes)?;
+        output.flush()?;
+    }
+}
+
+/// Run one request through the codec
+fn handle(schema: &Schema, request: &ConformanceRequest) -> ConformanceResponse {
+    let result = if request.message_type == FAILURE_SET {
+        // The runner asks for the testee's own failure list first; ours is
+        // passed with --failure_list instead
+        ConformanceResult::ProtobufPayload(Vec::new())
+    } else if request.message_type != TEST_ALL_TYPES_PROTO3 {
+        ConformanceResult::Skipped(format!("{} is not vendored", request.message_type))
+    } else {
+        roundtrip(schema, request)
+    };
+
+    ConformanceResponse { result: Some(result) }
+}
+
+fn roundtrip(schema: &Schema, request: &ConformanceRequest) -> ConformanceResult {
+    // Parse into the codec's JSON representation
+    let message = match &request.payload {
+        Some(Payload::ProtobufPayload(bytes)) => match decode(schema, bytes) {
+            Ok(message) => message,
+            Err(err) => return ConformanceResult::ParseError(err.to_string()),
+        },
+        Some(Payload::JsonPayload(json)) => {
+            // JSON input goes through encode and back through decode
+            let parsed = match serde_json::from_str::<Value>(json) {
+                Ok(parsed) if parsed.is_object() => parsed,
+                Ok(_) => return ConformanceResult::ParseError("expected a JSON object".to_string()),
+                Err(err) => return ConformanceResult::ParseError(err.to_string()),
+            };
+            let bytes = match encode(schema, &parsed) {
+                Ok(bytes) => bytes,
+                Err(err) => return ConformanceResult::ParseError(err.to_string()),
+            };
+            match decode(schema, &bytes) {
+                Ok(message) => message,
+                Err(err) => return ConformanceResult::ParseError(err.to_string()),
+            }
+        }
+        Some(Payload::JspbPayload(_)) => return ConformanceResult::Skipped("JSPB input".to_string()),
+        Some(Payload::TextPayload(_)) => return ConformanceResult::Skipped("text format input".to_string()),
+        None => return ConformanceResult::RuntimeError("request has no payload".to_string()),
+    };
+
+    match request.requested_output_format {
+        WIRE_FORMAT_PROTOBUF => match encode(schema, &message) {
+            Ok(bytes) => ConformanceResult::ProtobufPayload(bytes),
+            Err(err) => ConformanceResult::SerializeError(err.to_string()),
+        },
+        WIRE_FORMAT_JSON => match serde_json::to_string(&message) {
+            Ok(json) => ConformanceResult::JsonPayload(json),
+            Err(err) => ConformanceResult::SerializeError(err.to_string()),
+        },
+        other => ConformanceResult::Skipped(format!("output format {}", other)),
+    }
+}
+
+// ============================================================================
+// Vendored test messages
+// ============================================================================
+
+fn field(number: u32, name: &str, field_type: FieldType, is_repeated: bool) -> FieldDescriptor {
+    FieldDescriptor {
+        number,
+        name: name.to_string(),
+        field_type,
+        is_repeated,
+        is_optional: false,
+        is_packed: true,
+        oneof: None,
+    }
+}
+
+fn unpacked(field: FieldDescriptor) -> FieldDescriptor {
+    FieldDescriptor { is_packed: false, ..field }
+}
+
+fn with_presence(field: FieldDescriptor) -> FieldDescriptor {
+    FieldDescriptor { is_optional: true, ..field }
+}
+
+fn message(type_name: &str, fields: Vec<FieldDescriptor>) -> MessageDescriptor {
+    MessageDescriptor {
+        type_name: Some(type_name.to_string()),
+        fields,
+    }
+}
+
+/// Scalar types in the order of the `optional_*`/`repeated_*` field families
+fn scalar_types() -> Vec<(&'static str, FieldType)> {
+    vec![
+        ("Int32", FieldType::Int32),
+        ("Int64", FieldType::Int64),
+        ("Uint32", FieldType::Uint32),
+        ("Uint64", FieldType::Uint64),
+        ("Sint32", FieldType::Sint32),
+        ("Sint64", FieldType::Sint64),
+        ("Fixed32", FieldType::Fixed32),
+        ("Fixed64", FieldType::Fixed64),
+        ("Sfixed32", FieldType::Sfixed32),
+        ("Sfixed64", FieldType::Sfixed64),
+        ("Float", FieldType::Float),
+        ("Double", FieldType::Double),
+        ("Bool", FieldType::Bool),
+        ("String", FieldType::String),
+        ("Bytes", FieldType::Bytes),
+    ]
+}
+
+/// Map fields are repeated `{key = 1, value = 2}` entry messages on the wire
+fn map_entry(key: FieldType, value: FieldType) -> FieldType {
+    FieldType::Message(message("", vec![field(1, "key", key, false), field(2, "value", value, false)]))
+}
+
+/// `protobuf_test_messages.proto3.TestAllTypesProto3`, with JSON names as field names
+fn test_all_types_proto3(depth: usize) -> MessageDescriptor {
+    let foreign_message = || FieldType::Message(message("protobuf_test_messages.proto3.ForeignMessage", vec![
+        field(1, "c", FieldType::Int32, false),
+    ]));
+    let nested_message = || {
+        let mut fields = vec![field(1, "a", FieldType::Int32, false)];
+        if depth > 0 {
+            fields.push(field(2, "corecursive", FieldType::Message(test_all_types_proto3(depth - 1)), false));
+        }
+        FieldType::Message(message("protobuf_test_messages.proto3.TestAllTypesProto3.NestedMessage", fields))
+    };
+
+    let mut fields = Vec::new();
+
+    // Singular and repeated scalars: optional_* = 1.., repeated_* = 31..
+    for (i, (name, field_type)) in scalar_types().into_iter().enumerate() {
+        fields.push(field(1 + i as u32, &format!("optional{}", name), field_type.clone(), false));
+        fields.push(field(31 + i as u32, &format!("repeated{}", name), field_type, true));
+    }
+    // Packed and unpacked scalars (no string/bytes): packed_* = 75.., unpacked_* = 89..
+    for (i, (name, field_type)) in scalar_types().into_iter().take(13).enumerate() {
+        fields.push(field(75 + i as u32, &format!("packed{}", name), field_type.clone(), true));
+        fields.push(unpacked(field(89 + i as u32, &format!("unpacked{}", name), field_type, true)));
+    }
+
+    fields.extend([
+        field(18, "optionalNestedMessage", nested_message(), false),
+        field(19, "optionalForeignMessage", foreign_message(), false),
+        field(21, "optionalNestedEnum", FieldType::Enum, false),
+        field(22, "optionalForeignEnum", FieldType::Enum, false),
+        field(23, "optionalAliasedEnum", FieldType::Enum, false),
+        field(24, "optionalStringPiece", FieldType::String, false),
+        field(25, "optionalCord", FieldType::String, false),
+        field(48, "repeatedNestedMessage", nested_message(), true),
+        field(49, "repeatedForeignMessage", foreign_message(), true),
+        field(51, "repeatedNestedEnum", FieldType::Enum, true),
+        field(52, "repeatedForeignEnum", FieldType::Enum, true),
+        field(54, "repeatedStringPiece", FieldType::String, true),
+        field(55, "repeatedCord", FieldType::String, true),
+        field(88, "packedNestedEnum", FieldType::Enum, true),
+        unpacked(field(102, "unpackedNestedEnum", FieldType::Enum, true)),
+    ]);
+    if depth > 0 {
+        fields.push(field(27, "recursiveMessage", FieldType::Message(test_all_types_proto3(depth - 1)), false));
+    }
+
+    // Maps = 56..74
+    let same_key_value = [
+        ("Int32Int32", FieldType::Int32),
+        ("Int64Int64", FieldType::Int64),
+        ("Uint32Uint32", FieldType::Uint32),
+        ("Uint64Uint64", FieldType::Uint64),
+        ("Sint32Sint32", FieldType::Sint32),
+        ("Sint64Sint64", FieldType::Sint64),
+        ("Fixed32Fixed32", FieldType::Fixed32),
+        ("Fixed64Fixed64", FieldType::Fixed64),
+        ("Sfixed32Sfixed32", FieldType::Sfixed32),
+        ("Sfixed64Sfixed64", FieldType::Sfixed64),
+    ];
+    for (i, (name, field_type)) in same_key_value.into_iter().enumerate() {
+        fields.push(field(56 + i as u32, &format!("map{}", name), map_entry(field_type.clone(), field_type), true));
+    }
+    fields.extend([
+        field(66, "mapInt32Float", map_entry(FieldType::Int32, FieldType::Float), true),
+        field(67, "mapInt32Double", map_entry(FieldType::Int32, FieldType::Double), true),
+        field(68, "mapBoolBool", map_entry(FieldType::Bool, FieldType::Bool), true),
+        field(69, "mapStringString", map_entry(FieldType::String, FieldType::String), true),
+        field(70, "mapStringBytes", map_entry(FieldType::String, FieldType::Bytes), true),
+        field(71, "mapStringNestedMessage", map_entry(FieldType::String, nested_message()), true),
+        field(72, "mapStringForeignMessage", map_entry(FieldType::String, foreign_message()), true),
+        field(73, "mapStringNestedEnum", map_entry(FieldType::String, FieldType::Enum), true),
+        field(74, "mapStringForeignEnum", map_entry(FieldType::String, FieldType::Enum), true),
+    ]);
+
+    // oneof oneof_field = 111..120, declared as fields with presence rather than
+    // a oneof group so JSON output keeps the flat proto3 member keys
+    fields.extend([
+        with_presence(field(111, "oneofUint32", FieldType::Uint32, false)),
+        with_presence(field(112, "oneofNestedMessage", nested_message(), false)),
+        with_presence(field(113, "oneofString", FieldType::String, false)),
+        with_presence(field(114, "oneofBytes", FieldType::Bytes, false)),
+        with_presence(field(115, "oneofBool", FieldType::Bool, false)),
+        with_presence(field(116, "oneofUint64", FieldType::Uint64, false)),
+        with_presence(field(117, "oneofFloat", FieldType::Float, false)),
+        with_presence(field(118, "oneofDouble", FieldType::Double, false)),
+        with_presence(field(119, "oneofEnum", FieldType::Enum, false)),
+        with_presence(field(120, "oneofNullValue", FieldType::Enum, false)),
+    ]);
+
+    // Well-known types: wrappers = 201.. (repeated 211..), others = 301.. (repeated 311..)
+    let wrappers = [
+        ("Bool", WellKnownType::BoolValue),
+        ("Int32", WellKnownType::Int32Value),
+        ("Int64", WellKnownType::Int64Value),
+        ("Uint32", WellKnownType::UInt32Value),
+        ("Uint64", WellKnownType::UInt64Value),
+        ("Float", WellKnownType::FloatValue),
+        ("Double", WellKnownType::DoubleValue),
+        ("String", WellKnownType::StringValue),
+        ("Bytes", WellKnownType::BytesValue),
+    ];
+    for (i, (name, wrapper)) in wrappers.into_iter().enumerate() {
+        let wrapper = FieldType::WellKnown(wrapper);
+        fields.push(field(201 + i as u32, &format!("optional{}Wrapper", name), wrapper.clone(), false));
+        fields.push(field(211 + i as u32, &format!("repeated{}Wrapper", name), wrapper, true));
+    }
+    let well_known = FieldType::WellKnown;
+    fields.extend([
+        field(301, "optionalDuration", well_known(WellKnownType::Duration), false),
+        field(302, "optionalTimestamp", well_known(WellKnownType::Timestamp), false),
+        field(303, "optionalFieldMask", well_known(WellKnownType::FieldMask), false),
+        field(304, "optionalStruct", well_known(WellKnownType::Struct), false),
+        field(305, "optionalAny", well_known(WellKnownType::Any), false),
+        field(306, "optionalValue", well_known(WellKnownType::Value), false),
+        field(307, "optionalNullValue", FieldType::Enum, false),
+        field(311, "repeatedDuration", well_known(WellKnownType::Duration), true),
+        field(312, "repeatedTimestamp", well_known(WellKnownType::Timestamp), true),
+        field(313, "repeatedFieldmask", well_known(WellKnownType::FieldMask), true),
+        field(315, "repeatedAny", well_known(WellKnownType::Any), true),
+        field(316, "repeatedValue", well_known(WellKnownType::Value), true),
+        field(317, "repeatedListValue", well_known(WellKnownType::ListValue), true),
+        field(324, "repeatedStruct", well_known(WellKnownType::Struct), true),
+    ]);
+
+    // JSON name conversion fields = 401..418
+    let json_names = [
+        "fieldname1", "fieldName2", "FieldName3", "fieldName4", "field0name5", "field0Name6", "fieldName7",
+        "FieldName8", "fieldName9", "FieldName10", "FIELDNAME11", "FIELDName12", "FieldName13", "FieldName14",
+        "fieldName15", "fieldName16", "fieldName17", "FieldName18",
+    ];
+    for (i, name) in json_names.iter().enumerate() {
+        fields.push(field(401 + i as u32, name, FieldType::Int32, false));
+    }
+
+    fields.sort_by_key(|f| f.number);
+    message(TEST_ALL_TYPES_PROTO3, fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(payload: Payload, output: i32) -> Vec<u8> {
+        let request = ConformanceRequest {
+            payload: Some(payload),
+            requested_output_format: output,
+            message_type: TEST_ALL_TYPES_PROTO3.to_string(),
+            test_category: 0,
+        };
+        let bytes = request.encode_to_vec();
+        let mut framed = (bytes.len() as u32).to_le_bytes().to_vec();
+        framed.extend(bytes);
+        framed
+    }
+
+    fn responses(mut output: &[u8]) -> Vec<ConformanceResult> {
+        let mut results = Vec::new();
+        while !output.is_empty() {
+            let len = u32::from_le_bytes(output[..4].try_into().unwrap()) as usize;
+            let response = ConformanceResponse::decode(&output[4..4 + len]).unwrap();
+            results.push(response.result.unwrap());
+            output = &output[4 + len..];
+        }
+        results
+    }
+
+    #[test]
+    fn test_serve_protocol() {
+        // optional_int32 = 150, optional_string = "hi", optional_nested_message { a: 1 }
+        let binary = vec![0x08, 0x96, 0x01, 0x72, 0x02, b'h', b'i', 0x92, 0x01, 0x02, 0x08, 0x01];
+
+        let mut input = Vec::new();
+        input.extend(request(Payload::ProtobufPayload(binary.clone()), WIRE_FORMAT_PROTOBUF));
+        input.extend(request(Payload::ProtobufPayload(binary), WIRE_FORMAT_JSON));
+        input.extend(request(Payload::JsonPayload(r#"{"optionalSint64": "-3"}"#.to_string()), WIRE_FORMAT_PROTOBUF));
+        input.extend(request(Payload::ProtobufPayload(vec![0x08]), WIRE_FORMAT_PROTOBUF));
+        input.extend(request(Payload::TextPayload("optional_int32: 1".to_string()), WIRE_FORMAT_PROTOBUF));
+
+        let mut output = Vec::new();
+        serve(input.as_slice(), &mut output).unwrap();
+        let results = responses(&output);
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(
+            results[0],
+            ConformanceResult::ProtobufPayload(vec![0x08, 0x96, 0x01, 0x72, 0x02, b'h', b'i', 0x92, 0x01, 0x02, 0x08, 0x01])
+        );
+        match &results[1] {
+            ConformanceResult::JsonPayload(json) => {
+                let value: Value = serde_json::from_str(json).unwrap();
+                assert_eq!(value["optionalInt32"], 150);
+                assert_eq!(value["optionalString"], "hi");
+                assert_eq!(value["optionalNestedMessage"]["a"], 1);
+            }
+            other => panic!("expected JSON, got {:?}", other),
+        }
+        assert_eq!(results[2], ConformanceResult::ProtobufPayload(vec![0x30, 0x05]));
+        assert!(matches!(results[3], ConformanceResult::ParseError(_)));
+        assert!(matches!(results[4], ConformanceResult::Skipped(_)));
+    }
+
+    #[test]
+    fn test_well_known_json() {
+        let schema = Schema::from(test_all_types_proto3(1));
+        let json = r#"{
+            "optionalTimestamp": "2017-01-15T02:30:15.01+01:00",
+            "optionalDuration": "-1.5s",
+            "optionalInt64Wrapper": "5",
+            "optionalFieldMask": "fooBar,baz",
+            "optionalValue": null,
+            "optionalStruct": {"a": [1, "x", {"b": true}]}
+        }"#;
+        let request = ConformanceRequest {
+            payload: Some(Payload::JsonPayload(json.to_string())),
+            message_type: TEST_ALL_TYPES_PROTO3.to_string(),
+            requested_output_format: WIRE_FORMAT_JSON,
+            ..Default::default()
+        };
+        let output = match handle(&schema, &request).result {
+            Some(ConformanceResult::JsonPayload(output)) => serde_json::from_str::<Value>(&output).unwrap(),
+            other => panic!("expected JSON, got {:?}", other),
+        };
+        assert_eq!(output["optionalTimestamp"], "2017-01-15T01:30:15.010Z");
+        assert_eq!(output["optionalDuration"], "-1.500s");
+        assert_eq!(output["optionalInt64Wrapper"], "5");
+        assert_eq!(output["optionalFieldMask"], "fooBar,baz");
+        assert_eq!(output.get("optionalValue"), Some(&Value::Null));
+        assert_eq!(output["optionalStruct"], serde_json::json!({"a": [1.0, "x", {"b": true}]}));
+    }
+
+    #[test]
+    fn test_failure_set_and_unknown_types() {
+        let schema = Schema::from(test_all_types_proto3(1));
+        let failure_set = ConformanceRequest {
+            message_type: FAILURE_SET.to_string(),
+            requested_output_format: WIRE_FORMAT_PROTOBUF,
+            ..Default::default()
+        };
+        assert_eq!(handle(&schema, &failure_set).result, Some(ConformanceResult::ProtobufPayload(Vec::new())));
+
+        let proto2 = ConformanceRequest {
+            payload: Some(Payload::ProtobufPayload(Vec::new())),
+            message_type: "protobuf_test_messages.proto2.TestAllTypesProto2".to_string(),
+            requested_output_format: WIRE_FORMAT_PROTOBUF,
+            ..Default::default()
+        };
+        assert!(matches!(handle(&schema, &proto2).result, Some(ConformanceResult::Skipped(_))));
+    }
+
+    #[test]
+    fn test_vendored_schema_numbers() {
+        let schema = test_all_types_proto3(0);
+        let number = |name: &str| schema.fields.iter().find(|f| f.name == name).map(|f| f.number);
+        assert_eq!(number("optionalBytes"), Some(15));
+        assert_eq!(number("repeatedBytes"), Some(45));
+        assert_eq!(number("packedBool"), Some(87));
+        assert_eq!(number("unpackedBool"), Some(101));
+        assert_eq!(number("mapSfixed64Sfixed64"), Some(65));
+        assert_eq!(number("repeatedBytesWrapper"), Some(219));
+        assert_eq!(number("FieldName18"), Some(418));
+        assert_eq!(number("recursiveMessage"), None);
+    }
+}
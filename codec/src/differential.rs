@@ -0,0 +1,850 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Differential tests against prost
+//!
+//! Random messages for a few test schemas are encoded by both prost and
+//! `encode_message`. The bytes must be identical (both encoders write fields
+//! in number order and pack repeated numerics), and each side must decode the
+//! other's output back to the same value. Generation is seeded, so a failure
+//! reports the seed needed to reproduce it.
+//!
+//! Each test runs seeds `CODEC_DIFF_SEED..CODEC_DIFF_SEED + CODEC_DIFF_CASES`
+//! (default `0..300`). Rerun a failure with `CODEC_DIFF_SEED=<seed>
+//! CODEC_DIFF_CASES=1`, or search further with a larger count or another start.
+
+use prost::Message;
+use serde_json::{Map, Number, Value};
+
+use crate::well_known::{format_duration, format_timestamp};
+use crate::{base64_encode, decode_message, encode_message, parse_schema, ExtensionRegistry, MessageDescriptor};
+
+/// Seeds run when `CODEC_DIFF_CASES` is not set
+const DEFAULT_CASES: u64 = 300;
+
+/// Seeds for each test, from `CODEC_DIFF_SEED` and `CODEC_DIFF_CASES`
+fn seeds() -> std::ops::Range<u64> {
+    let var = |name: &str, default: u64| match std::env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| panic!("{} must be a number, got {:?}", name, value)),
+        Err(_) => default,
+    };
+    let start = var("CODEC_DIFF_SEED", 0);
+    start..start.saturating_add(var("CODEC_DIFF_CASES", DEFAULT_CASES))
+}
+
+// ============================================================================
+// Test schemas
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+enum Color {
+    Unspecified = 0,
+    Red = 1,
+    Blue = 2,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Scalars {
+    #[prost(int32, tag = "1")]
+    int32: i32,
+    #[prost(int64, tag = "2")]
+    int64: i64,
+    #[prost(uint32, tag = "3")]
+    uint32: u32,
+    #[prost(uint64, tag = "4")]
+    uint64: u64,
+    #[prost(sint32, tag = "5")]
+    sint32: i32,
+    #[prost(sint64, tag = "6")]
+    sint64: i64,
+    #[prost(fixed32, tag = "7")]
+    fixed32: u32,
+    #[prost(fixed64, tag = "8")]
+    fixed64: u64,
+    #[prost(sfixed32, tag = "9")]
+    sfixed32: i32,
+    #[prost(sfixed64, tag = "10")]
+    sfixed64: i64,
+    #[prost(float, tag = "11")]
+    float: f32,
+    #[prost(double, tag = "12")]
+    double: f64,
+    #[prost(bool, tag = "13")]
+    bool: bool,
+    #[prost(string, tag = "14")]
+    string: String,
+    #[prost(bytes = "vec", tag = "15")]
+    bytes: Vec<u8>,
+    #[prost(enumeration = "Color", tag = "16")]
+    color: i32,
+}
+
+const SCALARS_SCHEMA: &str = r#"[
+    {"n": 1, "name": "int32", "type": "int32"},
+    {"n": 2, "name": "int64", "type": "int64"},
+    {"n": 3, "name": "uint32", "type": "uint32"},
+    {"n": 4, "name": "uint64", "type": "uint64"},
+    {"n": 5, "name": "sint32", "type": "sint32"},
+    {"n": 6, "name": "sint64", "type": "sint64"},
+    {"n": 7, "name": "fixed32", "type": "fixed32"},
+    {"n": 8, "name": "fixed64", "type": "fixed64"},
+    {"n": 9, "name": "sfixed32", "type": "sfixed32"},
+    {"n": 10, "name": "sfixed64", "type": "sfixed64"},
+    {"n": 11, "name": "float", "type": "float"},
+    {"n": 12, "name": "double", "type": "double"},
+    {"n": 13, "name": "bool", "type": "bool"},
+    {"n": 14, "name": "string", "type": "string"},
+    {"n": 15, "name": "bytes", "type": "bytes"},
+    {"n": 16, "name": "color", "type": "enum"}
+]"#;
+
+/// Proto3 `optional` fields (explicit presence)
+#[derive(Clone, PartialEq, Message)]
+struct Optionals {
+    #[prost(int32, optional, tag = "1")]
+    int32: Option<i32>,
+    #[prost(uint64, optional, tag = "2")]
+    uint64: Option<u64>,
+    #[prost(sint64, optional, tag = "3")]
+    sint64: Option<i64>,
+    #[prost(sfixed32, optional, tag = "4")]
+    sfixed32: Option<i32>,
+    #[prost(double, optional, tag = "5")]
+    double: Option<f64>,
+    #[prost(bool, optional, tag = "6")]
+    bool: Option<bool>,
+    #[prost(string, optional, tag = "7")]
+    string: Option<String>,
+    #[prost(bytes = "vec", optional, tag = "8")]
+    bytes: Option<Vec<u8>>,
+}
+
+const OPTIONALS_SCHEMA: &str = r#"[
+    {"n": 1, "name": "int32", "type": "int32", "optional": true},
+    {"n": 2, "name": "uint64", "type": "uint64", "optional": true},
+    {"n": 3, "name": "sint64", "type": "sint64", "optional": true},
+    {"n": 4, "name": "sfixed32", "type": "sfixed32", "optional": true},
+    {"n": 5, "name": "double", "type": "double", "optional": true},
+    {"n": 6, "name": "bool", "type": "bool", "optional": true},
+    {"n": 7, "name": "string", "type": "string", "optional": true},
+    {"n": 8, "name": "bytes", "type": "bytes", "optional": true}
+]"#;
+
+#[derive(Clone, PartialEq, Message)]
+struct Repeated {
+    #[prost(int32, repeated, tag = "1")]
+    int32: Vec<i32>,
+    #[prost(int64, repeated, tag = "2")]
+    int64: Vec<i64>,
+    #[prost(uint32, repeated, tag = "3")]
+    uint32: Vec<u32>,
+    #[prost(uint64, repeated, tag = "4")]
+    uint64: Vec<u64>,
+    #[prost(sint32, repeated, tag = "5")]
+    sint32: Vec<i32>,
+    #[prost(sint64, repeated, tag = "6")]
+    sint64: Vec<i64>,
+    #[prost(fixed32, repeated, tag = "7")]
+    fixed32: Vec<u32>,
+    #[prost(fixed64, repeated, tag = "8")]
+    fixed64: Vec<u64>,
+    #[prost(sfixed32, repeated, tag = "9")]
+    sfixed32: Vec<i32>,
+    #[prost(sfixed64, repeated, tag = "10")]
+    sfixed64: Vec<i64>,
+    #[prost(float, repeated, tag = "11")]
+    float: Vec<f32>,
+    #[prost(double, repeated, tag = "12")]
+    double: Vec<f64>,
+    #[prost(bool, repeated, tag = "13")]
+    bool: Vec<bool>,
+    #[prost(string, repeated, tag = "14")]
+    string: Vec<String>,
+    #[prost(bytes = "vec", repeated, tag = "15")]
+    bytes: Vec<Vec<u8>>,
+    #[prost(enumeration = "Color", repeated, tag = "16")]
+    color: Vec<i32>,
+    #[prost(int32, repeated, packed = "false", tag = "17")]
+    unpacked_int32: Vec<i32>,
+    #[prost(sint64, repeated, packed = "false", tag = "18")]
+    unpacked_sint64: Vec<i64>,
+    #[prost(double, repeated, packed = "false", tag = "19")]
+    unpacked_double: Vec<f64>,
+}
+
+const REPEATED_SCHEMA: &str = r#"[
+    {"n": 1, "name": "int32", "type": "int32", "repeated": true},
+    {"n": 2, "name": "int64", "type": "int64", "repeated": true},
+    {"n": 3, "name": "uint32", "type": "uint32", "repeated": true},
+    {"n": 4, "name": "uint64", "type": "uint64", "repeated": true},
+    {"n": 5, "name": "sint32", "type": "sint32", "repeated": true},
+    {"n": 6, "name": "sint64", "type": "sint64", "repeated": true},
+    {"n": 7, "name": "fixed32", "type": "fixed32", "repeated": true},
+    {"n": 8, "name": "fixed64", "type": "fixed64", "repeated": true},
+    {"n": 9, "name": "sfixed32", "type": "sfixed32", "repeated": true},
+    {"n": 10, "name": "sfixed64", "type": "sfixed64", "repeated": true},
+    {"n": 11, "name": "float", "type": "float", "repeated": true},
+    {"n": 12, "name": "double", "type": "double", "repeated": true},
+    {"n": 13, "name": "bool", "type": "bool", "repeated": true},
+    {"n": 14, "name": "string", "type": "string", "repeated": true},
+    {"n": 15, "name": "bytes", "type": "bytes", "repeated": true},
+    {"n": 16, "name": "color", "type": "enum", "repeated": true},
+    {"n": 17, "name": "unpackedInt32", "type": "int32", "repeated": true, "packed": false},
+    {"n": 18, "name": "unpackedSint64", "type": "sint64", "repeated": true, "packed": false},
+    {"n": 19, "name": "unpackedDouble", "type": "double", "repeated": true, "packed": false}
+]"#;
+
+#[derive(Clone, PartialEq, Message)]
+struct Nested {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, optional, tag = "2")]
+    scalars: Option<Scalars>,
+    #[prost(message, repeated, tag = "3")]
+    items: Vec<Optionals>,
+    #[prost(message, optional, tag = "4")]
+    child: Option<Box<Nested>>,
+}
+
+fn nested_schema(depth: usize) -> String {
+    let child = if depth == 0 {
+        String::new()
+    } else {
+        format!(
+            r#", {{"n": 4, "name": "child", "type": "message", "fields": {}}}"#,
+            nested_schema(depth - 1)
+        )
+    };
+    format!(
+        r#"[
+            {{"n": 1, "name": "name", "type": "string"}},
+            {{"n": 2, "name": "scalars", "type": "message", "fields": {}}},
+            {{"n": 3, "name": "items", "type": "message", "repeated": true, "fields": {}}}{}
+        ]"#,
+        SCALARS_SCHEMA, OPTIONALS_SCHEMA, child
+    )
+}
+
+/// Oneof members, including defaults that must still be written
+#[derive(Clone, PartialEq, Message)]
+struct Choice {
+    #[prost(string, tag = "1")]
+    id: String,
+    #[prost(oneof = "Kind", tags = "2, 3, 4, 5")]
+    kind: Option<Kind>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum Kind {
+    #[prost(message, tag = "2")]
+    Scalars(Scalars),
+    #[prost(double, tag = "3")]
+    Side(f64),
+    #[prost(sint64, tag = "4")]
+    Offset(i64),
+    #[prost(string, tag = "5")]
+    Label(String),
+}
+
+fn choice_schema() -> String {
+    format!(
+        r#"[
+            {{"n": 1, "name": "id", "type": "string"}},
+            {{"n": 2, "name": "scalars", "type": "message", "oneof": "kind", "fields": {}}},
+            {{"n": 3, "name": "side", "type": "double", "oneof": "kind"}},
+            {{"n": 4, "name": "offset", "type": "sint64", "oneof": "kind"}},
+            {{"n": 5, "name": "label", "type": "string", "oneof": "kind"}}
+        ]"#,
+        SCALARS_SCHEMA
+    )
+}
+
+/// Well-known types, in their proto3 JSON form on the codec side
+#[derive(Clone, PartialEq, Message)]
+struct WellKnown {
+    #[prost(message, optional, tag = "1")]
+    timestamp: Option<prost_types::Timestamp>,
+    #[prost(message, optional, tag = "2")]
+    duration: Option<prost_types::Duration>,
+    #[prost(message, optional, tag = "3")]
+    int64_value: Option<i64>,
+    #[prost(message, optional, tag = "4")]
+    double_value: Option<f64>,
+    #[prost(message, optional, tag = "5")]
+    bytes_value: Option<Vec<u8>>,
+    #[prost(message, optional, tag = "6")]
+    structure: Option<prost_types::Struct>,
+    #[prost(message, optional, tag = "7")]
+    value: Option<prost_types::Value>,
+    #[prost(message, repeated, tag = "8")]
+    values: Vec<prost_types::Value>,
+    #[prost(message, optional, tag = "9")]
+    field_mask: Option<prost_types::FieldMask>,
+    #[prost(message, optional, tag = "10")]
+    any: Option<prost_types::Any>,
+}
+
+const WELL_KNOWN_SCHEMA: &str = r#"[
+    {"n": 1, "name": "timestamp", "type": "message", "typeName": "google.protobuf.Timestamp"},
+    {"n": 2, "name": "duration", "type": "message", "typeName": "google.protobuf.Duration"},
+    {"n": 3, "name": "int64Value", "type": "message", "typeName": "google.protobuf.Int64Value"},
+    {"n": 4, "name": "doubleValue", "type": "message", "typeName": "google.protobuf.DoubleValue"},
+    {"n": 5, "name": "bytesValue", "type": "message", "typeName": "google.protobuf.BytesValue"},
+    {"n": 6, "name": "structure", "type": "message", "typeName": "google.protobuf.Struct"},
+    {"n": 7, "name": "value", "type": "message", "typeName": "google.protobuf.Value"},
+    {"n": 8, "name": "values", "type": "message", "typeName": "google.protobuf.Value", "repeated": true},
+    {"n": 9, "name": "fieldMask", "type": "message", "typeName": "google.protobuf.FieldMask"},
+    {"n": 10, "name": "any", "type": "message", "typeName": "google.protobuf.Any"}
+]"#;
+
+// ============================================================================
+// Random values, biased towards edge cases
+// ============================================================================
+
+/// xorshift64* generator, so failures can be reproduced from the seed
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn chance(&mut self, one_in: u64) -> bool {
+        self.below(one_in) == 0
+    }
+
+    /// Mostly small magnitudes, sometimes edge values, sometimes any bits
+    fn bits(&mut self, edges: &[u64]) -> u64 {
+        match self.below(4) {
+            0 => edges[self.below(edges.len() as u64) as usize],
+            1 => self.next(),
+            _ => self.below(300).wrapping_sub(150),
+        }
+    }
+
+    fn i32(&mut self) -> i32 {
+        self.bits(&[0, 1, -1i64 as u64, i32::MIN as u64, i32::MAX as u64]) as i32
+    }
+
+    fn u32(&mut self) -> u32 {
+        self.bits(&[0, 1, 127, 128, u32::MAX as u64]) as u32
+    }
+
+    fn i64(&mut self) -> i64 {
+        self.bits(&[0, 1, -1i64 as u64, i64::MIN as u64, i64::MAX as u64]) as i64
+    }
+
+    fn u64(&mut self) -> u64 {
+        self.bits(&[0, 1, 127, 128, u64::MAX])
+    }
+
+    fn f32(&mut self) -> f32 {
+        let edges = [0.0, -0.0, 1.0, -1.5, f32::MIN, f32::MAX, f32::MIN_POSITIVE, f32::EPSILON];
+        if self.chance(3) {
+            return edges[self.below(edges.len() as u64) as usize];
+        }
+        let n = f32::from_bits(self.next() as u32);
+        if n.is_finite() { n } else { 0.5 }
+    }
+
+    fn f64(&mut self) -> f64 {
+        let edges = [0.0, -0.0, 1.0, -1.5, f64::MIN, f64::MAX, f64::MIN_POSITIVE, 1e-300];
+        if self.chance(3) {
+            return edges[self.below(edges.len() as u64) as usize];
+        }
+        let n = f64::from_bits(self.next());
+        if n.is_finite() { n } else { 0.5 }
+    }
+
+    fn bool(&mut self) -> bool {
+        self.chance(2)
+    }
+
+    fn string(&mut self) -> String {
+        const CHARS: &[char] = &['a', 'Z', '0', ' ', '"', '\\', '\n', '\u{0}', 'é', '€', '😀'];
+        (0..self.below(8)).map(|_| CHARS[self.below(CHARS.len() as u64) as usize]).collect()
+    }
+
+    fn bytes(&mut self) -> Vec<u8> {
+        (0..self.below(8)).map(|_| self.next() as u8).collect()
+    }
+
+    fn enum_value(&mut self) -> i32 {
+        // Open enums keep unrecognized numbers
+        if self.chance(4) { self.i32() } else { self.below(3) as i32 }
+    }
+
+    fn vec<T>(&mut self, mut item: impl FnMut(&mut Self) -> T) -> Vec<T> {
+        (0..self.below(5)).map(|_| item(self)).collect()
+    }
+
+    fn option<T>(&mut self, item: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if self.chance(2) { Some(item(self)) } else { None }
+    }
+}
+
+fn random_scalars(rng: &mut Rng) -> Scalars {
+    Scalars {
+        int32: rng.i32(),
+        int64: rng.i64(),
+        uint32: rng.u32(),
+        uint64: rng.u64(),
+        sint32: rng.i32(),
+        sint64: rng.i64(),
+        fixed32: rng.u32(),
+        fixed64: rng.u64(),
+        sfixed32: rng.i32(),
+        sfixed64: rng.i64(),
+        float: rng.f32(),
+        double: rng.f64(),
+        bool: rng.bool(),
+        string: rng.string(),
+        bytes: rng.bytes(),
+        color: rng.enum_value(),
+    }
+}
+
+fn random_optionals(rng: &mut Rng) -> Optionals {
+    Optionals {
+        int32: rng.option(Rng::i32),
+        uint64: rng.option(Rng::u64),
+        sint64: rng.option(Rng::i64),
+        sfixed32: rng.option(Rng::i32),
+        double: rng.option(Rng::f64),
+        bool: rng.option(Rng::bool),
+        string: rng.option(Rng::string),
+        bytes: rng.option(Rng::bytes),
+    }
+}
+
+fn random_repeated(rng: &mut Rng) -> Repeated {
+    Repeated {
+        int32: rng.vec(Rng::i32),
+        int64: rng.vec(Rng::i64),
+        uint32: rng.vec(Rng::u32),
+        uint64: rng.vec(Rng::u64),
+        sint32: rng.vec(Rng::i32),
+        sint64: rng.vec(Rng::i64),
+        fixed32: rng.vec(Rng::u32),
+        fixed64: rng.vec(Rng::u64),
+        sfixed32: rng.vec(Rng::i32),
+        sfixed64: rng.vec(Rng::i64),
+        float: rng.vec(Rng::f32),
+        double: rng.vec(Rng::f64),
+        bool: rng.vec(Rng::bool),
+        string: rng.vec(Rng::string),
+        bytes: rng.vec(Rng::bytes),
+        color: rng.vec(Rng::enum_value),
+        unpacked_int32: rng.vec(Rng::i32),
+        unpacked_sint64: rng.vec(Rng::i64),
+        unpacked_double: rng.vec(Rng::f64),
+    }
+}
+
+fn random_nested(rng: &mut Rng, depth: usize) -> Nested {
+    Nested {
+        name: rng.string(),
+        scalars: rng.option(random_scalars),
+        items: rng.vec(random_optionals),
+        child: if depth > 0 { rng.option(|rng| Box::new(random_nested(rng, depth - 1))) } else { None },
+    }
+}
+
+fn random_choice(rng: &mut Rng) -> Choice {
+    let kind = match rng.below(5) {
+        0 => Some(Kind::Scalars(random_scalars(rng))),
+        1 => Some(Kind::Side(rng.f64())),
+        2 => Some(Kind::Offset(rng.i64())),
+        3 => Some(Kind::Label(rng.string())),
+        _ => None,
+    };
+    Choice { id: rng.string(), kind }
+}
+
+/// Nanoseconds printed with 0, 3, 6 or 9 digits
+fn random_nanos(rng: &mut Rng) -> i32 {
+    let nanos = rng.below(1_000_000_000) as i32;
+    match rng.below(4) {
+        0 => 0,
+        1 => nanos / 1_000_000 * 1_000_000,
+        2 => nanos / 1_000 * 1_000,
+        _ => nanos,
+    }
+}
+
+fn random_value(rng: &mut Rng, depth: usize) -> prost_types::Value {
+    use prost_types::value::Kind;
+    let kind = match rng.below(if depth > 0 { 6 } else { 4 }) {
+        0 => Kind::NullValue(0),
+        1 => Kind::NumberValue(rng.f64()),
+        2 => Kind::StringValue(rng.string()),
+        3 => Kind::BoolValue(rng.bool()),
+        4 => Kind::StructValue(random_struct(rng, depth - 1)),
+        _ => Kind::ListValue(prost_types::ListValue { values: rng.vec(|rng| random_value(rng, depth - 1)) }),
+    };
+    prost_types::Value { kind: Some(kind) }
+}
+
+fn random_struct(rng: &mut Rng, depth: usize) -> prost_types::Struct {
+    let fields = rng.vec(|rng| (rng.string(), random_value(rng, depth)));
+    prost_types::Struct { fields: fields.into_iter().collect() }
+}
+
+fn random_well_known(rng: &mut Rng) -> WellKnown {
+    const PATHS: &[&str] = &["id", "display_name", "user.address.post_code", "a1_b2"];
+    WellKnown {
+        timestamp: rng.option(|rng| prost_types::Timestamp {
+            seconds: (rng.below(253_402_300_800 + 62_135_596_800) as i64) - 62_135_596_800,
+            nanos: random_nanos(rng),
+        }),
+        duration: rng.option(|rng| {
+            let seconds = rng.below(315_576_000_001) as i64;
+            let nanos = random_nanos(rng);
+            if rng.bool() {
+                prost_types::Duration { seconds, nanos }
+            } else {
+                prost_types::Duration { seconds: -seconds, nanos: -nanos }
+            }
+        }),
+        int64_value: rng.option(Rng::i64),
+        double_value: rng.option(Rng::f64),
+        bytes_value: rng.option(Rng::bytes),
+        structure: rng.option(|rng| random_struct(rng, 2)),
+        value: rng.option(|rng| random_value(rng, 2)),
+        values: rng.vec(|rng| random_value(rng, 1)),
+        field_mask: rng.option(|rng| prost_types::FieldMask {
+            paths: rng.vec(|rng| PATHS[rng.below(PATHS.len() as u64) as usize].to_string()),
+        }),
+        any: rng.option(|rng| {
+            if rng.bool() {
+                let duration = prost_types::Duration { seconds: rng.below(1000) as i64, nanos: random_nanos(rng) };
+                prost_types::Any {
+                    type_url: "type.googleapis.com/google.protobuf.Duration".to_string(),
+                    value: duration.encode_to_vec(),
+                }
+            } else {
+                prost_types::Any { type_url: "type.googleapis.com/example.Opaque".to_string(), value: rng.bytes() }
+            }
+        }),
+    }
+}
+
+// ============================================================================
+// Expected JSON (the codec's intermediate format)
+// ============================================================================
+
+/// Builds the JSON object for a message; with `defaults` false, fields
+/// without presence that hold their default are left out, as the codec
+/// decodes them
+struct JsonObject {
+    map: Map<String, Value>,
+    defaults: bool,
+}
+
+impl JsonObject {
+    fn new(defaults: bool) -> Self {
+        Self { map: Map::new(), defaults }
+    }
+
+    fn scalar(mut self, name: &str, value: Value, is_default: bool) -> Self {
+        if self.defaults || !is_default {
+            self.map.insert(name.to_string(), value);
+        }
+        self
+    }
+
+    fn field(mut self, name: &str, value: Value) -> Self {
+        self.map.insert(name.to_string(), value);
+        self
+    }
+
+    fn optional<T>(mut self, name: &str, value: Option<T>, to_json: impl Fn(T) -> Value) -> Self {
+        if let Some(v) = value {
+            self.map.insert(name.to_string(), to_json(v));
+        }
+        self
+    }
+
+    fn repeated<T>(mut self, name: &str, values: &[T], to_json: impl Fn(&T) -> Value) -> Self {
+        self.map.insert(name.to_string(), Value::Array(values.iter().map(to_json).collect()));
+        self
+    }
+
+    fn build(self) -> Value {
+        Value::Object(self.map)
+    }
+}
+
+fn number(n: impl Into<Number>) -> Value {
+    Value::Number(n.into())
+}
+
+/// 64-bit integers are strings in proto3 JSON
+fn int64(n: impl ToString) -> Value {
+    Value::String(n.to_string())
+}
+
+fn float(n: f64) -> Value {
+    Value::Number(Number::from_f64(n).unwrap())
+}
+
+fn bytes(b: &[u8]) -> Value {
+    Value::String(base64_encode(b))
+}
+
+fn scalars_json(m: &Scalars, defaults: bool) -> Value {
+    JsonObject::new(defaults)
+        .scalar("int32", number(m.int32), m.int32 == 0)
+        .scalar("int64", int64(m.int64), m.int64 == 0)
+        .scalar("uint32", number(m.uint32), m.uint32 == 0)
+        .scalar("uint64", int64(m.uint64), m.uint64 == 0)
+        .scalar("sint32", number(m.sint32), m.sint32 == 0)
+        .scalar("sint64", int64(m.sint64), m.sint64 == 0)
+        .scalar("fixed32", number(m.fixed32), m.fixed32 == 0)
+        .scalar("fixed64", int64(m.fixed64), m.fixed64 == 0)
+        .scalar("sfixed32", number(m.sfixed32), m.sfixed32 == 0)
+        .scalar("sfixed64", int64(m.sfixed64), m.sfixed64 == 0)
+        .scalar("float", float(m.float as f64), m.float == 0.0)
+        .scalar("double", float(m.double), m.double == 0.0)
+        .scalar("bool", Value::Bool(m.bool), !m.bool)
+        .scalar("string", Value::String(m.string.clone()), m.string.is_empty())
+        .scalar("bytes", bytes(&m.bytes), m.bytes.is_empty())
+        .scalar("color", number(m.color), m.color == 0)
+        .build()
+}
+
+fn optionals_json(m: &Optionals) -> Value {
+    JsonObject::new(false)
+        .optional("int32", m.int32, number)
+        .optional("uint64", m.uint64, int64)
+        .optional("sint64", m.sint64, int64)
+        .optional("sfixed32", m.sfixed32, number)
+        .optional("double", m.double, float)
+        .optional("bool", m.bool, Value::Bool)
+        .optional("string", m.string.clone(), Value::String)
+        .optional("bytes", m.bytes.as_deref(), bytes)
+        .build()
+}
+
+fn repeated_json(m: &Repeated) -> Value {
+    JsonObject::new(false)
+        .repeated("int32", &m.int32, |n| number(*n))
+        .repeated("int64", &m.int64, |n| int64(n))
+        .repeated("uint32", &m.uint32, |n| number(*n))
+        .repeated("uint64", &m.uint64, |n| int64(n))
+        .repeated("sint32", &m.sint32, |n| number(*n))
+        .repeated("sint64", &m.sint64, |n| int64(n))
+        .repeated("fixed32", &m.fixed32, |n| number(*n))
+        .repeated("fixed64", &m.fixed64, |n| int64(n))
+        .repeated("sfixed32", &m.sfixed32, |n| number(*n))
+        .repeated("sfixed64", &m.sfixed64, |n| int64(n))
+        .repeated("float", &m.float, |n| float(*n as f64))
+        .repeated("double", &m.double, |n| float(*n))
+        .repeated("bool", &m.bool, |b| Value::Bool(*b))
+        .repeated("string", &m.string, |s| Value::String(s.clone()))
+        .repeated("bytes", &m.bytes, |b| bytes(b))
+        .repeated("color", &m.color, |n| number(*n))
+        .repeated("unpackedInt32", &m.unpacked_int32, |n| number(*n))
+        .repeated("unpackedSint64", &m.unpacked_sint64, |n| int64(n))
+        .repeated("unpackedDouble", &m.unpacked_double, |n| float(*n))
+        .build()
+}
+
+fn nested_json(m: &Nested, defaults: bool, depth: usize) -> Value {
+    let mut object = JsonObject::new(defaults)
+        .scalar("name", Value::String(m.name.clone()), m.name.is_empty())
+        .optional("scalars", m.scalars.as_ref(), |s| scalars_json(s, defaults))
+        .repeated("items", &m.items, optionals_json);
+    if depth > 0 {
+        object = object.optional("child", m.child.as_deref(), |c| nested_json(c, defaults, depth - 1));
+    }
+    object.build()
+}
+
+/// `variants` selects the variant shape encode also accepts (`"kind": {"TAG": ..., "_0": ...}`)
+/// over the flat member keys decode writes
+fn choice_json(m: &Choice, variants: bool) -> Value {
+    let member = match &m.kind {
+        Some(Kind::Scalars(s)) => Some(("scalars", scalars_json(s, false))),
+        Some(Kind::Side(n)) => Some(("side", float(*n))),
+        Some(Kind::Offset(n)) => Some(("offset", int64(n))),
+        Some(Kind::Label(s)) => Some(("label", Value::String(s.clone()))),
+        None => None,
+    };
+    let mut object = JsonObject::new(false).scalar("id", Value::String(m.id.clone()), m.id.is_empty());
+    if let Some((name, value)) = member {
+        if variants {
+            let variant = serde_json::json!({"TAG": crate::capitalize(name), "_0": value});
+            object = object.field("kind", variant);
+        } else {
+            object = object.field(name, value);
+        }
+    }
+    object.build()
+}
+
+fn value_json(v: &prost_types::Value) -> Value {
+    use prost_types::value::Kind;
+    match v.kind.as_ref().unwrap() {
+        Kind::NullValue(_) => Value::Null,
+        Kind::NumberValue(n) => float(*n),
+        Kind::StringValue(s) => Value::String(s.clone()),
+        Kind::BoolValue(b) => Value::Bool(*b),
+        Kind::StructValue(s) => struct_json(s),
+        Kind::ListValue(l) => Value::Array(l.values.iter().map(value_json).collect()),
+    }
+}
+
+fn struct_json(s: &prost_types::Struct) -> Value {
+    Value::Object(s.fields.iter().map(|(k, v)| (k.clone(), value_json(v))).collect())
+}
+
+fn well_known_json(m: &WellKnown) -> Value {
+    let camel_case = |path: &String| {
+        let mut words = path.split('_');
+        let first = words.next().unwrap_or("").to_string();
+        words.fold(first, |acc, w| acc + &crate::capitalize(w))
+    };
+    JsonObject::new(false)
+        .optional("timestamp", m.timestamp.as_ref(), |t| Value::String(format_timestamp(t.seconds, t.nanos).unwrap()))
+        .optional("duration", m.duration.as_ref(), |d| Value::String(format_duration(d.seconds, d.nanos).unwrap()))
+        .optional("int64Value", m.int64_value, int64)
+        .optional("doubleValue", m.double_value, float)
+        .optional("bytesValue", m.bytes_value.as_deref(), bytes)
+        .optional("structure", m.structure.as_ref(), struct_json)
+        .optional("value", m.value.as_ref(), value_json)
+        .repeated("values", &m.values, value_json)
+        .optional("fieldMask", m.field_mask.as_ref(), |f| {
+            Value::String(f.paths.iter().map(camel_case).collect::<Vec<_>>().join(","))
+        })
+        .optional("any", m.any.as_ref(), |a| {
+            let mut any = Map::new();
+            any.insert("@type".to_string(), Value::String(a.type_url.clone()));
+            if a.type_url.ends_with("google.protobuf.Duration") {
+                let d = prost_types::Duration::decode(a.value.as_slice()).unwrap();
+                any.insert("value".to_string(), Value::String(format_duration(d.seconds, d.nanos).unwrap()));
+            } else if !a.value.is_empty() {
+                any.insert(crate::UNKNOWN_FIELDS_KEY.to_string(), bytes(&a.value));
+            }
+            Value::Object(any)
+        })
+        .build()
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Encode `value` with prost and the codec and compare both ways
+///
+/// `full` is the codec input with every field set, `expected` what the codec
+/// should decode from prost's bytes (defaults of fields without presence left
+/// out).
+fn check<M: Message + Default + PartialEq + std::fmt::Debug>(
+    seed: u64,
+    schema: &MessageDescriptor,
+    value: &M,
+    full: &Value,
+    expected: &Value,
+) {
+    let registry = ExtensionRegistry::new();
+    let prost_bytes = value.encode_to_vec();
+
+    let codec_bytes = encode_message(schema, full.as_object().unwrap(), &registry)
+        .unwrap_or_else(|e| panic!("seed {}: encode failed: {}\n{}", seed, e, full));
+    assert_eq!(codec_bytes, prost_bytes, "seed {}: encoded bytes differ for\n{}", seed, full);
+
+    let decoded = decode_message(schema, &prost_bytes, &registry)
+        .unwrap_or_else(|e| panic!("seed {}: decode failed: {}\n{:?}", seed, e, value));
+    assert_eq!(&Value::Object(decoded), expected, "seed {}: decoded JSON differs", seed);
+
+    let cross = M::decode(codec_bytes.as_slice())
+        .unwrap_or_else(|e| panic!("seed {}: prost rejected codec output: {}", seed, e));
+    assert_eq!(&cross, value, "seed {}: prost decoded a different value", seed);
+}
+
+#[test]
+fn differential_scalars() {
+    let schema = parse_schema(SCALARS_SCHEMA).unwrap();
+    for seed in seeds() {
+        let value = random_scalars(&mut Rng::new(seed));
+        check(seed, &schema, &value, &scalars_json(&value, true), &scalars_json(&value, false));
+    }
+}
+
+#[test]
+fn differential_optionals() {
+    let schema = parse_schema(OPTIONALS_SCHEMA).unwrap();
+    for seed in seeds() {
+        let value = random_optionals(&mut Rng::new(seed));
+        let json = optionals_json(&value);
+        check(seed, &schema, &value, &json, &json);
+    }
+}
+
+#[test]
+fn differential_repeated() {
+    let schema = parse_schema(REPEATED_SCHEMA).unwrap();
+    for seed in seeds() {
+        let value = random_repeated(&mut Rng::new(seed));
+        let json = repeated_json(&value);
+        check(seed, &schema, &value, &json, &json);
+    }
+}
+
+#[test]
+fn differential_nested() {
+    let schema = parse_schema(&nested_schema(2)).unwrap();
+    for seed in seeds() {
+        let value = random_nested(&mut Rng::new(seed), 2);
+        check(seed, &schema, &value, &nested_json(&value, true, 2), &nested_json(&value, false, 2));
+    }
+}
+
+#[test]
+fn differential_oneofs() {
+    let schema = parse_schema(&choice_schema()).unwrap();
+    for seed in seeds() {
+        let value = random_choice(&mut Rng::new(seed));
+        let flat = choice_json(&value, false);
+        check(seed, &schema, &value, &flat, &flat);
+
+        // The variant shape encodes to the same bytes
+        let variants = choice_json(&value, true);
+        let bytes = encode_message(&schema, variants.as_object().unwrap(), &ExtensionRegistry::new()).unwrap();
+        assert_eq!(bytes, value.encode_to_vec(), "seed {}: variant shape encodes differently", seed);
+    }
+}
+
+#[test]
+fn differential_well_known_types() {
+    let schema = parse_schema(WELL_KNOWN_SCHEMA).unwrap();
+    for seed in seeds() {
+        let value = random_well_known(&mut Rng::new(seed));
+        let json = well_known_json(&value);
+        check(seed, &schema, &value, &json, &json);
+    }
+}
+
+#[test]
+fn unpacked_input_decodes_for_packed_fields() {
+    // Parsers must accept both encodings of a repeated numeric field
+    let value = Repeated { int32: vec![1, -1], ..Default::default() };
+    let mut unpacked = Vec::new();
+    for n in &value.int32 {
+        prost::encoding::int32::encode(1, n, &mut unpacked);
+    }
+    let schema = parse_schema(REPEATED_SCHEMA).unwrap();
+    let decoded = decode_message(&schema, &unpacked, &ExtensionRegistry::new()).unwrap();
+    assert_eq!(Value::Object(decoded), repeated_json(&value));
+}
@@ -2,17 +2,38 @@
 //! WASM codec library for ReScript protobuf support
 //!
 //! This library provides protobuf binary encoding/decoding functions
-//! that are called from ReScript via wasm-bindgen.
+//! that are called from ReScript via wasm-bindgen (the `wasm` feature,
+//! on by default) or directly from Rust.
 //!
 //! The codec uses JSON as an intermediate format:
 //! - ReScript passes JSON to encode functions
 //! - WASM returns base64-encoded protobuf binary
 //! - For decoding, WASM receives base64 binary and returns JSON
-
-use integer_encoding::{VarInt, VarIntWriter};
+//!
+//! From Rust, use [`Schema`], [`encode`] and [`decode`]:
+//!
+//! ```
+//! use rescript_grpc_codec::{decode, encode, Schema};
+//!
+//! let schema = Schema::parse(r#"[{"n": 1, "name": "name", "type": "string"}]"#)?;
+//! let bytes = encode(&schema, &serde_json::json!({"name": "Alice"}))?;
+//! assert_eq!(bytes, b"\x0a\x05Alice");
+//! assert_eq!(decode(&schema, &bytes)?["name"], "Alice");
+//! # Ok::<(), rescript_grpc_codec::CodecError>(())
+//! ```
+
+use integer_encoding::VarIntWriter;
 use serde_json::{Map, Value};
-use std::io::Write;
-use wasm_bindgen::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod well_known;
+#[cfg(test)]
+mod differential;
+
+pub use well_known::WellKnownType;
 
 // ============================================================================
 // Wire Types (protobuf encoding)
@@ -21,8 +42,19 @@ use wasm_bindgen::prelude::*;
 const WIRE_VARINT: u32 = 0;
 const WIRE_FIXED64: u32 = 1;
 const WIRE_LEN: u32 = 2;
+const WIRE_START_GROUP: u32 = 3;
+const WIRE_END_GROUP: u32 = 4;
 const WIRE_FIXED32: u32 = 5;
 
+/// Maximum nesting of messages and groups, the same limit prost applies
+const RECURSION_LIMIT: u32 = 100;
+
+/// JSON key carrying unknown fields (base64 of their raw wire bytes)
+pub const UNKNOWN_FIELDS_KEY: &str = "$unknown";
+
+/// Type URL prefix written by [`pack_any`]
+pub const ANY_TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
 // ============================================================================
 // Protobuf Encoder
 // ============================================================================
@@ -49,18 +81,6 @@ impl ProtoEncoder {
         self.buf.write_varint(value).unwrap();
     }
 
-    fn write_sint32(&mut self, value: i32) {
-        // ZigZag encoding
-        let encoded = ((value << 1) ^ (value >> 31)) as u32;
-        self.write_varint(encoded as u64);
-    }
-
-    fn write_sint64(&mut self, value: i64) {
-        // ZigZag encoding
-        let encoded = ((value << 1) ^ (value >> 63)) as u64;
-        self.write_varint(encoded);
-    }
-
     fn write_fixed32(&mut self, value: u32) {
         self.buf.extend_from_slice(&value.to_le_bytes());
     }
@@ -81,14 +101,6 @@ impl ProtoEncoder {
         self.buf.write_varint(data.len()).unwrap();
         self.buf.extend_from_slice(data);
     }
-
-    fn write_string(&mut self, s: &str) {
-        self.write_bytes(s.as_bytes());
-    }
-
-    fn write_bool(&mut self, value: bool) {
-        self.write_varint(if value { 1 } else { 0 });
-    }
 }
 
 // ============================================================================
@@ -98,11 +110,22 @@ impl ProtoEncoder {
 struct ProtoDecoder<'a> {
     data: &'a [u8],
     pos: usize,
+    // Messages and groups entered and not yet closed, including the
+    // enclosing messages of a nested message's decoder
+    depth: u32,
 }
 
 impl<'a> ProtoDecoder<'a> {
     fn new(data: &'a [u8]) -> Self {
-        Self { data, pos: 0 }
+        Self { data, pos: 0, depth: 0 }
+    }
+
+    fn enter(&mut self) -> Result<(), &'static str> {
+        if self.depth >= RECURSION_LIMIT {
+            return Err("Recursion limit reached");
+        }
+        self.depth += 1;
+        Ok(())
     }
 
     fn remaining(&self) -> usize {
@@ -210,21 +233,44 @@ impl<'a> ProtoDecoder<'a> {
         Ok(self.read_varint()? != 0)
     }
 
-    fn skip_field(&mut self, wire_type: u32) -> Result<(), &'static str> {
+    fn skip_bytes(&mut self, len: usize) -> Result<(), &'static str> {
+        if self.remaining() < len {
+            return Err("Not enough data to skip field");
+        }
+        self.pos += len;
+        Ok(())
+    }
+
+    fn skip_field(&mut self, field_number: u32, wire_type: u32) -> Result<(), &'static str> {
         match wire_type {
             WIRE_VARINT => {
                 self.read_varint()?;
             }
-            WIRE_FIXED64 => {
-                self.pos += 8;
-            }
+            WIRE_FIXED64 => self.skip_bytes(8)?,
             WIRE_LEN => {
                 let len = self.read_varint()? as usize;
-                self.pos += len;
+                self.skip_bytes(len)?;
             }
-            WIRE_FIXED32 => {
-                self.pos += 4;
+            WIRE_START_GROUP => {
+                // Skip nested fields up to the matching END_GROUP tag
+                self.enter()?;
+                loop {
+                    if self.remaining() == 0 {
+                        return Err("Unterminated group");
+                    }
+                    let (nested_number, nested_wire_type) = self.read_tag()?;
+                    if nested_wire_type == WIRE_END_GROUP {
+                        if nested_number != field_number {
+                            return Err("Mismatched end group");
+                        }
+                        break;
+                    }
+                    self.skip_field(nested_number, nested_wire_type)?;
+                }
+                self.depth -= 1;
             }
+            WIRE_END_GROUP => return Err("Unexpected end group"),
+            WIRE_FIXED32 => self.skip_bytes(4)?,
             _ => return Err("Unknown wire type"),
         }
         Ok(())
@@ -242,7 +288,34 @@ pub struct FieldDescriptor {
     pub name: String,
     pub field_type: FieldType,
     pub is_repeated: bool,
+    /// Explicit presence (proto3 `optional`, proto2, extensions): the value is
+    /// written even when it equals the default
     pub is_optional: bool,
+    /// Repeated numeric values are written as one length-delimited run
+    pub is_packed: bool,
+    /// Set for members of a oneof group
+    pub oneof: Option<OneofMember>,
+}
+
+impl FieldDescriptor {
+    /// Whether a default value is still written (fields with explicit presence)
+    fn has_presence(&self) -> bool {
+        self.is_optional || self.oneof.is_some()
+    }
+}
+
+/// Membership of a field in a oneof group
+///
+/// Members keep their presence and at most one is set. Decode writes the flat
+/// proto3 JSON member key, as the generated `fromJson` reads it; encode also
+/// accepts the runtime shape of the ReScript variant generated for the group,
+/// keyed by the group name: `"kind": {"TAG": "Circle", "_0": {...}}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OneofMember {
+    /// Group name, the record field holding the variant
+    pub group: String,
+    /// Variant constructor of this member
+    pub variant: String,
 }
 
 #[derive(Clone)]
@@ -262,168 +335,501 @@ pub enum FieldType {
     Bool,
     String,
     Bytes,
-    Message(Vec<FieldDescriptor>), // nested message
+    Message(MessageDescriptor), // nested message
+    Group(MessageDescriptor),   // proto2 group, delimited by START_GROUP/END_GROUP
     Enum,
+    WellKnown(WellKnownType),   // google.protobuf.* message, in its proto3 JSON form
+}
+
+impl FieldType {
+    /// Scalar types that can use packed encoding
+    fn is_packable(&self) -> bool {
+        !matches!(
+            self,
+            FieldType::String | FieldType::Bytes | FieldType::Message(_) | FieldType::Group(_) | FieldType::WellKnown(_)
+        )
+    }
+}
+
+/// Message descriptor for dynamic encoding
+///
+/// `type_name` is the fully-qualified proto name (e.g. "example.User") and is
+/// only needed when the message can carry extensions.
+#[derive(Clone, Default)]
+pub struct MessageDescriptor {
+    pub type_name: Option<String>,
+    pub fields: Vec<FieldDescriptor>,
+}
+
+// ============================================================================
+// Extension registry
+// ============================================================================
+
+/// Registry of extension fields, keyed by the fully-qualified extendee name
+///
+/// Extension descriptors use the proto3 JSON key as their name, e.g.
+/// "[example.priority]", so encode and decode read and write the same keys
+/// as the generated `fromJson`/`toJson` functions.
+///
+/// The registry also holds message types that may be packed into a
+/// `google.protobuf.Any`, so their fields are written inline next to `@type`.
+#[derive(Clone, Default)]
+pub struct ExtensionRegistry {
+    extensions: HashMap<String, Vec<FieldDescriptor>>,
+    types: HashMap<String, MessageDescriptor>,
+}
+
+impl ExtensionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an extension field, replacing any extension with the same number
+    pub fn register(&mut self, extendee: &str, field: FieldDescriptor) {
+        let fields = self.extensions.entry(extendee.trim_start_matches('.').to_string()).or_default();
+        fields.retain(|f| f.number != field.number);
+        fields.push(field);
+    }
+
+    /// Register an extension field given as a JSON schema entry, e.g.
+    /// `{"n": 100, "name": "[example.priority]", "type": "int32"}`
+    pub fn register_schema(&mut self, extendee: &str, field_schema: &str) -> Result<(), CodecError> {
+        let value: Value = serde_json::from_str(field_schema)
+            .map_err(|e| CodecError::Schema(format!("Schema parse error: {}", e)))?;
+        let field = parse_field_descriptor(&value).map_err(CodecError::Schema)?;
+        self.register(extendee, field);
+        Ok(())
+    }
+
+    pub fn find_by_number(&self, extendee: &str, number: u32) -> Option<&FieldDescriptor> {
+        self.extensions
+            .get(extendee)
+            .and_then(|fields| fields.iter().find(|f| f.number == number))
+    }
+
+    pub fn find_by_name(&self, extendee: &str, name: &str) -> Option<&FieldDescriptor> {
+        self.extensions
+            .get(extendee)
+            .and_then(|fields| fields.iter().find(|f| f.name == name))
+    }
+
+    /// Register a message type for `google.protobuf.Any`, replacing any
+    /// schema with the same type name
+    pub fn register_type(&mut self, schema: &Schema) -> Result<(), CodecError> {
+        let type_name = schema
+            .message
+            .type_name
+            .clone()
+            .ok_or_else(|| CodecError::Schema("Registering a type requires a typeName".to_string()))?;
+        self.types.insert(type_name, schema.message.clone());
+        Ok(())
+    }
+
+    pub fn find_type(&self, type_name: &str) -> Option<&MessageDescriptor> {
+        self.types.get(type_name.trim_start_matches('.'))
+    }
+
+    pub fn clear(&mut self) {
+        self.extensions.clear();
+        self.types.clear();
+    }
 }
 
 /// Encode a JSON value to protobuf binary based on field descriptors
-fn encode_message(fields: &[FieldDescriptor], json: &Map<String, Value>) -> Result<Vec<u8>, String> {
+fn encode_message(
+    message: &MessageDescriptor,
+    json: &Map<String, Value>,
+    registry: &ExtensionRegistry,
+) -> Result<Vec<u8>, String> {
     let mut encoder = ProtoEncoder::new();
+    let oneofs = oneof_values(message, json)?;
+
+    for field in &message.fields {
+        let value = match &field.oneof {
+            Some(_) => oneofs.get(&field.number).copied(),
+            None => json.get(&field.name),
+        };
+        encode_field_value(&mut encoder, field, value, registry)?;
+    }
 
-    for field in fields {
-        let value = json.get(&field.name);
-
-        if field.is_repeated {
-            if let Some(Value::Array(arr)) = value {
-                for item in arr {
-                    encode_field(&mut encoder, field, item)?;
-                }
-            }
-        } else if let Some(v) = value {
-            if !v.is_null() {
-                encode_field(&mut encoder, field, v)?;
+    // Extension values use "[full.name]" keys
+    if let Some(type_name) = &message.type_name {
+        for (key, value) in json.iter().filter(|(k, _)| k.starts_with('[')) {
+            if let Some(field) = registry.find_by_name(type_name, key) {
+                encode_field_value(&mut encoder, field, Some(value), registry)?;
             }
         }
     }
 
+    // Re-emit unknown fields captured during decode, unchanged
+    if let Some(unknown) = json.get(UNKNOWN_FIELDS_KEY).and_then(|v| v.as_str()) {
+        encoder.buf.extend_from_slice(&base64_decode(unknown)?);
+    }
+
     Ok(encoder.into_bytes())
 }
 
-fn encode_field(encoder: &mut ProtoEncoder, field: &FieldDescriptor, value: &Value) -> Result<(), String> {
-    match &field.field_type {
-        FieldType::Int32 | FieldType::Uint32 | FieldType::Enum => {
-            if let Some(n) = value.as_i64() {
-                encoder.write_tag(field.number, WIRE_VARINT);
-                encoder.write_varint(n as u64);
+/// Values of the oneof members set in `json`, by field number, checking that
+/// each group has at most one member set
+fn oneof_values<'a>(message: &MessageDescriptor, json: &'a Map<String, Value>) -> Result<HashMap<u32, &'a Value>, String> {
+    let mut values = HashMap::new();
+    let mut set_members: HashMap<&str, &str> = HashMap::new();
+
+    for field in &message.fields {
+        let Some(member) = &field.oneof else { continue };
+
+        let flat = json.get(&field.name).filter(|v| !v.is_null());
+        let variant = json
+            .get(&member.group)
+            .and_then(|v| v.as_object())
+            .filter(|v| v.get("TAG").and_then(|t| t.as_str()) == Some(member.variant.as_str()))
+            .map(|v| v.get("_0").unwrap_or(&Value::Null));
+
+        for value in flat.into_iter().chain(variant) {
+            if let Some(other) = set_members.insert(&member.group, &field.name) {
+                return Err(format!("Oneof {} has more than one member set: {}, {}", member.group, other, field.name));
             }
+            values.insert(field.number, value);
         }
-        FieldType::Int64 | FieldType::Uint64 => {
-            // Handle as string for bigint
-            if let Some(s) = value.as_str() {
-                let n: i64 = s.parse().map_err(|_| "Invalid int64")?;
-                encoder.write_tag(field.number, WIRE_VARINT);
-                encoder.write_varint(n as u64);
-            } else if let Some(n) = value.as_i64() {
-                encoder.write_tag(field.number, WIRE_VARINT);
-                encoder.write_varint(n as u64);
+    }
+
+    // A variant whose TAG matches no member would otherwise be dropped silently
+    for field in &message.fields {
+        let Some(member) = &field.oneof else { continue };
+        if let Some(tag) = json.get(&member.group).and_then(|v| v.get("TAG")).and_then(|t| t.as_str()) {
+            let known = message.fields.iter().any(|f| {
+                f.oneof.as_ref().is_some_and(|m| m.group == member.group && m.variant == tag)
+            });
+            if !known {
+                return Err(format!("Unknown variant {} for oneof {}", tag, member.group));
             }
         }
-        FieldType::Sint32 => {
-            if let Some(n) = value.as_i64() {
-                encoder.write_tag(field.number, WIRE_VARINT);
-                encoder.write_sint32(n as i32);
+    }
+
+    Ok(values)
+}
+
+fn encode_field_value(
+    encoder: &mut ProtoEncoder,
+    field: &FieldDescriptor,
+    value: Option<&Value>,
+    registry: &ExtensionRegistry,
+) -> Result<(), String> {
+    if field.is_repeated {
+        if let Some(Value::Array(arr)) = value {
+            if field.is_packed && field.field_type.is_packable() {
+                return encode_packed(encoder, field, arr);
             }
-        }
-        FieldType::Sint64 => {
-            if let Some(s) = value.as_str() {
-                let n: i64 = s.parse().map_err(|_| "Invalid sint64")?;
-                encoder.write_tag(field.number, WIRE_VARINT);
-                encoder.write_sint64(n);
+            for item in arr {
+                encode_field(encoder, field, item, registry)?;
             }
         }
-        FieldType::Fixed32 | FieldType::Sfixed32 => {
-            if let Some(n) = value.as_i64() {
-                encoder.write_tag(field.number, WIRE_FIXED32);
-                encoder.write_fixed32(n as u32);
+    } else if let Some(v) = value {
+        // `null` is a value of its own for google.protobuf.Value
+        if !v.is_null() || matches!(field.field_type, FieldType::WellKnown(WellKnownType::Value)) {
+            // Fields without explicit presence are not written when they hold the default
+            if !field.has_presence() {
+                if let Some(scalar) = scalar_value(&field.field_type, v)? {
+                    if !scalar.is_default() {
+                        scalar.write(encoder, field.number);
+                    }
+                    return Ok(());
+                }
             }
+            encode_field(encoder, field, v, registry)?;
         }
-        FieldType::Fixed64 | FieldType::Sfixed64 => {
-            if let Some(s) = value.as_str() {
-                let n: u64 = s.parse().map_err(|_| "Invalid fixed64")?;
-                encoder.write_tag(field.number, WIRE_FIXED64);
-                encoder.write_fixed64(n);
-            }
+    }
+    Ok(())
+}
+
+/// Write a repeated numeric field as one length-delimited run of values
+fn encode_packed(encoder: &mut ProtoEncoder, field: &FieldDescriptor, arr: &[Value]) -> Result<(), String> {
+    let mut packed = ProtoEncoder::new();
+    for item in arr {
+        if let Some(scalar) = scalar_value(&field.field_type, item)? {
+            scalar.write_value(&mut packed);
         }
-        FieldType::Float => {
-            if let Some(n) = value.as_f64() {
-                encoder.write_tag(field.number, WIRE_FIXED32);
-                encoder.write_float(n as f32);
+    }
+    if !packed.buf.is_empty() {
+        encoder.write_tag(field.number, WIRE_LEN);
+        encoder.write_bytes(&packed.buf);
+    }
+    Ok(())
+}
+
+fn encode_field(
+    encoder: &mut ProtoEncoder,
+    field: &FieldDescriptor,
+    value: &Value,
+    registry: &ExtensionRegistry,
+) -> Result<(), String> {
+    match &field.field_type {
+        FieldType::Message(nested) => {
+            if let Some(obj) = value.as_object() {
+                let nested_bytes = encode_message(nested, obj, registry)?;
+                encoder.write_tag(field.number, WIRE_LEN);
+                encoder.write_bytes(&nested_bytes);
             }
         }
-        FieldType::Double => {
-            if let Some(n) = value.as_f64() {
-                encoder.write_tag(field.number, WIRE_FIXED64);
-                encoder.write_double(n);
+        FieldType::Group(nested) => {
+            if let Some(obj) = value.as_object() {
+                let nested_bytes = encode_message(nested, obj, registry)?;
+                encoder.write_tag(field.number, WIRE_START_GROUP);
+                encoder.buf.extend_from_slice(&nested_bytes);
+                encoder.write_tag(field.number, WIRE_END_GROUP);
             }
         }
-        FieldType::Bool => {
-            if let Some(b) = value.as_bool() {
-                encoder.write_tag(field.number, WIRE_VARINT);
-                encoder.write_bool(b);
-            }
+        FieldType::WellKnown(well_known) => {
+            let nested_bytes = well_known::encode(*well_known, value, registry)?;
+            encoder.write_tag(field.number, WIRE_LEN);
+            encoder.write_bytes(&nested_bytes);
         }
-        FieldType::String => {
-            if let Some(s) = value.as_str() {
-                encoder.write_tag(field.number, WIRE_LEN);
-                encoder.write_string(s);
+        field_type => {
+            if let Some(scalar) = scalar_value(field_type, value)? {
+                scalar.write(encoder, field.number);
             }
         }
-        FieldType::Bytes => {
-            // Base64 encoded
-            if let Some(s) = value.as_str() {
-                let bytes = base64_decode(s)?;
-                encoder.write_tag(field.number, WIRE_LEN);
-                encoder.write_bytes(&bytes);
-            }
+    }
+    Ok(())
+}
+
+/// A scalar field value converted from JSON, ready to be written
+enum Scalar {
+    Varint(u64),
+    Fixed32(u32),
+    Fixed64(u64),
+    Float(f32),
+    Double(f64),
+    Len(Vec<u8>),
+}
+
+impl Scalar {
+    fn is_default(&self) -> bool {
+        match self {
+            Scalar::Varint(n) | Scalar::Fixed64(n) => *n == 0,
+            Scalar::Fixed32(n) => *n == 0,
+            Scalar::Float(n) => *n == 0.0,
+            Scalar::Double(n) => *n == 0.0,
+            Scalar::Len(bytes) => bytes.is_empty(),
         }
-        FieldType::Message(nested_fields) => {
-            if let Some(obj) = value.as_object() {
-                let nested_bytes = encode_message(nested_fields, obj)?;
-                encoder.write_tag(field.number, WIRE_LEN);
-                encoder.write_bytes(&nested_bytes);
-            }
+    }
+
+    fn write(&self, encoder: &mut ProtoEncoder, field_number: u32) {
+        let wire_type = match self {
+            Scalar::Varint(_) => WIRE_VARINT,
+            Scalar::Fixed32(_) | Scalar::Float(_) => WIRE_FIXED32,
+            Scalar::Fixed64(_) | Scalar::Double(_) => WIRE_FIXED64,
+            Scalar::Len(_) => WIRE_LEN,
+        };
+        encoder.write_tag(field_number, wire_type);
+        self.write_value(encoder);
+    }
+
+    fn write_value(&self, encoder: &mut ProtoEncoder) {
+        match self {
+            Scalar::Varint(n) => encoder.write_varint(*n),
+            Scalar::Fixed32(n) => encoder.write_fixed32(*n),
+            Scalar::Fixed64(n) => encoder.write_fixed64(*n),
+            Scalar::Float(n) => encoder.write_float(*n),
+            Scalar::Double(n) => encoder.write_double(*n),
+            Scalar::Len(bytes) => encoder.write_bytes(bytes),
         }
     }
-    Ok(())
+}
+
+/// Convert a JSON value to a scalar of `field_type`
+///
+/// Values of the wrong JSON type are ignored (`None`); 64-bit integers are
+/// accepted both as strings and as numbers.
+fn scalar_value(field_type: &FieldType, value: &Value) -> Result<Option<Scalar>, String> {
+    let scalar = match field_type {
+        // Negative int32 and enum values are sign-extended to ten bytes
+        FieldType::Int32 | FieldType::Enum => value.as_i64().map(|n| Scalar::Varint(n as i32 as u64)),
+        FieldType::Uint32 => value.as_i64().map(|n| Scalar::Varint(n as u32 as u64)),
+        FieldType::Int64 => int64_value(value, "int64")?.map(|n| Scalar::Varint(n as u64)),
+        FieldType::Uint64 => uint64_value(value, "uint64")?.map(Scalar::Varint),
+        FieldType::Sint32 => value.as_i64().map(|n| Scalar::Varint(zigzag32(n as i32))),
+        FieldType::Sint64 => int64_value(value, "sint64")?.map(|n| Scalar::Varint(zigzag64(n))),
+        FieldType::Fixed32 | FieldType::Sfixed32 => value.as_i64().map(|n| Scalar::Fixed32(n as u32)),
+        FieldType::Fixed64 => uint64_value(value, "fixed64")?.map(Scalar::Fixed64),
+        FieldType::Sfixed64 => int64_value(value, "sfixed64")?.map(|n| Scalar::Fixed64(n as u64)),
+        FieldType::Float => value.as_f64().map(|n| Scalar::Float(n as f32)),
+        FieldType::Double => value.as_f64().map(Scalar::Double),
+        FieldType::Bool => value.as_bool().map(|b| Scalar::Varint(b as u64)),
+        FieldType::String => value.as_str().map(|s| Scalar::Len(s.as_bytes().to_vec())),
+        // Base64 encoded
+        FieldType::Bytes => value.as_str().map(base64_decode).transpose()?.map(Scalar::Len),
+        FieldType::Message(_) | FieldType::Group(_) | FieldType::WellKnown(_) => None,
+    };
+    Ok(scalar)
+}
+
+fn int64_value(value: &Value, type_name: &str) -> Result<Option<i64>, String> {
+    match value {
+        Value::String(s) => s.parse().map(Some).map_err(|_| format!("Invalid {}", type_name)),
+        _ => Ok(value.as_i64()),
+    }
+}
+
+fn uint64_value(value: &Value, type_name: &str) -> Result<Option<u64>, String> {
+    match value {
+        Value::String(s) => s.parse().map(Some).map_err(|_| format!("Invalid {}", type_name)),
+        _ => Ok(value.as_u64()),
+    }
+}
+
+// ZigZag encoding
+fn zigzag32(value: i32) -> u64 {
+    ((value << 1) ^ (value >> 31)) as u32 as u64
+}
+
+fn zigzag64(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
 }
 
 /// Decode protobuf binary to JSON based on field descriptors
-fn decode_message(fields: &[FieldDescriptor], data: &[u8]) -> Result<Map<String, Value>, String> {
-    let mut decoder = ProtoDecoder::new(data);
+fn decode_message(
+    message: &MessageDescriptor,
+    data: &[u8],
+    registry: &ExtensionRegistry,
+) -> Result<Map<String, Value>, String> {
+    decode_nested(message, data, registry, 0)
+}
+
+/// Decode a message nested `depth` messages and groups deep
+fn decode_nested(
+    message: &MessageDescriptor,
+    data: &[u8],
+    registry: &ExtensionRegistry,
+    depth: u32,
+) -> Result<Map<String, Value>, String> {
+    if depth > RECURSION_LIMIT {
+        return Err("Recursion limit reached".to_string());
+    }
+    let mut decoder = ProtoDecoder { data, pos: 0, depth };
+    decode_fields(&mut decoder, message, registry, None)
+}
+
+/// Decode fields until the input ends or, inside a group, until the
+/// END_GROUP tag matching `group_number`
+fn decode_fields(
+    decoder: &mut ProtoDecoder,
+    message: &MessageDescriptor,
+    registry: &ExtensionRegistry,
+    group_number: Option<u32>,
+) -> Result<Map<String, Value>, String> {
     let mut result = Map::new();
+    let mut unknown = Vec::new();
 
     // Initialize repeated fields
-    for field in fields {
+    for field in &message.fields {
         if field.is_repeated {
             result.insert(field.name.clone(), Value::Array(Vec::new()));
         }
     }
 
-    while decoder.remaining() > 0 {
+    loop {
+        if decoder.remaining() == 0 {
+            if group_number.is_some() {
+                return Err("Unterminated group".to_string());
+            }
+            break;
+        }
+
+        let field_start = decoder.pos;
         let (field_number, wire_type) = decoder.read_tag().map_err(|e| e.to_string())?;
 
-        // Find field descriptor
-        let field = fields.iter().find(|f| f.number == field_number);
+        if wire_type == WIRE_END_GROUP {
+            if group_number == Some(field_number) {
+                break;
+            }
+            return Err("Unexpected end group".to_string());
+        }
+
+        // Find field descriptor, falling back to registered extensions
+        let field = message
+            .fields
+            .iter()
+            .find(|f| f.number == field_number)
+            .or_else(|| {
+                message
+                    .type_name
+                    .as_deref()
+                    .and_then(|t| registry.find_by_number(t, field_number))
+            });
 
         match field {
+            Some(f) if f.is_repeated && wire_type == WIRE_LEN && f.field_type.is_packable() => {
+                // Packed run of values; accepted whether or not the schema asks for packing
+                let bytes = decoder.read_bytes().map_err(|e| e.to_string())?;
+                let mut packed = ProtoDecoder::new(&bytes);
+                let mut values = Vec::new();
+                while packed.remaining() > 0 {
+                    values.push(decode_field(&mut packed, f, wire_type, registry)?);
+                }
+                push_repeated(&mut result, f, values);
+            }
             Some(f) => {
-                let value = decode_field(&mut decoder, f, wire_type)?;
+                let value = decode_field(decoder, f, wire_type, registry)?;
 
                 if f.is_repeated {
-                    if let Some(Value::Array(arr)) = result.get_mut(&f.name) {
-                        arr.push(value);
+                    push_repeated(&mut result, f, vec![value]);
+                } else if let Some(member) = &f.oneof {
+                    // Replaces any member decoded earlier: the last one on the wire wins
+                    for other in &message.fields {
+                        if other.oneof.as_ref().is_some_and(|m| m.group == member.group) {
+                            result.remove(&other.name);
+                        }
                     }
+                    result.insert(f.name.clone(), value);
                 } else {
                     result.insert(f.name.clone(), value);
                 }
             }
             None => {
-                // Skip unknown field
-                decoder.skip_field(wire_type).map_err(|e| e.to_string())?;
+                // Skip unknown field, keeping its raw bytes for re-encoding
+                decoder.skip_field(field_number, wire_type).map_err(|e| e.to_string())?;
+                unknown.extend_from_slice(&decoder.data[field_start..decoder.pos]);
             }
         }
     }
 
+    if !unknown.is_empty() {
+        result.insert(UNKNOWN_FIELDS_KEY.to_string(), Value::String(base64_encode(&unknown)));
+    }
+
     Ok(result)
 }
 
-fn decode_field(decoder: &mut ProtoDecoder, field: &FieldDescriptor, wire_type: u32) -> Result<Value, String> {
+/// Append values to a repeated field, creating the array on first use since
+/// extensions are not pre-initialized
+fn push_repeated(result: &mut Map<String, Value>, field: &FieldDescriptor, values: Vec<Value>) {
+    let entry = result.entry(field.name.clone()).or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(arr) = entry {
+        arr.extend(values);
+    }
+}
+
+fn decode_field(
+    decoder: &mut ProtoDecoder,
+    field: &FieldDescriptor,
+    wire_type: u32,
+    registry: &ExtensionRegistry,
+) -> Result<Value, String> {
     match &field.field_type {
-        FieldType::Int32 | FieldType::Uint32 | FieldType::Enum => {
+        FieldType::Int32 | FieldType::Enum => {
+            let n = decoder.read_varint().map_err(|e| e.to_string())?;
+            Ok(Value::Number(serde_json::Number::from(n as i32)))
+        }
+        FieldType::Uint32 => {
+            let n = decoder.read_varint().map_err(|e| e.to_string())?;
+            Ok(Value::Number(serde_json::Number::from(n as u32)))
+        }
+        FieldType::Int64 => {
             let n = decoder.read_varint().map_err(|e| e.to_string())?;
-            Ok(Value::Number(serde_json::Number::from(n as i64)))
+            Ok(Value::String((n as i64).to_string()))
         }
-        FieldType::Int64 | FieldType::Uint64 => {
+        FieldType::Uint64 => {
             let n = decoder.read_varint().map_err(|e| e.to_string())?;
             Ok(Value::String(n.to_string()))
         }
@@ -435,14 +841,22 @@ fn decode_field(decoder: &mut ProtoDecoder, field: &FieldDescriptor, wire_type:
             let n = decoder.read_sint64().map_err(|e| e.to_string())?;
             Ok(Value::String(n.to_string()))
         }
-        FieldType::Fixed32 | FieldType::Sfixed32 => {
+        FieldType::Fixed32 => {
             let n = decoder.read_fixed32().map_err(|e| e.to_string())?;
             Ok(Value::Number(serde_json::Number::from(n)))
         }
-        FieldType::Fixed64 | FieldType::Sfixed64 => {
+        FieldType::Sfixed32 => {
+            let n = decoder.read_fixed32().map_err(|e| e.to_string())?;
+            Ok(Value::Number(serde_json::Number::from(n as i32)))
+        }
+        FieldType::Fixed64 => {
             let n = decoder.read_fixed64().map_err(|e| e.to_string())?;
             Ok(Value::String(n.to_string()))
         }
+        FieldType::Sfixed64 => {
+            let n = decoder.read_fixed64().map_err(|e| e.to_string())?;
+            Ok(Value::String((n as i64).to_string()))
+        }
         FieldType::Float => {
             let n = decoder.read_float().map_err(|e| e.to_string())?;
             Ok(serde_json::Number::from_f64(n as f64)
@@ -467,11 +881,29 @@ fn decode_field(decoder: &mut ProtoDecoder, field: &FieldDescriptor, wire_type:
             let bytes = decoder.read_bytes().map_err(|e| e.to_string())?;
             Ok(Value::String(base64_encode(&bytes)))
         }
-        FieldType::Message(nested_fields) => {
+        FieldType::Message(nested) => {
             let bytes = decoder.read_bytes().map_err(|e| e.to_string())?;
-            let obj = decode_message(nested_fields, &bytes)?;
+            decoder.enter()?;
+            let obj = decode_nested(nested, &bytes, registry, decoder.depth)?;
+            decoder.depth -= 1;
             Ok(Value::Object(obj))
         }
+        FieldType::Group(nested) => {
+            if wire_type != WIRE_START_GROUP {
+                return Err(format!("Expected start group for field {}", field.name));
+            }
+            decoder.enter()?;
+            let obj = decode_fields(decoder, nested, registry, Some(field.number))?;
+            decoder.depth -= 1;
+            Ok(Value::Object(obj))
+        }
+        FieldType::WellKnown(well_known) => {
+            let bytes = decoder.read_bytes().map_err(|e| e.to_string())?;
+            decoder.enter()?;
+            let value = well_known::decode(*well_known, &bytes, registry, decoder.depth)?;
+            decoder.depth -= 1;
+            Ok(value)
+        }
     }
 }
 
@@ -482,7 +914,7 @@ fn decode_field(decoder: &mut ProtoDecoder, field: &FieldDescriptor, wire_type:
 const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
 fn base64_encode(data: &[u8]) -> String {
-    let mut result = String::with_capacity((data.len() + 2) / 3 * 4);
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
 
     for chunk in data.chunks(3) {
         let b0 = chunk[0] as usize;
@@ -548,52 +980,182 @@ fn base64_decode(s: &str) -> Result<Vec<u8>, String> {
 }
 
 // ============================================================================
-// WASM Exports
+// Rust API
 // ============================================================================
 
-/// Encode JSON to protobuf binary (returns base64)
-///
-/// The schema parameter is a JSON array of field descriptors:
-/// [{"n": 1, "name": "field_name", "type": "string", "repeated": false}, ...]
-#[wasm_bindgen]
-pub fn encode(schema: &str, json_data: &str) -> Result<String, JsValue> {
-    let fields = parse_schema(schema).map_err(|e| JsValue::from_str(&e))?;
-    let json: Value = serde_json::from_str(json_data)
-        .map_err(|e| JsValue::from_str(&format!("JSON parse error: {}", e)))?;
+/// Error returned by the codec
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodecError {
+    /// The schema is not valid JSON or uses an unknown field type
+    Schema(String),
+    /// The value does not fit the schema (not an object, invalid int64 string, ...)
+    Encode(String),
+    /// The bytes are not a valid message for the schema
+    Decode(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Schema(message) | CodecError::Encode(message) | CodecError::Decode(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
 
-    let obj = json.as_object()
-        .ok_or_else(|| JsValue::from_str("Expected JSON object"))?;
+impl std::error::Error for CodecError {}
 
-    let bytes = encode_message(&fields, obj)
-        .map_err(|e| JsValue::from_str(&e))?;
+/// A parsed message schema
+#[derive(Clone)]
+pub struct Schema {
+    message: MessageDescriptor,
+}
+
+impl Schema {
+    /// Parse a schema in the JSON format used by the ReScript runtime: a field
+    /// array, or `{"typeName": ..., "fields": [...]}` for messages with extensions
+    pub fn parse(schema: &str) -> Result<Self, CodecError> {
+        parse_schema(schema).map(Self::from).map_err(CodecError::Schema)
+    }
+
+    pub fn message(&self) -> &MessageDescriptor {
+        &self.message
+    }
+}
+
+impl From<MessageDescriptor> for Schema {
+    fn from(message: MessageDescriptor) -> Self {
+        Self { message }
+    }
+}
+
+/// Encode a JSON object (proto3 JSON field values) to protobuf binary
+pub fn encode(schema: &Schema, value: &Value) -> Result<Vec<u8>, CodecError> {
+    encode_with_extensions(schema, value, &ExtensionRegistry::new())
+}
+
+/// Encode, also writing extension values found in `registry`
+pub fn encode_with_extensions(
+    schema: &Schema,
+    value: &Value,
+    registry: &ExtensionRegistry,
+) -> Result<Vec<u8>, CodecError> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| CodecError::Encode("Expected JSON object".to_string()))?;
+    encode_message(&schema.message, obj, registry).map_err(CodecError::Encode)
+}
+
+/// Decode protobuf binary to a JSON object
+pub fn decode(schema: &Schema, bytes: &[u8]) -> Result<Value, CodecError> {
+    decode_with_extensions(schema, bytes, &ExtensionRegistry::new())
+}
 
-    Ok(base64_encode(&bytes))
+/// Decode, reading extension fields found in `registry` under their JSON keys
+pub fn decode_with_extensions(
+    schema: &Schema,
+    bytes: &[u8],
+    registry: &ExtensionRegistry,
+) -> Result<Value, CodecError> {
+    decode_message(&schema.message, bytes, registry)
+        .map(Value::Object)
+        .map_err(CodecError::Decode)
 }
 
-/// Decode protobuf binary (base64) to JSON
-#[wasm_bindgen]
-pub fn decode(schema: &str, base64_data: &str) -> Result<String, JsValue> {
-    let fields = parse_schema(schema).map_err(|e| JsValue::from_str(&e))?;
-    let bytes = base64_decode(base64_data)
-        .map_err(|e| JsValue::from_str(&e))?;
+/// Pack a message into the JSON form of `google.protobuf.Any`
+///
+/// Fields are written inline next to `@type`, or under `value` for
+/// well-known types. The schema must have a type name. Encoding the result
+/// needs the type registered with [`ExtensionRegistry::register_type`].
+pub fn pack_any(schema: &Schema, value: &Value) -> Result<Value, CodecError> {
+    let type_name = schema
+        .message
+        .type_name
+        .as_deref()
+        .ok_or_else(|| CodecError::Schema("Packing into Any requires a typeName".to_string()))?;
+    let mut any = Map::new();
+    any.insert("@type".to_string(), Value::String(format!("{}{}", ANY_TYPE_URL_PREFIX, type_name)));
+    if WellKnownType::from_type_name(type_name).is_some() {
+        any.insert("value".to_string(), value.clone());
+    } else {
+        let fields = value
+            .as_object()
+            .ok_or_else(|| CodecError::Encode("Expected JSON object".to_string()))?;
+        any.extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    Ok(Value::Object(any))
+}
 
-    let obj = decode_message(&fields, &bytes)
-        .map_err(|e| JsValue::from_str(&e))?;
+/// Unpack the message of the schema's type from the JSON form of `google.protobuf.Any`
+///
+/// Accepts inline fields as well as the raw `$unknown` bytes that decoding
+/// leaves for types that were not registered.
+pub fn unpack_any(schema: &Schema, any: &Value) -> Result<Value, CodecError> {
+    let error = |message: String| CodecError::Decode(message);
+    let any = any.as_object().ok_or_else(|| error("Any must be an object".to_string()))?;
+    let type_url = any
+        .get("@type")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| error("Any requires an @type".to_string()))?;
+    let type_name = well_known::any_type_name(type_url);
+    if schema.message.type_name.as_deref() != Some(type_name) {
+        return Err(error(format!(
+            "Any holds {}, not {}",
+            type_name,
+            schema.message.type_name.as_deref().unwrap_or("an untyped message")
+        )));
+    }
 
-    serde_json::to_string(&obj)
-        .map_err(|e| JsValue::from_str(&format!("JSON serialize error: {}", e)))
+    if WellKnownType::from_type_name(type_name).is_some() {
+        return Ok(any.get("value").cloned().unwrap_or(Value::Null));
+    }
+    let mut fields: Map<String, Value> = any
+        .iter()
+        .filter(|(k, _)| *k != "@type")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    if fields.len() == 1 {
+        if let Some(raw) = fields.get(UNKNOWN_FIELDS_KEY).and_then(|v| v.as_str()) {
+            let bytes = base64_decode(raw).map_err(error)?;
+            fields = decode_message(&schema.message, &bytes, &ExtensionRegistry::new()).map_err(error)?;
+        }
+    }
+    Ok(Value::Object(fields))
 }
 
-/// Parse schema from JSON
-fn parse_schema(schema: &str) -> Result<Vec<FieldDescriptor>, String> {
-    let arr: Vec<Value> = serde_json::from_str(schema)
+/// Parse schema from JSON (either a field array or a message object)
+fn parse_schema(schema: &str) -> Result<MessageDescriptor, String> {
+    let value: Value = serde_json::from_str(schema)
         .map_err(|e| format!("Schema parse error: {}", e))?;
 
-    let mut fields = Vec::new();
-    for item in arr {
-        fields.push(parse_field_descriptor(&item)?);
+    match &value {
+        Value::Array(arr) => parse_fields(arr).map(|fields| MessageDescriptor {
+            type_name: None,
+            fields,
+        }),
+        Value::Object(obj) => parse_message_descriptor(obj),
+        _ => Err("Schema must be an array or object".to_string()),
     }
-    Ok(fields)
+}
+
+fn parse_fields(arr: &[Value]) -> Result<Vec<FieldDescriptor>, String> {
+    arr.iter().map(parse_field_descriptor).collect()
+}
+
+fn parse_message_descriptor(obj: &Map<String, Value>) -> Result<MessageDescriptor, String> {
+    let fields = obj.get("fields")
+        .and_then(|v| v.as_array())
+        .ok_or("Message type requires 'fields' array")?;
+
+    let type_name = obj.get("typeName")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim_start_matches('.').to_string());
+
+    Ok(MessageDescriptor {
+        type_name,
+        fields: parse_fields(fields)?,
+    })
 }
 
 fn parse_field_descriptor(v: &Value) -> Result<FieldDescriptor, String> {
@@ -620,6 +1182,24 @@ fn parse_field_descriptor(v: &Value) -> Result<FieldDescriptor, String> {
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    // Proto3 packs repeated numeric fields unless told otherwise
+    let is_packed = obj.get("packed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    // Members of a oneof name their group; the variant defaults to the field
+    // name with its first letter capitalized, as the generated constructors
+    let oneof = match obj.get("oneof").and_then(|v| v.as_str()) {
+        Some(group) => {
+            let variant = match obj.get("variant").and_then(|v| v.as_str()) {
+                Some(variant) => variant.to_string(),
+                None => capitalize(&name),
+            };
+            Some(OneofMember { group: group.to_string(), variant })
+        }
+        None => None,
+    };
+
     let field_type = match type_str {
         "int32" => FieldType::Int32,
         "int64" => FieldType::Int64,
@@ -637,15 +1217,12 @@ fn parse_field_descriptor(v: &Value) -> Result<FieldDescriptor, String> {
         "string" => FieldType::String,
         "bytes" => FieldType::Bytes,
         "enum" => FieldType::Enum,
-        "message" => {
-            let nested = obj.get("fields")
-                .and_then(|v| v.as_array())
-                .ok_or("Message type requires 'fields' array")?;
-            let nested_fields: Result<Vec<_>, _> = nested.iter()
-                .map(parse_field_descriptor)
-                .collect();
-            FieldType::Message(nested_fields?)
-        }
+        // Well-known types need no field list, their layout is built in
+        "message" => match obj.get("typeName").and_then(|v| v.as_str()).and_then(WellKnownType::from_type_name) {
+            Some(well_known) => FieldType::WellKnown(well_known),
+            None => FieldType::Message(parse_message_descriptor(obj)?),
+        },
+        "group" => FieldType::Group(parse_message_descriptor(obj)?),
         _ => return Err(format!("Unknown field type: {}", type_str)),
     };
 
@@ -655,30 +1232,453 @@ fn parse_field_descriptor(v: &Value) -> Result<FieldDescriptor, String> {
         field_type,
         is_repeated,
         is_optional,
+        is_packed,
+        oneof,
     })
 }
 
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_encode_decode_simple() {
+        let schema = Schema::parse(
+            r#"[
+                {"n": 1, "name": "name", "type": "string"},
+                {"n": 2, "name": "id", "type": "int32"}
+            ]"#,
+        )
+        .unwrap();
+
+        let original = serde_json::json!({"name": "Alice", "id": 42});
+
+        let encoded = encode(&schema, &original).unwrap();
+        let result = decode(&schema, &encoded).unwrap();
+
+        assert_eq!(original["name"], result["name"]);
+        assert_eq!(original["id"], result["id"]);
+    }
+
+    #[test]
+    fn test_api_errors() {
+        assert!(matches!(Schema::parse("{}"), Err(CodecError::Schema(_))));
+        assert!(matches!(
+            Schema::parse(r#"[{"n": 1, "name": "x", "type": "int128"}]"#),
+            Err(CodecError::Schema(message)) if message == "Unknown field type: int128"
+        ));
+
+        let schema = Schema::parse(r#"[{"n": 1, "name": "big", "type": "int64"}]"#).unwrap();
+        assert_eq!(
+            encode(&schema, &serde_json::json!([1])),
+            Err(CodecError::Encode("Expected JSON object".to_string()))
+        );
+        assert_eq!(
+            encode(&schema, &serde_json::json!({"big": "12x"})).unwrap_err().to_string(),
+            "Invalid int64"
+        );
+        assert!(matches!(decode(&schema, &[0x08]), Err(CodecError::Decode(_))));
+
+        let mut registry = ExtensionRegistry::new();
+        assert!(registry.register_schema("example.User", r#"{"n": 100}"#).is_err());
+        registry
+            .register_schema("example.User", r#"{"n": 100, "name": "[example.priority]", "type": "int32"}"#)
+            .unwrap();
+        assert!(registry.find_by_number("example.User", 100).is_some());
+    }
+
+    #[test]
+    fn test_any_pack_unpack() {
+        let user = Schema::parse(
+            r#"{"typeName": "example.User", "fields": [{"n": 1, "name": "name", "type": "string"}]}"#,
+        )
+        .unwrap();
+        let event = Schema::parse(
+            r#"[{"n": 1, "name": "payload", "type": "message", "typeName": "google.protobuf.Any"}]"#,
+        )
+        .unwrap();
+
+        let any = pack_any(&user, &serde_json::json!({"name": "Alice"})).unwrap();
+        assert_eq!(any, serde_json::json!({"@type": "type.googleapis.com/example.User", "name": "Alice"}));
+        let message = serde_json::json!({"payload": any});
+
+        // Inline fields need the type registered
+        assert!(encode(&event, &message).is_err());
+        let mut registry = ExtensionRegistry::new();
+        registry.register_type(&user).unwrap();
+        let bytes = encode_with_extensions(&event, &message, &registry).unwrap();
+        let mut expected = b"\x0a\x2b\x0a\x20type.googleapis.com/example.User".to_vec();
+        expected.extend(b"\x12\x07\x0a\x05Alice");
+        assert_eq!(bytes, expected);
+        assert_eq!(decode_with_extensions(&event, &bytes, &registry).unwrap(), message);
+
+        // Without the registration the packed bytes stay raw, and unpack decodes them
+        let raw = decode(&event, &bytes).unwrap();
+        assert_eq!(raw["payload"]["$unknown"], "CgVBbGljZQ==");
+        assert_eq!(unpack_any(&user, &raw["payload"]).unwrap(), serde_json::json!({"name": "Alice"}));
+        assert_eq!(unpack_any(&user, &any).unwrap(), serde_json::json!({"name": "Alice"}));
+
+        let other = Schema::parse(r#"{"typeName": "example.Team", "fields": []}"#).unwrap();
+        assert_eq!(
+            unpack_any(&other, &any).unwrap_err().to_string(),
+            "Any holds example.User, not example.Team"
+        );
+
+        // Well-known types are packed under "value"
+        let duration = Schema::parse(r#"{"typeName": "google.protobuf.Duration", "fields": []}"#).unwrap();
+        let any = pack_any(&duration, &serde_json::json!("1.5s")).unwrap();
+        assert_eq!(any["value"], "1.5s");
+        assert_eq!(unpack_any(&duration, &any).unwrap(), "1.5s");
+    }
+
+    #[test]
+    fn test_extension_roundtrip() {
+        let message = MessageDescriptor {
+            type_name: Some("example.Annotated".to_string()),
+            fields: vec![FieldDescriptor {
+                number: 1,
+                name: "name".to_string(),
+                field_type: FieldType::String,
+                is_repeated: false,
+                is_optional: false,
+                is_packed: false,
+                oneof: None,
+            }],
+        };
+
+        let mut registry = ExtensionRegistry::new();
+        registry.register(
+            ".example.Annotated",
+            FieldDescriptor {
+                number: 100,
+                name: "[example.priority]".to_string(),
+                field_type: FieldType::Int32,
+                is_repeated: false,
+                is_optional: true,
+                is_packed: false,
+                oneof: None,
+            },
+        );
+        registry.register(
+            "example.Annotated",
+            FieldDescriptor {
+                number: 101,
+                name: "[example.labels]".to_string(),
+                field_type: FieldType::String,
+                is_repeated: true,
+                is_optional: false,
+                is_packed: false,
+                oneof: None,
+            },
+        );
+
+        let json: Value = serde_json::from_str(
+            r#"{"name": "task", "[example.priority]": 3, "[example.labels]": ["a", "b"]}"#,
+        )
+        .unwrap();
+        let bytes = encode_message(&message, json.as_object().unwrap(), &registry).unwrap();
+
+        // Registered extensions are decoded under their JSON keys
+        let decoded = decode_message(&message, &bytes, &registry).unwrap();
+        assert_eq!(Value::Object(decoded), json);
+
+        // Without the registry, extension fields are skipped like unknown fields
+        let decoded = decode_message(&message, &bytes, &ExtensionRegistry::new()).unwrap();
+        assert_eq!(decoded.get("name"), Some(&Value::String("task".to_string())));
+        assert!(decoded.get("[example.priority]").is_none());
+    }
+
+    #[test]
+    fn test_group_roundtrip() {
         let schema = r#"[
-            {"n": 1, "name": "name", "type": "string"},
-            {"n": 2, "name": "id", "type": "int32"}
+            {"n": 1, "name": "id", "type": "int32"},
+            {"n": 2, "name": "result", "type": "group", "repeated": true, "fields": [
+                {"n": 3, "name": "url", "type": "string"}
+            ]}
         ]"#;
+        let message = parse_schema(schema).unwrap();
+        let json: Value = serde_json::from_str(r#"{"id": 7, "result": [{"url": "a"}, {"url": "b"}]}"#).unwrap();
+
+        let bytes = encode_message(&message, json.as_object().unwrap(), &ExtensionRegistry::new()).unwrap();
+        assert_eq!(
+            bytes,
+            vec![0x08, 7, 0x13, 0x1a, 1, b'a', 0x14, 0x13, 0x1a, 1, b'b', 0x14]
+        );
+
+        let decoded = decode_message(&message, &bytes, &ExtensionRegistry::new()).unwrap();
+        assert_eq!(Value::Object(decoded), json);
+
+        // Unknown groups are skipped as a whole and kept as raw bytes
+        let only_id = parse_schema(r#"[{"n": 1, "name": "id", "type": "int32"}]"#).unwrap();
+        let decoded = decode_message(&only_id, &bytes, &ExtensionRegistry::new()).unwrap();
+        assert_eq!(decoded["id"], 7);
+        assert_eq!(
+            base64_decode(decoded[UNKNOWN_FIELDS_KEY].as_str().unwrap()).unwrap(),
+            bytes[2..].to_vec()
+        );
+
+        // Unterminated or mismatched groups are errors
+        let registry = ExtensionRegistry::new();
+        assert!(decode_message(&message, &bytes[..bytes.len() - 1], &registry).is_err());
+        assert!(decode_message(&only_id, &[0x13, 0x1c], &registry).is_err());
+    }
 
-        let json = r#"{"name": "Alice", "id": 42}"#;
+    #[test]
+    fn test_group_recursion_limit() {
+        let registry = ExtensionRegistry::new();
+        let only_id = parse_schema(r#"[{"n": 1, "name": "id", "type": "int32"}]"#).unwrap();
+        // Unknown group 2 nested `depth` times: START_GROUP tags, then END_GROUP tags
+        let nested = |depth: usize| [vec![0x13; depth], vec![0x14; depth]].concat();
+
+        assert!(decode_message(&only_id, &nested(100), &registry).is_ok());
+        let err = decode_message(&only_id, &nested(101), &registry).unwrap_err();
+        assert!(err.contains("Recursion limit"), "{}", err);
+
+        // Deep enough to overflow the stack without a limit
+        assert!(decode_message(&only_id, &vec![0x13; 1_000_000], &registry).is_err());
+
+        // Schema groups count towards the same limit
+        let group = parse_schema(
+            r#"[{"n": 1, "name": "g", "type": "group", "fields": [{"n": 1, "name": "g", "type": "group", "fields": []}]}]"#,
+        )
+        .unwrap();
+        assert!(decode_message(&group, &[0x0b, 0x0b, 0x0c, 0x0c], &registry).is_ok());
+        let deep = [vec![0x0b, 0x0b], vec![0x13; 99], vec![0x14; 99], vec![0x0c, 0x0c]].concat();
+        assert!(decode_message(&group, &deep, &registry).is_err());
+    }
 
-        let encoded = encode(schema, json).unwrap();
-        let decoded = decode(schema, &encoded).unwrap();
+    #[test]
+    fn test_message_recursion_limit() {
+        let registry = ExtensionRegistry::new();
+        // Field 1 holds a message of the same shape `levels` times
+        let schema = |levels: usize| {
+            (0..levels).fold(MessageDescriptor::default(), |inner, _| MessageDescriptor {
+                type_name: None,
+                fields: vec![FieldDescriptor {
+                    number: 1,
+                    name: "m".to_string(),
+                    field_type: FieldType::Message(inner),
+                    is_repeated: false,
+                    is_optional: false,
+                    is_packed: false,
+                    oneof: None,
+                }],
+            })
+        };
+        let nested = |levels: usize, inner: Vec<u8>| {
+            (0..levels).fold(inner, |inner, _| {
+                let mut bytes = vec![0x0a];
+                bytes.write_varint(inner.len()).unwrap();
+                [bytes, inner].concat()
+            })
+        };
+
+        assert!(decode_message(&schema(100), &nested(100, vec![]), &registry).is_ok());
+        let err = decode_message(&schema(101), &nested(101, vec![]), &registry).unwrap_err();
+        assert!(err.contains("Recursion limit"), "{}", err);
+
+        // Groups inside nested messages share the depth of their messages
+        let groups = |depth: usize| [vec![0x13; depth], vec![0x14; depth]].concat();
+        assert!(decode_message(&schema(60), &nested(60, groups(40)), &registry).is_ok());
+        let err = decode_message(&schema(60), &nested(60, groups(41)), &registry).unwrap_err();
+        assert!(err.contains("Recursion limit"), "{}", err);
+    }
 
-        let original: Value = serde_json::from_str(json).unwrap();
-        let result: Value = serde_json::from_str(&decoded).unwrap();
+    #[test]
+    fn test_well_known_recursion_limit() {
+        let schema = |type_name: &str| {
+            Schema::parse(&format!(r#"[{{"n": 1, "name": "v", "type": "message", "typeName": "{}"}}]"#, type_name)).unwrap()
+        };
+        let len_field = |tag: u8, inner: Vec<u8>| {
+            let mut bytes = vec![tag];
+            bytes.write_varint(inner.len()).unwrap();
+            [bytes, inner].concat()
+        };
+
+        // Value.list_value -> ListValue.values -> Value ... 1000 levels deep, about 7 KB
+        let value = (0..1000).fold(vec![], |inner, _| len_field(0x32, len_field(0x0a, inner)));
+        let err = decode(&schema("google.protobuf.Value"), &len_field(0x0a, value)).unwrap_err();
+        assert_eq!(err, CodecError::Decode("Recursion limit reached".to_string()));
+        // The innermost Value is empty, which is null
+        let shallow = (0..10).fold(vec![], |inner, _| len_field(0x32, len_field(0x0a, inner)));
+        let decoded = decode(&schema("google.protobuf.Value"), &len_field(0x0a, shallow)).unwrap();
+        assert_eq!(decoded["v"], serde_json::json!([[[[[[[[[[null]]]]]]]]]]));
+
+        // Any packing an Any packing an Any ...
+        let type_url = b"type.googleapis.com/google.protobuf.Any";
+        let any = (0..1000).fold(vec![], |inner, _| [len_field(0x0a, type_url.to_vec()), len_field(0x12, inner)].concat());
+        let err = decode(&schema("google.protobuf.Any"), &len_field(0x0a, any)).unwrap_err();
+        assert_eq!(err, CodecError::Decode("Recursion limit reached".to_string()));
+    }
 
-        assert_eq!(original["name"], result["name"]);
-        assert_eq!(original["id"], result["id"]);
+    #[test]
+    fn test_unknown_fields_roundtrip() {
+        let registry = ExtensionRegistry::new();
+        let full = parse_schema(
+            r#"[
+                {"n": 1, "name": "name", "type": "string"},
+                {"n": 2, "name": "score", "type": "sint64"},
+                {"n": 3, "name": "ratio", "type": "float"},
+                {"n": 4, "name": "inner", "type": "message", "fields": [
+                    {"n": 1, "name": "flag", "type": "bool"},
+                    {"n": 2, "name": "note", "type": "string"}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+        // An older schema that only knows "name" and part of "inner"
+        let old = parse_schema(
+            r#"[
+                {"n": 1, "name": "name", "type": "string"},
+                {"n": 4, "name": "inner", "type": "message", "fields": [
+                    {"n": 1, "name": "flag", "type": "bool"}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+
+        let json = serde_json::json!({
+            "name": "x",
+            "score": "-5",
+            "ratio": 0.5,
+            "inner": {"flag": true, "note": "kept"}
+        });
+        let bytes = encode_message(&full, json.as_object().unwrap(), &registry).unwrap();
+
+        // Decode with the old schema, modify a known field, re-encode
+        let mut decoded = decode_message(&old, &bytes, &registry).unwrap();
+        assert!(decoded.contains_key(UNKNOWN_FIELDS_KEY));
+        assert!(decoded["inner"].as_object().unwrap().contains_key(UNKNOWN_FIELDS_KEY));
+        decoded.insert("name".to_string(), Value::String("y".to_string()));
+        let reencoded = encode_message(&old, &decoded, &registry).unwrap();
+
+        // Fields unknown to the old schema survive the round-trip
+        let result = decode_message(&full, &reencoded, &registry).unwrap();
+        assert_eq!(result["name"], "y");
+        assert_eq!(result["score"], "-5");
+        assert_eq!(result["ratio"], 0.5);
+        assert_eq!(result["inner"], serde_json::json!({"flag": true, "note": "kept"}));
+    }
+
+    #[test]
+    fn test_oneof() {
+        let registry = ExtensionRegistry::new();
+        let message = parse_schema(
+            r#"[
+                {"n": 1, "name": "side", "type": "int32", "oneof": "kind"},
+                {"n": 2, "name": "label", "type": "string", "oneof": "kind", "variant": "Text"}
+            ]"#,
+        )
+        .unwrap();
+
+        // Last member on the wire wins: side = 3, then label = "a"
+        let decoded = decode_message(&message, &[0x08, 3, 0x12, 1, b'a'], &registry).unwrap();
+        assert_eq!(Value::Object(decoded), serde_json::json!({"label": "a"}));
+
+        // Members keep their presence, so zero values are still written
+        let flat = serde_json::json!({"side": 0});
+        assert_eq!(encode_message(&message, flat.as_object().unwrap(), &registry).unwrap(), vec![0x08, 0]);
+        let variant = serde_json::json!({"kind": {"TAG": "Side", "_0": 0}});
+        assert_eq!(encode_message(&message, variant.as_object().unwrap(), &registry).unwrap(), vec![0x08, 0]);
+
+        let both = serde_json::json!({"side": 1, "label": "a"});
+        assert_eq!(
+            encode_message(&message, both.as_object().unwrap(), &registry).unwrap_err(),
+            "Oneof kind has more than one member set: side, label"
+        );
+        let unknown = serde_json::json!({"kind": {"TAG": "Label", "_0": "a"}});
+        assert_eq!(
+            encode_message(&message, unknown.as_object().unwrap(), &registry).unwrap_err(),
+            "Unknown variant Label for oneof kind"
+        );
+    }
+
+    #[test]
+    fn test_oneof_generated_schema() {
+        // The schema `Shape.wasmSchema` in runtime/tests/generated sends; the
+        // decoded values are the ones runtime/tests/WasmCodec_test.res feeds
+        // through the generated `Shape.decode`
+        let registry = ExtensionRegistry::new();
+        let message = parse_schema(
+            r#"[
+                {"n": 1, "name": "id", "type": "string", "repeated": false, "optional": false},
+                {"n": 2, "name": "circle", "type": "message", "repeated": false, "optional": false, "fields": [
+                    {"n": 1, "name": "radius", "type": "double", "repeated": false, "optional": false}
+                ], "oneof": "kind", "variant": "Circle"},
+                {"n": 3, "name": "side", "type": "double", "repeated": false, "optional": false, "oneof": "kind", "variant": "Side"},
+                {"n": 4, "name": "color", "type": "enum", "repeated": false, "optional": false, "oneof": "kind", "variant": "Color"},
+                {"n": 5, "name": "label", "type": "string", "repeated": false, "optional": false, "oneof": "kind", "variant": "Label"},
+                {"n": 6, "name": "z_index", "type": "int32", "repeated": false, "optional": true}
+            ]"#,
+        )
+        .unwrap();
+
+        // id = "s1", circle = {} (radius left at its default)
+        let decoded = decode_message(&message, &[0x0a, 2, b's', b'1', 0x12, 0], &registry).unwrap();
+        assert_eq!(Value::Object(decoded), serde_json::json!({"id": "s1", "circle": {}}));
+
+        // id left at its default, label = "a", z_index = 0
+        let decoded = decode_message(&message, &[0x2a, 1, b'a', 0x30, 0], &registry).unwrap();
+        assert_eq!(Value::Object(decoded), serde_json::json!({"label": "a", "z_index": 0}));
+    }
+
+    #[test]
+    fn test_well_known_generated_schema() {
+        // `AuditLog.wasmSchema` in runtime/tests/generated/basic; the decoded
+        // JSON is what runtime/tests/WasmCodec_test.res compares with fromJson
+        let registry = ExtensionRegistry::new();
+        let message = parse_schema(
+            r#"[
+                {"n": 1, "name": "id", "type": "string", "repeated": false, "optional": false},
+                {"n": 2, "name": "action", "type": "string", "repeated": false, "optional": false},
+                {"n": 3, "name": "created_at", "type": "message", "repeated": false, "optional": false, "typeName": "google.protobuf.Timestamp"},
+                {"n": 4, "name": "duration", "type": "message", "repeated": false, "optional": false, "typeName": "google.protobuf.Duration"},
+                {"n": 5, "name": "description", "type": "message", "repeated": false, "optional": false, "typeName": "google.protobuf.StringValue"},
+                {"n": 6, "name": "response_code", "type": "message", "repeated": false, "optional": false, "typeName": "google.protobuf.Int64Value"}
+            ]"#,
+        )
+        .unwrap();
+
+        let bytes = [
+            0x0a, 0x02, 0x61, 0x31, 0x12, 0x01, 0x78, 0x1a, 0x0c, 0x08, 0x80, 0xe2, 0xcf, 0xaa, 0x06, 0x10, 0x80, 0xca,
+            0xb5, 0xee, 0x01, 0x22, 0x08, 0x08, 0x01, 0x10, 0x80, 0xca, 0xb5, 0xee, 0x01, 0x2a, 0x03, 0x0a, 0x01, 0x64,
+            0x32, 0x03, 0x08, 0xc8, 0x01,
+        ];
+        let decoded = decode_message(&message, &bytes, &registry).unwrap();
+        assert_eq!(
+            Value::Object(decoded),
+            serde_json::json!({
+                "id": "a1",
+                "action": "x",
+                "created_at": "2023-11-14T22:13:20.500Z",
+                "duration": "1.500s",
+                "description": "d",
+                "response_code": "200"
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_schema_object_form() {
+        let message = parse_schema(
+            r#"{"typeName": ".example.Annotated", "fields": [
+                {"n": 1, "name": "inner", "type": "message", "typeName": "example.Inner", "fields": []}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(message.type_name.as_deref(), Some("example.Annotated"));
+        match &message.fields[0].field_type {
+            FieldType::Message(nested) => assert_eq!(nested.type_name.as_deref(), Some("example.Inner")),
+            _ => panic!("expected message field"),
+        }
     }
 
     #[test]
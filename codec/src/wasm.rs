@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+//! wasm-bindgen exports used by the ReScript runtime (`WasmCodec.res`)
+//!
+//! Thin wrappers over the Rust API: schemas and values travel as JSON
+//! strings, binary data as base64, and errors as JS strings. The `_object`
+//! variants take and return JS values and `Uint8Array`s instead, skipping
+//! `JSON.stringify`/`JSON.parse` and base64 for large messages.
+
+use std::cell::RefCell;
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, base64_encode, CodecError, ExtensionRegistry, Schema};
+
+thread_local! {
+    /// Extensions and Any types registered from JS via `register_extension`
+    /// and `register_type`
+    static EXTENSIONS: RefCell<ExtensionRegistry> = RefCell::new(ExtensionRegistry::new());
+}
+
+fn js_error(error: CodecError) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+/// Encode JSON to protobuf binary (returns base64)
+///
+/// The schema parameter is a JSON array of field descriptors:
+/// [{"n": 1, "name": "field_name", "type": "string", "repeated": false}, ...]
+///
+/// Fields may also set "optional" (explicit presence), "packed" (defaults
+/// to true for repeated numeric fields) and "oneof" (group name, with an
+/// optional "variant" constructor name).
+///
+/// Messages that carry extensions use the object form instead:
+/// {"typeName": "example.User", "fields": [...]}
+#[wasm_bindgen]
+pub fn encode(schema: &str, json_data: &str) -> Result<String, JsValue> {
+    let schema = Schema::parse(schema).map_err(js_error)?;
+    let json: Value = serde_json::from_str(json_data)
+        .map_err(|e| JsValue::from_str(&format!("JSON parse error: {}", e)))?;
+
+    let bytes = EXTENSIONS
+        .with(|registry| crate::encode_with_extensions(&schema, &json, &registry.borrow()))
+        .map_err(js_error)?;
+
+    Ok(base64_encode(&bytes))
+}
+
+/// Decode protobuf binary (base64) to JSON
+#[wasm_bindgen]
+pub fn decode(schema: &str, base64_data: &str) -> Result<String, JsValue> {
+    let schema = Schema::parse(schema).map_err(js_error)?;
+    let bytes = base64_decode(base64_data)
+        .map_err(|e| JsValue::from_str(&e))?;
+
+    let json = EXTENSIONS
+        .with(|registry| crate::decode_with_extensions(&schema, &bytes, &registry.borrow()))
+        .map_err(js_error)?;
+
+    serde_json::to_string(&json)
+        .map_err(|e| JsValue::from_str(&format!("JSON serialize error: {}", e)))
+}
+
+/// Encode a JS object to protobuf binary, reading it without `JSON.stringify`
+///
+/// Takes the same schema and field values as `encode`.
+#[wasm_bindgen]
+pub fn encode_object(schema: &str, message: JsValue) -> Result<Vec<u8>, JsValue> {
+    let schema = Schema::parse(schema).map_err(js_error)?;
+    let json: Value = serde_wasm_bindgen::from_value(message)?;
+
+    EXTENSIONS
+        .with(|registry| crate::encode_with_extensions(&schema, &json, &registry.borrow()))
+        .map_err(js_error)
+}
+
+/// Decode protobuf binary straight to a JS object, without `JSON.parse`
+#[wasm_bindgen]
+pub fn decode_object(schema: &str, data: &[u8]) -> Result<JsValue, JsValue> {
+    let schema = Schema::parse(schema).map_err(js_error)?;
+
+    let json = EXTENSIONS
+        .with(|registry| crate::decode_with_extensions(&schema, data, &registry.borrow()))
+        .map_err(js_error)?;
+
+    // Plain objects rather than Maps, as JSON.parse would produce
+    Ok(json.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// Register an extension field for the given extendee
+///
+/// The field schema uses the same format as a single schema entry, with the
+/// proto3 JSON key as its name:
+/// {"n": 100, "name": "[example.priority]", "type": "int32"}
+#[wasm_bindgen]
+pub fn register_extension(extendee: &str, field_schema: &str) -> Result<(), JsValue> {
+    EXTENSIONS.with(|registry| registry.borrow_mut().register_schema(extendee, field_schema))
+        .map_err(js_error)
+}
+
+/// Register a message type that may be packed into `google.protobuf.Any`
+///
+/// Takes a schema in the object form, {"typeName": "example.User", "fields": [...]};
+/// Any values of that type then carry its fields inline next to "@type".
+#[wasm_bindgen]
+pub fn register_type(schema: &str) -> Result<(), JsValue> {
+    let schema = Schema::parse(schema).map_err(js_error)?;
+    EXTENSIONS.with(|registry| registry.borrow_mut().register_type(&schema))
+        .map_err(js_error)
+}
+
+/// Remove all registered extensions and Any types
+#[wasm_bindgen]
+pub fn clear_extensions() {
+    EXTENSIONS.with(|registry| registry.borrow_mut().clear());
+}
@@ -0,0 +1,652 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Well-known types (`google.protobuf.*`)
+//!
+//! Fields of these types use the proto3 JSON representation, the same one the
+//! generated `WellKnown.*` JSON codecs read and write:
+//!
+//! - Timestamp: RFC 3339 string, `"2017-01-15T01:30:15.010Z"`
+//! - Duration: seconds with an `s` suffix, `"1.5s"`
+//! - wrappers: the bare value (64-bit integers and bytes as strings)
+//! - Struct, Value, ListValue: plain JSON objects, values and arrays
+//! - FieldMask: comma-separated camelCase paths, `"user.displayName,id"`
+//! - Any: `{"@type": url, "value": ...}` for packed well-known types, and
+//!   the message fields inline next to `@type` for types registered with
+//!   `ExtensionRegistry::register_type`; others keep their encoded fields
+//!   under `$unknown`
+//! - Empty: `{}`
+//!
+//! Each type is encoded through a descriptor of its underlying message, with
+//! the JSON converted on the way in and out.
+
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    base64_decode, base64_encode, decode_nested, encode_message, ExtensionRegistry, FieldDescriptor, FieldType,
+    MessageDescriptor, OneofMember, UNKNOWN_FIELDS_KEY,
+};
+
+/// 0001-01-01T00:00:00Z and 9999-12-31T23:59:59Z, the range RFC 3339 can express
+const MIN_TIMESTAMP_SECONDS: i64 = -62_135_596_800;
+const MAX_TIMESTAMP_SECONDS: i64 = 253_402_300_799;
+/// About 10,000 years, as documented in duration.proto
+const MAX_DURATION_SECONDS: i64 = 315_576_000_000;
+const NANOS_PER_SECOND: i32 = 1_000_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WellKnownType {
+    Timestamp,
+    Duration,
+    DoubleValue,
+    FloatValue,
+    Int64Value,
+    UInt64Value,
+    Int32Value,
+    UInt32Value,
+    BoolValue,
+    StringValue,
+    BytesValue,
+    Struct,
+    Value,
+    ListValue,
+    FieldMask,
+    Any,
+    Empty,
+}
+
+impl WellKnownType {
+    /// Well-known type for a fully-qualified message name (leading `.` optional)
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        let well_known = match type_name.trim_start_matches('.').strip_prefix("google.protobuf.")? {
+            "Timestamp" => WellKnownType::Timestamp,
+            "Duration" => WellKnownType::Duration,
+            "DoubleValue" => WellKnownType::DoubleValue,
+            "FloatValue" => WellKnownType::FloatValue,
+            "Int64Value" => WellKnownType::Int64Value,
+            "UInt64Value" => WellKnownType::UInt64Value,
+            "Int32Value" => WellKnownType::Int32Value,
+            "UInt32Value" => WellKnownType::UInt32Value,
+            "BoolValue" => WellKnownType::BoolValue,
+            "StringValue" => WellKnownType::StringValue,
+            "BytesValue" => WellKnownType::BytesValue,
+            "Struct" => WellKnownType::Struct,
+            "Value" => WellKnownType::Value,
+            "ListValue" => WellKnownType::ListValue,
+            "FieldMask" => WellKnownType::FieldMask,
+            "Any" => WellKnownType::Any,
+            "Empty" => WellKnownType::Empty,
+            _ => return None,
+        };
+        Some(well_known)
+    }
+
+    pub fn type_name(self) -> &'static str {
+        match self {
+            WellKnownType::Timestamp => "google.protobuf.Timestamp",
+            WellKnownType::Duration => "google.protobuf.Duration",
+            WellKnownType::DoubleValue => "google.protobuf.DoubleValue",
+            WellKnownType::FloatValue => "google.protobuf.FloatValue",
+            WellKnownType::Int64Value => "google.protobuf.Int64Value",
+            WellKnownType::UInt64Value => "google.protobuf.UInt64Value",
+            WellKnownType::Int32Value => "google.protobuf.Int32Value",
+            WellKnownType::UInt32Value => "google.protobuf.UInt32Value",
+            WellKnownType::BoolValue => "google.protobuf.BoolValue",
+            WellKnownType::StringValue => "google.protobuf.StringValue",
+            WellKnownType::BytesValue => "google.protobuf.BytesValue",
+            WellKnownType::Struct => "google.protobuf.Struct",
+            WellKnownType::Value => "google.protobuf.Value",
+            WellKnownType::ListValue => "google.protobuf.ListValue",
+            WellKnownType::FieldMask => "google.protobuf.FieldMask",
+            WellKnownType::Any => "google.protobuf.Any",
+            WellKnownType::Empty => "google.protobuf.Empty",
+        }
+    }
+
+    /// Descriptor of the underlying message
+    fn descriptor(self) -> MessageDescriptor {
+        let fields = match self {
+            WellKnownType::Timestamp | WellKnownType::Duration => {
+                vec![field(1, "seconds", FieldType::Int64), field(2, "nanos", FieldType::Int32)]
+            }
+            WellKnownType::DoubleValue => vec![field(1, "value", FieldType::Double)],
+            WellKnownType::FloatValue => vec![field(1, "value", FieldType::Float)],
+            WellKnownType::Int64Value => vec![field(1, "value", FieldType::Int64)],
+            WellKnownType::UInt64Value => vec![field(1, "value", FieldType::Uint64)],
+            WellKnownType::Int32Value => vec![field(1, "value", FieldType::Int32)],
+            WellKnownType::UInt32Value => vec![field(1, "value", FieldType::Uint32)],
+            WellKnownType::BoolValue => vec![field(1, "value", FieldType::Bool)],
+            WellKnownType::StringValue => vec![field(1, "value", FieldType::String)],
+            WellKnownType::BytesValue => vec![field(1, "value", FieldType::Bytes)],
+            WellKnownType::Struct => {
+                // map<string, Value>, as repeated key/value entries
+                let entry = MessageDescriptor {
+                    type_name: None,
+                    fields: vec![
+                        field(1, "key", FieldType::String),
+                        field(2, "value", FieldType::WellKnown(WellKnownType::Value)),
+                    ],
+                };
+                vec![repeated(field(1, "fields", FieldType::Message(entry)))]
+            }
+            WellKnownType::Value => vec![
+                kind(field(1, "nullValue", FieldType::Enum)),
+                kind(field(2, "numberValue", FieldType::Double)),
+                kind(field(3, "stringValue", FieldType::String)),
+                kind(field(4, "boolValue", FieldType::Bool)),
+                kind(field(5, "structValue", FieldType::WellKnown(WellKnownType::Struct))),
+                kind(field(6, "listValue", FieldType::WellKnown(WellKnownType::ListValue))),
+            ],
+            WellKnownType::ListValue => {
+                vec![repeated(field(1, "values", FieldType::WellKnown(WellKnownType::Value)))]
+            }
+            WellKnownType::FieldMask => vec![repeated(field(1, "paths", FieldType::String))],
+            WellKnownType::Any => vec![field(1, "typeUrl", FieldType::String), field(2, "value", FieldType::Bytes)],
+            WellKnownType::Empty => vec![],
+        };
+        MessageDescriptor {
+            type_name: Some(self.type_name().to_string()),
+            fields,
+        }
+    }
+
+    /// Convert the JSON representation to the JSON of the underlying message
+    fn message_json(self, value: &Value, registry: &ExtensionRegistry) -> Result<Map<String, Value>, String> {
+        let message = match self {
+            WellKnownType::Timestamp => {
+                let text = value.as_str().ok_or("Timestamp must be a string")?;
+                let (seconds, nanos) = parse_timestamp(text)?;
+                seconds_nanos(seconds, nanos)
+            }
+            WellKnownType::Duration => {
+                let text = value.as_str().ok_or("Duration must be a string")?;
+                let (seconds, nanos) = parse_duration(text)?;
+                seconds_nanos(seconds, nanos)
+            }
+            WellKnownType::DoubleValue
+            | WellKnownType::FloatValue
+            | WellKnownType::Int64Value
+            | WellKnownType::UInt64Value
+            | WellKnownType::Int32Value
+            | WellKnownType::UInt32Value
+            | WellKnownType::BoolValue
+            | WellKnownType::StringValue
+            | WellKnownType::BytesValue => object([("value", value.clone())]),
+            WellKnownType::Struct => {
+                let fields = value.as_object().ok_or("Struct must be an object")?;
+                let entries = fields
+                    .iter()
+                    .map(|(key, value)| Value::Object(object([("key", Value::String(key.clone())), ("value", value.clone())])))
+                    .collect();
+                object([("fields", Value::Array(entries))])
+            }
+            WellKnownType::Value => {
+                let member = match value {
+                    Value::Null => ("nullValue", Value::from(0)),
+                    Value::Number(_) => ("numberValue", value.clone()),
+                    Value::String(_) => ("stringValue", value.clone()),
+                    Value::Bool(_) => ("boolValue", value.clone()),
+                    Value::Object(_) => ("structValue", value.clone()),
+                    Value::Array(_) => ("listValue", value.clone()),
+                };
+                object([member])
+            }
+            WellKnownType::ListValue => {
+                let values = value.as_array().ok_or("ListValue must be an array")?;
+                object([("values", Value::Array(values.clone()))])
+            }
+            WellKnownType::FieldMask => {
+                let paths = value.as_str().ok_or("FieldMask must be a string")?;
+                let paths = paths
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(|p| Value::String(snake_case_path(p)))
+                    .collect();
+                object([("paths", Value::Array(paths))])
+            }
+            WellKnownType::Any => {
+                let any = value.as_object().ok_or("Any must be an object")?;
+                let type_url = any.get("@type").and_then(|v| v.as_str()).ok_or("Any requires an @type")?;
+                let type_name = any_type_name(type_url);
+                let packed = match (WellKnownType::from_type_name(type_name), registry.find_type(type_name)) {
+                    (Some(well_known), _) => encode(well_known, any.get("value").unwrap_or(&Value::Null), registry)?,
+                    (None, Some(message)) => {
+                        let mut fields = any.clone();
+                        fields.remove("@type");
+                        encode_message(message, &fields, registry)?
+                    }
+                    (None, None) => {
+                        if let Some(key) = any.keys().find(|k| *k != "@type" && *k != UNKNOWN_FIELDS_KEY) {
+                            return Err(format!("Cannot encode field {} of Any with unregistered type {}", key, type_url));
+                        }
+                        match any.get(UNKNOWN_FIELDS_KEY).and_then(|v| v.as_str()) {
+                            Some(raw) => base64_decode(raw)?,
+                            None => Vec::new(),
+                        }
+                    }
+                };
+                object([
+                    ("typeUrl", Value::String(type_url.to_string())),
+                    ("value", Value::String(base64_encode(&packed))),
+                ])
+            }
+            WellKnownType::Empty => Map::new(),
+        };
+        Ok(message)
+    }
+
+    /// Convert the decoded JSON of the underlying message to the JSON representation
+    ///
+    /// `depth` is the nesting of this message; an Any's packed message is one deeper.
+    fn well_known_json(self, message: Map<String, Value>, registry: &ExtensionRegistry, depth: u32) -> Result<Value, String> {
+        let get = |name: &str| message.get(name).cloned();
+        let value = match self {
+            WellKnownType::Timestamp => {
+                let (seconds, nanos) = read_seconds_nanos(&message)?;
+                Value::String(format_timestamp(seconds, nanos)?)
+            }
+            WellKnownType::Duration => {
+                let (seconds, nanos) = read_seconds_nanos(&message)?;
+                Value::String(format_duration(seconds, nanos)?)
+            }
+            // Absent wrapper values are the default of the wrapped type
+            WellKnownType::DoubleValue | WellKnownType::FloatValue => {
+                get("value").unwrap_or_else(|| Value::Number(Number::from_f64(0.0).unwrap()))
+            }
+            WellKnownType::Int64Value | WellKnownType::UInt64Value => {
+                get("value").unwrap_or_else(|| Value::String("0".to_string()))
+            }
+            WellKnownType::Int32Value | WellKnownType::UInt32Value => get("value").unwrap_or_else(|| Value::from(0)),
+            WellKnownType::BoolValue => get("value").unwrap_or(Value::Bool(false)),
+            WellKnownType::StringValue | WellKnownType::BytesValue => {
+                get("value").unwrap_or_else(|| Value::String(String::new()))
+            }
+            WellKnownType::Struct => {
+                let mut fields = Map::new();
+                for entry in message.get("fields").and_then(|v| v.as_array()).into_iter().flatten() {
+                    let key = entry.get("key").and_then(|v| v.as_str()).unwrap_or("");
+                    fields.insert(key.to_string(), entry.get("value").cloned().unwrap_or(Value::Null));
+                }
+                Value::Object(fields)
+            }
+            // At most one `kind` member is decoded; nullValue and no member are null
+            WellKnownType::Value => ["numberValue", "stringValue", "boolValue", "structValue", "listValue"]
+                .iter()
+                .find_map(|key| message.get(*key).cloned())
+                .unwrap_or(Value::Null),
+            WellKnownType::ListValue => get("values").unwrap_or_else(|| Value::Array(Vec::new())),
+            WellKnownType::FieldMask => {
+                let paths: Vec<String> = message
+                    .get("paths")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|p| p.as_str())
+                    .map(camel_case_path)
+                    .collect();
+                Value::String(paths.join(","))
+            }
+            WellKnownType::Any => {
+                let type_url = message.get("typeUrl").and_then(|v| v.as_str()).unwrap_or("");
+                let packed = match message.get("value").and_then(|v| v.as_str()) {
+                    Some(encoded) => base64_decode(encoded)?,
+                    None => Vec::new(),
+                };
+                let type_name = any_type_name(type_url);
+                let mut any = match (WellKnownType::from_type_name(type_name), registry.find_type(type_name)) {
+                    (Some(well_known), _) => object([("value", decode(well_known, &packed, registry, depth + 1)?)]),
+                    (None, Some(message)) => decode_nested(message, &packed, registry, depth + 1)?,
+                    (None, None) if !packed.is_empty() => {
+                        object([(UNKNOWN_FIELDS_KEY, Value::String(base64_encode(&packed)))])
+                    }
+                    (None, None) => Map::new(),
+                };
+                any.insert("@type".to_string(), Value::String(type_url.to_string()));
+                Value::Object(any)
+            }
+            WellKnownType::Empty => Value::Object(Map::new()),
+        };
+        Ok(value)
+    }
+}
+
+/// Encode the JSON representation of a well-known type to its message bytes
+pub(crate) fn encode(well_known: WellKnownType, value: &Value, registry: &ExtensionRegistry) -> Result<Vec<u8>, String> {
+    let message = well_known.message_json(value, registry)?;
+    encode_message(&well_known.descriptor(), &message, registry)
+}
+
+/// Decode message bytes of a well-known type, nested `depth` messages deep,
+/// to its JSON representation
+pub(crate) fn decode(
+    well_known: WellKnownType,
+    data: &[u8],
+    registry: &ExtensionRegistry,
+    depth: u32,
+) -> Result<Value, String> {
+    let message = decode_nested(&well_known.descriptor(), data, registry, depth)?;
+    well_known.well_known_json(message, registry, depth)
+}
+
+fn field(number: u32, name: &str, field_type: FieldType) -> FieldDescriptor {
+    FieldDescriptor {
+        number,
+        name: name.to_string(),
+        field_type,
+        is_repeated: false,
+        is_optional: false,
+        is_packed: true,
+        oneof: None,
+    }
+}
+
+fn repeated(field: FieldDescriptor) -> FieldDescriptor {
+    FieldDescriptor { is_repeated: true, ..field }
+}
+
+/// Member of `google.protobuf.Value`'s `kind` oneof
+fn kind(field: FieldDescriptor) -> FieldDescriptor {
+    let variant = crate::capitalize(&field.name);
+    FieldDescriptor {
+        oneof: Some(OneofMember { group: "kind".to_string(), variant }),
+        ..field
+    }
+}
+
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Map<String, Value> {
+    entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+fn seconds_nanos(seconds: i64, nanos: i32) -> Map<String, Value> {
+    object([("seconds", Value::from(seconds)), ("nanos", Value::from(nanos))])
+}
+
+fn read_seconds_nanos(message: &Map<String, Value>) -> Result<(i64, i32), String> {
+    let seconds = match message.get("seconds").and_then(|v| v.as_str()) {
+        Some(s) => s.parse().map_err(|_| "Invalid seconds")?,
+        None => 0,
+    };
+    let nanos = message.get("nanos").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+    Ok((seconds, nanos))
+}
+
+/// Type name part of an Any type URL, `type.googleapis.com/google.protobuf.Duration`
+pub(crate) fn any_type_name(type_url: &str) -> &str {
+    type_url.rsplit('/').next().unwrap_or(type_url)
+}
+
+// ============================================================================
+// Timestamp and Duration strings
+// ============================================================================
+
+/// Fractional seconds with 0, 3, 6 or 9 digits, as proto3 JSON prints them
+fn fraction(nanos: i32) -> String {
+    if nanos == 0 {
+        String::new()
+    } else if nanos % 1_000_000 == 0 {
+        format!(".{:03}", nanos / 1_000_000)
+    } else if nanos % 1_000 == 0 {
+        format!(".{:06}", nanos / 1_000)
+    } else {
+        format!(".{:09}", nanos)
+    }
+}
+
+/// Parse up to nine fractional digits as nanoseconds
+fn parse_fraction(digits: &str) -> Option<i32> {
+    if digits.is_empty() || digits.len() > 9 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let scale = 10i32.pow(9 - digits.len() as u32);
+    digits.parse::<i32>().ok().map(|n| n * scale)
+}
+
+pub(crate) fn format_timestamp(seconds: i64, nanos: i32) -> Result<String, String> {
+    if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds) || !(0..NANOS_PER_SECOND).contains(&nanos) {
+        return Err(format!("Timestamp out of range: {}s {}ns", seconds, nanos));
+    }
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    Ok(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        fraction(nanos)
+    ))
+}
+
+/// Parse an RFC 3339 timestamp, `2017-01-15T01:30:15.01Z` or with a `+01:00` offset
+fn parse_timestamp(text: &str) -> Result<(i64, i32), String> {
+    let invalid = || format!("Invalid timestamp: {}", text);
+    let number = |start: usize, len: usize| -> Result<i64, String> {
+        text.get(start..start + len)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let separator = |index: usize, expected: &[u8]| text.as_bytes().get(index).is_some_and(|b| expected.contains(b));
+
+    if !(separator(4, b"-") && separator(7, b"-") && separator(10, b"Tt") && separator(13, b":") && separator(16, b":")) {
+        return Err(invalid());
+    }
+    let (year, month, day) = (number(0, 4)?, number(5, 2)?, number(8, 2)?);
+    let (hour, minute, second) = (number(11, 2)?, number(14, 2)?, number(17, 2)?);
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+
+    let mut rest = &text[19..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(|b| b.is_ascii_digit()).count();
+        nanos = parse_fraction(&fraction[..digits]).ok_or_else(invalid)?;
+        rest = &fraction[digits..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first() {
+                Some(b'+') => 1,
+                Some(b'-') => -1,
+                _ => return Err(invalid()),
+            };
+            let (hours, minutes) = match rest[1..].split_once(':') {
+                Some((h, m)) if h.len() == 2 && m.len() == 2 => (h, m),
+                _ => return Err(invalid()),
+            };
+            let hours: i64 = hours.parse().map_err(|_| invalid())?;
+            let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+            if hours > 23 || minutes > 59 {
+                return Err(invalid());
+            }
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds) {
+        return Err(format!("Timestamp out of range: {}", text));
+    }
+    Ok((seconds, nanos))
+}
+
+pub(crate) fn format_duration(seconds: i64, nanos: i32) -> Result<String, String> {
+    let mixed_signs = (seconds > 0 && nanos < 0) || (seconds < 0 && nanos > 0);
+    if seconds.abs() > MAX_DURATION_SECONDS || nanos.abs() >= NANOS_PER_SECOND || mixed_signs {
+        return Err(format!("Duration out of range: {}s {}ns", seconds, nanos));
+    }
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    Ok(format!("{}{}{}s", sign, seconds.unsigned_abs(), fraction(nanos.abs())))
+}
+
+/// Parse `"1.5s"`, `"-0.000000001s"` or `"3600s"`
+fn parse_duration(text: &str) -> Result<(i64, i32), String> {
+    let invalid = || format!("Invalid duration: {}", text);
+    let body = text.strip_suffix('s').ok_or_else(invalid)?;
+    let (negative, body) = match body.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, body),
+    };
+    let (whole, nanos) = match body.split_once('.') {
+        Some((whole, fraction)) => (whole, parse_fraction(fraction).ok_or_else(invalid)?),
+        None => (body, 0),
+    };
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let seconds: i64 = whole.parse().map_err(|_| invalid())?;
+    if seconds > MAX_DURATION_SECONDS {
+        return Err(format!("Duration out of range: {}", text));
+    }
+    Ok(if negative { (-seconds, -nanos) } else { (seconds, nanos) })
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// ============================================================================
+// FieldMask paths
+// ============================================================================
+
+/// `user.display_name` -> `user.displayName`
+fn camel_case_path(path: &str) -> String {
+    let mut result = String::with_capacity(path.len());
+    let mut upper_next = false;
+    for c in path.chars() {
+        if c == '_' {
+            upper_next = true;
+        } else if upper_next {
+            result.push(c.to_ascii_uppercase());
+            upper_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// `user.displayName` -> `user.display_name`
+fn snake_case_path(path: &str) -> String {
+    let mut result = String::with_capacity(path.len() + 4);
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            result.push('_');
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_strings() {
+        assert_eq!(format_timestamp(0, 0).unwrap(), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_484_443_815, 10_000_000).unwrap(), "2017-01-15T01:30:15.010Z");
+        assert_eq!(format_timestamp(951_782_400, 1_000).unwrap(), "2000-02-29T00:00:00.000001Z");
+        assert_eq!(format_timestamp(-1, 999_999_999).unwrap(), "1969-12-31T23:59:59.999999999Z");
+        assert_eq!(format_timestamp(MIN_TIMESTAMP_SECONDS, 0).unwrap(), "0001-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(MAX_TIMESTAMP_SECONDS, 0).unwrap(), "9999-12-31T23:59:59Z");
+        assert!(format_timestamp(MAX_TIMESTAMP_SECONDS + 1, 0).is_err());
+        assert!(format_timestamp(0, -1).is_err());
+
+        assert_eq!(parse_timestamp("2017-01-15T01:30:15.01Z").unwrap(), (1_484_443_815, 10_000_000));
+        assert_eq!(parse_timestamp("2017-01-15T02:30:15+01:00").unwrap(), (1_484_443_815, 0));
+        assert_eq!(parse_timestamp("1969-12-31t23:59:59.999999999z").unwrap(), (-1, 999_999_999));
+        for invalid in ["2017-01-15", "2017-02-29T00:00:00Z", "2017-01-15T01:30:15", "2017-01-15T01:30:15.Z", "2017-01-15T01:30:15.0123456789Z"] {
+            assert!(parse_timestamp(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_duration_strings() {
+        assert_eq!(format_duration(0, 0).unwrap(), "0s");
+        assert_eq!(format_duration(1, 500_000_000).unwrap(), "1.500s");
+        assert_eq!(format_duration(0, -1).unwrap(), "-0.000000001s");
+        assert_eq!(format_duration(-3600, 0).unwrap(), "-3600s");
+        assert!(format_duration(1, -1).is_err());
+        assert!(format_duration(MAX_DURATION_SECONDS + 1, 0).is_err());
+
+        assert_eq!(parse_duration("1.5s").unwrap(), (1, 500_000_000));
+        assert_eq!(parse_duration("-0.000000001s").unwrap(), (0, -1));
+        assert_eq!(parse_duration("3600s").unwrap(), (3600, 0));
+        for invalid in ["1.5", "s", "-s", "1.s", "+1s", "1e3s"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_nanosecond_round_trip() {
+        let schema = crate::Schema::parse(
+            r#"[{"n": 1, "name": "at", "type": "message", "typeName": "google.protobuf.Timestamp"},
+                {"n": 2, "name": "took", "type": "message", "typeName": "google.protobuf.Duration"}]"#,
+        )
+        .unwrap();
+        let round_trip = |at: (i64, i32), took: (i64, i32)| {
+            let value = serde_json::json!({
+                "at": format_timestamp(at.0, at.1).unwrap(),
+                "took": format_duration(took.0, took.1).unwrap(),
+            });
+            let decoded = crate::decode(&schema, &crate::encode(&schema, &value).unwrap()).unwrap();
+            (
+                parse_timestamp(decoded["at"].as_str().unwrap()).unwrap(),
+                parse_duration(decoded["took"].as_str().unwrap()).unwrap(),
+            )
+        };
+
+        // Entries 1ns apart keep their order, at both ends of the range
+        for at in [(MIN_TIMESTAMP_SECONDS, 1), (1_484_443_815, 10_000_000), (MAX_TIMESTAMP_SECONDS, 999_999_998)] {
+            let next = (at.0, at.1 + 1);
+            assert_eq!(round_trip(at, (0, -1)), (at, (0, -1)));
+            assert!(round_trip(at, (0, 0)).0 < round_trip(next, (0, 0)).0);
+        }
+        assert_eq!(round_trip((0, 0), (-MAX_DURATION_SECONDS, -999_999_999)).1, (-MAX_DURATION_SECONDS, -999_999_999));
+    }
+
+    #[test]
+    fn test_field_mask_paths() {
+        assert_eq!(camel_case_path("user.display_name"), "user.displayName");
+        assert_eq!(snake_case_path("user.displayName"), "user.display_name");
+    }
+}
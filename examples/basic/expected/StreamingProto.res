@@ -0,0 +1,833 @@
+// Generated from streaming.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: example
+
+/** User type for streaming (self-contained for testing) */
+module StreamUser = {
+  type t = {
+    id: int,
+    name: string,
+    email: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~name,
+    ~email,
+    ~unknownFields=?
+  ): t => {
+    id,
+    name,
+    email,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+        Json.Encode.required("email", msg.email, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let email = Json.Decode.required(obj, "email", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id, name, email) {
+        | (Ok(id), Ok(name), Ok(email)) =>
+          Some({
+            id,
+            name,
+            email,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Request for getting a user */
+module GetStreamUserRequest = {
+  type t = {
+    id: int,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~unknownFields=?
+  ): t => {
+    id,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id) {
+        | (Ok(id)) =>
+          Some({
+            id,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Request for listing users with streaming */
+module ListUsersStreamRequest = {
+  type t = {
+    pageSize: int,
+    pageToken: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~pageSize,
+    ~pageToken,
+    ~unknownFields=?
+  ): t => {
+    pageSize,
+    pageToken,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("page_size", msg.pageSize, Json.Encode.int),
+        Json.Encode.required("page_token", msg.pageToken, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let pageSize = Json.Decode.required(obj, "page_size", Json.Decode.int)
+        let pageToken = Json.Decode.required(obj, "page_token", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (pageSize, pageToken) {
+        | (Ok(pageSize), Ok(pageToken)) =>
+          Some({
+            pageSize,
+            pageToken,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Request for uploading multiple users */
+module UploadUsersRequest = {
+  type t = {
+    user: option<StreamUser.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~user=?,
+    ~unknownFields=?
+  ): t => {
+    user,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+      ],
+      [
+        Json.Encode.optional("user", msg.user, v => StreamUser.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let user = Json.Decode.optional(obj, "user", StreamUser.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        Some({
+          user,
+          unknownFields: ?unknownFields,
+        })
+    | None => None
+    }
+  }
+}
+
+
+/** Response for upload */
+module UploadUsersResponse = {
+  type t = {
+    uploadedCount: int,
+    failedIds: array<string>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~uploadedCount,
+    ~failedIds=[],
+    ~unknownFields=?
+  ): t => {
+    uploadedCount,
+    failedIds,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("uploaded_count", msg.uploadedCount, Json.Encode.int),
+      ],
+      [
+        Json.Encode.repeated("failed_ids", msg.failedIds, Json.Encode.string),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let uploadedCount = Json.Decode.required(obj, "uploaded_count", Json.Decode.int)
+        let failedIds = Json.Decode.repeated(obj, "failed_ids", Json.Decode.string)->Result.getOr([])
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (uploadedCount) {
+        | (Ok(uploadedCount)) =>
+          Some({
+            uploadedCount,
+            failedIds,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Chat message for bidirectional streaming */
+module ChatMessage = {
+  type t = {
+    id: string,
+    sender: string,
+    content: string,
+    timestamp: bigint,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~sender,
+    ~content,
+    ~timestamp,
+    ~unknownFields=?
+  ): t => {
+    id,
+    sender,
+    content,
+    timestamp,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.string),
+        Json.Encode.required("sender", msg.sender, Json.Encode.string),
+        Json.Encode.required("content", msg.content, Json.Encode.string),
+        Json.Encode.required("timestamp", msg.timestamp, Json.Encode.int64),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.string)
+        let sender = Json.Decode.required(obj, "sender", Json.Decode.string)
+        let content = Json.Decode.required(obj, "content", Json.Decode.string)
+        let timestamp = Json.Decode.required(obj, "timestamp", Json.Decode.int64)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id, sender, content, timestamp) {
+        | (Ok(id), Ok(sender), Ok(content), Ok(timestamp)) =>
+          Some({
+            id,
+            sender,
+            content,
+            timestamp,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** User service with streaming RPCs */
+module UserStreamingServiceClient = {
+  type error =
+    | NetworkError(string)
+    | GrpcError(int, string)
+    // google.rpc.Status from grpc-status-details-bin or an error JSON body
+    | StatusError(RpcStatus.t)
+    | DecodeError(string)
+    // The deadline passed before the response arrived
+    | DeadlineExceeded
+    // Aborted through the caller's signal
+    | Cancelled
+
+  // Stream handler for server-streaming RPCs
+  type streamHandler<'a> = {
+    onMessage: 'a => unit,
+    onError: error => unit,
+    onComplete: unit => unit,
+  }
+
+  // Cancellation handle for streaming RPCs
+  type streamCancel = {
+    cancel: unit => unit,
+  }
+
+  // Wraps calls to rewrite metadata, transform results or wrap stream handlers
+  type interceptor = Interceptor.t<error, streamHandler<Js.Json.t>, streamCancel>
+
+  // gRPC-web client configuration
+  type config = {
+    baseUrl: string,
+    headers: option<Js.Dict.t<string>>,
+    // Run around every call, the first one outermost
+    interceptors?: array<interceptor>,
+    // Default deadline of unary calls, sent as grpc-timeout
+    timeoutMs?: int,
+    // Retries of failed unary calls, see Retry.fromServiceConfig
+    retry?: Retry.config,
+  }
+
+  let defaultConfig = {
+    baseUrl: "http://localhost:8080",
+    headers: None,
+    retry: {Retry.policy: Retry.defaultPolicy},
+  }
+
+  // Service name in gRPC service configs
+  let serviceName = "example.UserStreamingService"
+
+  // gRPC status of a failed call, for retry policies
+  let retryStatus = (error: error): option<int> => {
+    switch error {
+    | NetworkError(_) => Some(14)
+    | GrpcError(status, _) => Some(Retry.codeOfHttpStatus(status))
+    | StatusError(status) => Some(status.code)
+    | DecodeError(_) | DeadlineExceeded | Cancelled => None
+    }
+  }
+
+  // Default headers merged with config.headers
+  let requestHeaders = (~config: config, ~accept: string): Js.Dict.t<string> => {
+    let headers = Js.Dict.fromArray([
+      ("Content-Type", "application/json"),
+      ("Accept", accept),
+    ])
+    // Merge custom headers
+    switch config.headers {
+    | Some(h) => Js.Dict.entries(h)->Array.forEach(((k, v)) => Js.Dict.set(headers, k, v))
+    | None => ()
+    }
+    headers
+  }
+
+  // Internal fetch helper, called by the innermost interceptor
+  let fetchUnary = async (
+    ~config: config,
+    ~deadline: Deadline.t,
+    request: Interceptor.request,
+  ): result<Js.Json.t, error> => {
+    let url = `${config.baseUrl}/${request.method}`
+    // grpc-timeout counts from this attempt, so retries send the time left
+    let headers = Js.Dict.fromArray(Js.Dict.entries(request.headers))
+    Deadline.remainingMs(deadline)->Option.forEach(ms => Js.Dict.set(headers, "grpc-timeout", Deadline.header(ms)))
+    try {
+      let response = await Fetch.fetch(
+        url,
+        {
+          method: #POST,
+          headers: Fetch.Headers.fromDict(headers),
+          body: Fetch.Body.string(Js.Json.stringify(request.body)),
+          signal: Deadline.signal(deadline),
+        },
+      )
+      let header = name => Fetch.Headers.get(Fetch.Headers.ofResponse(response), name)->Nullable.toOption
+      if Fetch.Response.ok(response) && header("grpc-status")->Option.getOr("0") == "0" {
+        let json = await Fetch.Response.json(response)
+        Ok(json)
+      } else {
+        let status = Fetch.Response.status(response)
+        let text = await Fetch.Response.text(response)
+        switch RpcStatus.fromResponse(~header, ~body=text) {
+        | Some(rpcStatus) => Error(StatusError(rpcStatus))
+        | None => Error(GrpcError(status, text))
+        }
+      }
+    } catch {
+    | _ if Deadline.timedOut(deadline) => Error(DeadlineExceeded)
+    | _ if Deadline.aborted(deadline) => Error(Cancelled)
+    | Exn.Error(exn) => Error(NetworkError(Exn.message(exn)->Option.getOr("Unknown error")))
+    | _ => Error(NetworkError("Unknown error"))
+    }
+  }
+
+  // Unary call through config.interceptors, aborted at the deadline or by `signal`
+  // and retried per config.retry
+  let call = async (
+    ~config: config,
+    ~method: string,
+    ~request: Js.Json.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+    ~idempotent: bool=false,
+  ): result<Js.Json.t, error> => {
+    let timeoutMs = timeoutMs->Option.orElse(config.timeoutMs)
+    let deadline = Deadline.make(~timeoutMs?, ~signal?, ())
+    let headers = requestHeaders(~config, ~accept="application/json")
+    timeoutMs->Option.forEach(ms => Js.Dict.set(headers, "grpc-timeout", Deadline.header(ms)))
+    let interceptors = config.interceptors->Option.getOr([])
+    let send = switch Retry.policyFor(config.retry, ~method, ~idempotent) {
+    | Some(policy) =>
+      request =>
+        Retry.run(
+          policy,
+          ~status=retryStatus,
+          ~signal=Deadline.signal(deadline),
+          ~aborted=() => Deadline.timedOut(deadline) ? DeadlineExceeded : Cancelled,
+          () => fetchUnary(~config, ~deadline, request),
+        )
+    | None => request => fetchUnary(~config, ~deadline, request)
+    }
+    let run = Interceptor.chainUnary(interceptors, send)
+    // Cleared on success, failure and exceptions from interceptors alike
+    await run({method, headers, body: request})->Promise.finally(() => Deadline.clear(deadline))
+  }
+
+  // Internal streaming helper, called by the innermost interceptor
+  let fetchStream = (
+    ~config: config,
+    request: Interceptor.request,
+    handler: streamHandler<Js.Json.t>,
+  ): streamCancel => {
+    let cancelled = ref(false)
+    let url = `${config.baseUrl}/${request.method}`
+
+    // Start the streaming request
+    let _ = Streaming.fetchNdjson(
+      ~url,
+      ~method=#POST,
+      ~headers=request.headers,
+      ~body=Js.Json.stringify(request.body),
+      ~onMessage=json => {
+        if !cancelled.contents {
+          handler.onMessage(json)
+        }
+      },
+      ~onError=msg => {
+        if !cancelled.contents {
+          handler.onError(NetworkError(msg))
+        }
+      },
+      ~onComplete=() => {
+        if !cancelled.contents {
+          handler.onComplete()
+        }
+      },
+    )
+
+    {cancel: () => cancelled := true}
+  }
+
+  // Server-streaming call through config.interceptors
+  let callStream = (
+    ~config: config,
+    ~method: string,
+    ~request: Js.Json.t,
+    ~handler: streamHandler<Js.Json.t>,
+  ): streamCancel => {
+    let interceptors = config.interceptors->Option.getOr([])
+    let run = Interceptor.chainStream(interceptors, (request, handler) =>
+      fetchStream(~config, request, handler)
+    )
+    let headers = requestHeaders(~config, ~accept="application/x-ndjson")
+    run({method, headers, body: request}, handler)
+  }
+
+  // GetUser RPC (unary)
+  /** Unary RPC - get single user */
+  let getUser = async (
+    ~config: config=defaultConfig,
+    ~request: GetStreamUserRequest.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+  ): result<StreamUser.t, error> => {
+    let requestJson = GetStreamUserRequest.toJson(request)
+    let response = await call(~config, ~method="UserStreamingService/GetUser", ~request=requestJson, ~timeoutMs?, ~signal?)
+    switch response {
+    | Ok(json) =>
+      switch StreamUser.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+  // ListUsersStream RPC (server streaming)
+  /** Server streaming - list users as stream */
+  let listUsersStream = (
+    ~config: config=defaultConfig,
+    ~request: ListUsersStreamRequest.t,
+    ~handler: streamHandler<StreamUser.t>,
+  ): streamCancel => {
+    let requestJson = ListUsersStreamRequest.toJson(request)
+    callStream(
+      ~config,
+      ~method="UserStreamingService/ListUsersStream",
+      ~request=requestJson,
+      ~handler={
+        onMessage: json => {
+          switch StreamUser.fromJson(json) {
+          | Some(msg) => handler.onMessage(msg)
+          | None => handler.onError(DecodeError("Failed to decode stream message"))
+          }
+        },
+        onError: handler.onError,
+        onComplete: handler.onComplete,
+      },
+    )
+  }
+
+  // UploadUsers RPC (client streaming - batch mode)
+  /** Client streaming - upload multiple users */
+  let uploadUsers = async (
+    ~config: config=defaultConfig,
+    ~requests: array<UploadUsersRequest.t>,
+  ): result<UploadUsersResponse.t, error> => {
+    let requestsJson = Js.Json.array(Array.map(requests, UploadUsersRequest.toJson))
+    let response = await call(~config, ~method="UserStreamingService/UploadUsers", ~request=requestsJson)
+    switch response {
+    | Ok(json) =>
+      switch UploadUsersResponse.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+  // Chat RPC (bidirectional streaming - batch/stream mode)
+  /** Bidirectional streaming - chat */
+  let chat = (
+    ~config: config=defaultConfig,
+    ~requests: array<ChatMessage.t>,
+    ~handler: streamHandler<ChatMessage.t>,
+  ): streamCancel => {
+    let requestsJson = Js.Json.array(Array.map(requests, ChatMessage.toJson))
+    callStream(
+      ~config,
+      ~method="UserStreamingService/Chat",
+      ~request=requestsJson,
+      ~handler={
+        onMessage: json => {
+          switch ChatMessage.fromJson(json) {
+          | Some(msg) => handler.onMessage(msg)
+          | None => handler.onError(DecodeError("Failed to decode stream message"))
+          }
+        },
+        onError: handler.onError,
+        onComplete: handler.onComplete,
+      },
+    )
+  }
+
+}
+
+/** User service with streaming RPCs */
+module UserStreamingServiceServer = {
+  // Server error types
+  type grpcStatus =
+    | Ok
+    | Cancelled
+    | Unknown
+    | InvalidArgument
+    | DeadlineExceeded
+    | NotFound
+    | AlreadyExists
+    | PermissionDenied
+    | ResourceExhausted
+    | FailedPrecondition
+    | Aborted
+    | OutOfRange
+    | Unimplemented
+    | Internal
+    | Unavailable
+    | DataLoss
+    | Unauthenticated
+
+  // Request context with metadata
+  type context = {
+    headers: Js.Dict.t<string>,
+    metadata: Js.Dict.t<string>,
+  }
+
+  type serverError = {
+    status: grpcStatus,
+    message: string,
+    // Typed google.rpc error details, e.g. BadRequest field violations
+    details?: array<RpcStatus.detail>,
+  }
+
+  // Stream writer for server-streaming responses
+  type streamWriter<'a> = {
+    send: 'a => promise<unit>,
+    complete: unit => unit,
+    error: serverError => unit,
+  }
+
+  // Trailers (grpc-status, ...) and JSON body of a handled request
+  type response = {
+    headers: array<(string, string)>,
+    body: Js.Json.t,
+  }
+
+  // Handler type definitions
+  type getUserHandler = (~request: GetStreamUserRequest.t, ~context: context) => promise<result<StreamUser.t, serverError>>
+  type listUsersStreamHandler = (~request: ListUsersStreamRequest.t, ~context: context, ~writer: streamWriter<StreamUser.t>) => unit
+  type uploadUsersHandler = (~requests: array<UploadUsersRequest.t>, ~context: context) => promise<result<UploadUsersResponse.t, serverError>>
+  type chatHandler = (~requests: array<ChatMessage.t>, ~context: context, ~writer: streamWriter<ChatMessage.t>) => unit
+
+  // Service implementation type
+  type service = {
+    getUser: getUserHandler,
+    listUsersStream: listUsersStreamHandler,
+    uploadUsers: uploadUsersHandler,
+    chat: chatHandler,
+  }
+
+  let statusToCode = (status: grpcStatus): int => {
+    switch status {
+    | Ok => 0
+    | Cancelled => 1
+    | Unknown => 2
+    | InvalidArgument => 3
+    | DeadlineExceeded => 4
+    | NotFound => 5
+    | AlreadyExists => 6
+    | PermissionDenied => 7
+    | ResourceExhausted => 8
+    | FailedPrecondition => 9
+    | Aborted => 10
+    | OutOfRange => 11
+    | Unimplemented => 12
+    | Internal => 13
+    | Unavailable => 14
+    | DataLoss => 15
+    | Unauthenticated => 16
+    }
+  }
+
+  let errorStatus = (error: serverError): RpcStatus.t => {
+    code: statusToCode(error.status),
+    message: error.message,
+    details: error.details->Option.getOr([]),
+  }
+
+  // google.rpc.Status JSON body for an error response
+  let errorToJson = (error: serverError): Js.Json.t => RpcStatus.toJson(errorStatus(error))
+
+  // grpc-status, grpc-message and grpc-status-details-bin for an error response
+  let errorHeaders = (error: serverError): array<(string, string)> =>
+    RpcStatus.toHeaders(errorStatus(error))
+
+  // Route method name to handler
+  let methodNames = [
+    "UserStreamingService/GetUser",
+    "UserStreamingService/ListUsersStream",
+    "UserStreamingService/UploadUsers",
+    "UserStreamingService/Chat",
+  ]
+
+  // Handle incoming JSON request
+  let handleRequest = async (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+  ): result<Js.Json.t, serverError> => {
+    switch method {
+    | "UserStreamingService/GetUser" =>
+      switch GetStreamUserRequest.fromJson(body) {
+      | Some(request) =>
+        let response = await service.getUser(~request, ~context)
+        switch response {
+        | Ok(msg) => Ok(StreamUser.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | "UserStreamingService/UploadUsers" =>
+      switch Js.Json.decodeArray(body) {
+      | Some(arr) =>
+        let requests = Array.filterMap(arr, UploadUsersRequest.fromJson)
+        let response = await service.uploadUsers(~requests, ~context)
+        switch response {
+        | Ok(msg) => Ok(UploadUsersResponse.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Expected array of requests"})
+      }
+    | _ => Error({status: Unimplemented, message: "Method not found"})
+    }
+  }
+
+  // handleRequest with the trailers and body to send back
+  let respond = async (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+  ): response => {
+    switch await handleRequest(~service, ~method, ~body, ~context) {
+    | Ok(body) => {headers: [("grpc-status", "0")], body}
+    | Error(error) => {headers: errorHeaders(error), body: errorToJson(error)}
+    }
+  }
+
+  // Handle streaming request
+  let handleStreamingRequest = (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+    ~writer: streamWriter<Js.Json.t>,
+  ): unit => {
+    switch method {
+    | "UserStreamingService/ListUsersStream" =>
+      let typedWriter: streamWriter<StreamUser.t> = {
+        send: async msg => await writer.send(StreamUser.toJson(msg)),
+        complete: writer.complete,
+        error: writer.error,
+      }
+      switch ListUsersStreamRequest.fromJson(body) {
+      | Some(request) =>
+        service.listUsersStream(~request, ~context, ~writer=typedWriter)
+      | None => writer.error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | "UserStreamingService/Chat" =>
+      let typedWriter: streamWriter<ChatMessage.t> = {
+        send: async msg => await writer.send(ChatMessage.toJson(msg)),
+        complete: writer.complete,
+        error: writer.error,
+      }
+      switch Js.Json.decodeArray(body) {
+      | Some(arr) =>
+        let requests = Array.filterMap(arr, ChatMessage.fromJson)
+        service.chat(~requests, ~context, ~writer=typedWriter)
+      | None => writer.error({status: InvalidArgument, message: "Expected array of requests"})
+      }
+    | _ => writer.error({status: Unimplemented, message: "Method not found"})
+    }
+  }
+
+  // Check if method requires streaming response
+  let isStreamingMethod = (method: string): bool => {
+    switch method {
+    | "UserStreamingService/ListUsersStream" => true
+    | "UserStreamingService/Chat" => true
+    | _ => false
+    }
+  }
+}
+
+
@@ -4,6 +4,7 @@
 
 // Package: example
 
+/** Status enum for user account */
 module Status = {
   type t = [
     | #StatusUnknown
@@ -33,57 +34,76 @@ module Status = {
 }
 
 
-module ListUsersRequest = {
-  type t = {
-    pageSize: int,
-    pageToken: string,
-  }
-
-  let make = (
-    ~pageSize,
-    ~pageToken
-  ): t => {
-    pageSize,
-    pageToken,
-  }
-}
-
-
-module GetUserRequest = {
-  type t = {
-    id: int,
-  }
-
-  let make = (
-    ~id
-  ): t => {
-    id,
-  }
-}
-
-
+/** Nested address message */
 module Address = {
   type t = {
     street: string,
     city: string,
     country: string,
     postalCode: option<string>,
+    unknownFields?: UnknownFields.t,
   }
 
   let make = (
     ~street,
     ~city,
     ~country,
-    ~postalCode=?
+    ~postalCode=?,
+    ~unknownFields=?
   ): t => {
     street,
     city,
     country,
     postalCode,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("street", msg.street, Json.Encode.string),
+        Json.Encode.required("city", msg.city, Json.Encode.string),
+        Json.Encode.required("country", msg.country, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("postal_code", msg.postalCode, Json.Encode.string),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let street = Json.Decode.required(obj, "street", Json.Decode.string)
+        let city = Json.Decode.required(obj, "city", Json.Decode.string)
+        let country = Json.Decode.required(obj, "country", Json.Decode.string)
+        let postalCode = Json.Decode.optional(obj, "postal_code", Json.Decode.string)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (street, city, country) {
+        | (Ok(street), Ok(city), Ok(country)) =>
+          Some({
+            street,
+            city,
+            country,
+            postalCode,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
   }
 }
 
 
+/** User message */
 module User = {
   type t = {
     name: string,
@@ -92,6 +112,7 @@ module User = {
     status: Status.t,
     tags: array<string>,
     address: option<Address.t>,
+    unknownFields?: UnknownFields.t,
   }
 
   let make = (
@@ -100,7 +121,8 @@ module User = {
     ~email=?,
     ~status,
     ~tags=[],
-    ~address=?
+    ~address=?,
+    ~unknownFields=?
   ): t => {
     name,
     id,
@@ -108,6 +130,162 @@ module User = {
     status,
     tags,
     address,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+        Json.Encode.required("status", msg.status, v => Json.Encode.int(Status.toInt(v))),
+      ],
+      [
+        Json.Encode.optional("email", msg.email, Json.Encode.string),
+        Json.Encode.repeated("tags", msg.tags, Json.Encode.string),
+        Json.Encode.optional("address", msg.address, v => Address.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let email = Json.Decode.optional(obj, "email", Json.Decode.string)->Result.getOr(None)
+        let status = Json.Decode.required(obj, "status", json => Json.Decode.int(json)->Option.flatMap(Status.fromInt))
+        let tags = Json.Decode.repeated(obj, "tags", Json.Decode.string)->Result.getOr([])
+        let address = Json.Decode.optional(obj, "address", Address.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (name, id, status) {
+        | (Ok(name), Ok(id), Ok(status)) =>
+          Some({
+            name,
+            id,
+            email,
+            status,
+            tags,
+            address,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+module GetUserRequest = {
+  type t = {
+    id: int,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~unknownFields=?
+  ): t => {
+    id,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id) {
+        | (Ok(id)) =>
+          Some({
+            id,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+module ListUsersRequest = {
+  type t = {
+    pageSize: int,
+    pageToken: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~pageSize,
+    ~pageToken,
+    ~unknownFields=?
+  ): t => {
+    pageSize,
+    pageToken,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("page_size", msg.pageSize, Json.Encode.int),
+        Json.Encode.required("page_token", msg.pageToken, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let pageSize = Json.Decode.required(obj, "page_size", Json.Decode.int)
+        let pageToken = Json.Decode.required(obj, "page_token", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (pageSize, pageToken) {
+        | (Ok(pageSize), Ok(pageToken)) =>
+          Some({
+            pageSize,
+            pageToken,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
   }
 }
 
@@ -116,14 +294,442 @@ module ListUsersResponse = {
   type t = {
     users: array<User.t>,
     nextPageToken: string,
+    unknownFields?: UnknownFields.t,
   }
 
   let make = (
     ~users=[],
-    ~nextPageToken
+    ~nextPageToken,
+    ~unknownFields=?
   ): t => {
     users,
     nextPageToken,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("next_page_token", msg.nextPageToken, Json.Encode.string),
+      ],
+      [
+        Json.Encode.repeated("users", msg.users, v => User.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let users = Json.Decode.repeated(obj, "users", User.fromJson)->Result.getOr([])
+        let nextPageToken = Json.Decode.required(obj, "next_page_token", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (nextPageToken) {
+        | (Ok(nextPageToken)) =>
+          Some({
+            users,
+            nextPageToken,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Example service (for future gRPC support) */
+module UserServiceClient = {
+  type error =
+    | NetworkError(string)
+    | GrpcError(int, string)
+    // google.rpc.Status from grpc-status-details-bin or an error JSON body
+    | StatusError(RpcStatus.t)
+    | DecodeError(string)
+    // The deadline passed before the response arrived
+    | DeadlineExceeded
+    // Aborted through the caller's signal
+    | Cancelled
+
+  // Stream handler for server-streaming RPCs
+  type streamHandler<'a> = {
+    onMessage: 'a => unit,
+    onError: error => unit,
+    onComplete: unit => unit,
+  }
+
+  // Cancellation handle for streaming RPCs
+  type streamCancel = {
+    cancel: unit => unit,
+  }
+
+  // Wraps calls to rewrite metadata, transform results or wrap stream handlers
+  type interceptor = Interceptor.t<error, streamHandler<Js.Json.t>, streamCancel>
+
+  // gRPC-web client configuration
+  type config = {
+    baseUrl: string,
+    headers: option<Js.Dict.t<string>>,
+    // Run around every call, the first one outermost
+    interceptors?: array<interceptor>,
+    // Default deadline of unary calls, sent as grpc-timeout
+    timeoutMs?: int,
+    // Retries of failed unary calls, see Retry.fromServiceConfig
+    retry?: Retry.config,
+  }
+
+  let defaultConfig = {
+    baseUrl: "http://localhost:8080",
+    headers: None,
+    retry: {Retry.policy: Retry.defaultPolicy},
+  }
+
+  // Service name in gRPC service configs
+  let serviceName = "example.UserService"
+
+  // gRPC status of a failed call, for retry policies
+  let retryStatus = (error: error): option<int> => {
+    switch error {
+    | NetworkError(_) => Some(14)
+    | GrpcError(status, _) => Some(Retry.codeOfHttpStatus(status))
+    | StatusError(status) => Some(status.code)
+    | DecodeError(_) | DeadlineExceeded | Cancelled => None
+    }
+  }
+
+  // Default headers merged with config.headers
+  let requestHeaders = (~config: config, ~accept: string): Js.Dict.t<string> => {
+    let headers = Js.Dict.fromArray([
+      ("Content-Type", "application/json"),
+      ("Accept", accept),
+    ])
+    // Merge custom headers
+    switch config.headers {
+    | Some(h) => Js.Dict.entries(h)->Array.forEach(((k, v)) => Js.Dict.set(headers, k, v))
+    | None => ()
+    }
+    headers
+  }
+
+  // Internal fetch helper, called by the innermost interceptor
+  let fetchUnary = async (
+    ~config: config,
+    ~deadline: Deadline.t,
+    request: Interceptor.request,
+  ): result<Js.Json.t, error> => {
+    let url = `${config.baseUrl}/${request.method}`
+    // grpc-timeout counts from this attempt, so retries send the time left
+    let headers = Js.Dict.fromArray(Js.Dict.entries(request.headers))
+    Deadline.remainingMs(deadline)->Option.forEach(ms => Js.Dict.set(headers, "grpc-timeout", Deadline.header(ms)))
+    try {
+      let response = await Fetch.fetch(
+        url,
+        {
+          method: #POST,
+          headers: Fetch.Headers.fromDict(headers),
+          body: Fetch.Body.string(Js.Json.stringify(request.body)),
+          signal: Deadline.signal(deadline),
+        },
+      )
+      let header = name => Fetch.Headers.get(Fetch.Headers.ofResponse(response), name)->Nullable.toOption
+      if Fetch.Response.ok(response) && header("grpc-status")->Option.getOr("0") == "0" {
+        let json = await Fetch.Response.json(response)
+        Ok(json)
+      } else {
+        let status = Fetch.Response.status(response)
+        let text = await Fetch.Response.text(response)
+        switch RpcStatus.fromResponse(~header, ~body=text) {
+        | Some(rpcStatus) => Error(StatusError(rpcStatus))
+        | None => Error(GrpcError(status, text))
+        }
+      }
+    } catch {
+    | _ if Deadline.timedOut(deadline) => Error(DeadlineExceeded)
+    | _ if Deadline.aborted(deadline) => Error(Cancelled)
+    | Exn.Error(exn) => Error(NetworkError(Exn.message(exn)->Option.getOr("Unknown error")))
+    | _ => Error(NetworkError("Unknown error"))
+    }
+  }
+
+  // Unary call through config.interceptors, aborted at the deadline or by `signal`
+  // and retried per config.retry
+  let call = async (
+    ~config: config,
+    ~method: string,
+    ~request: Js.Json.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+    ~idempotent: bool=false,
+  ): result<Js.Json.t, error> => {
+    let timeoutMs = timeoutMs->Option.orElse(config.timeoutMs)
+    let deadline = Deadline.make(~timeoutMs?, ~signal?, ())
+    let headers = requestHeaders(~config, ~accept="application/json")
+    timeoutMs->Option.forEach(ms => Js.Dict.set(headers, "grpc-timeout", Deadline.header(ms)))
+    let interceptors = config.interceptors->Option.getOr([])
+    let send = switch Retry.policyFor(config.retry, ~method, ~idempotent) {
+    | Some(policy) =>
+      request =>
+        Retry.run(
+          policy,
+          ~status=retryStatus,
+          ~signal=Deadline.signal(deadline),
+          ~aborted=() => Deadline.timedOut(deadline) ? DeadlineExceeded : Cancelled,
+          () => fetchUnary(~config, ~deadline, request),
+        )
+    | None => request => fetchUnary(~config, ~deadline, request)
+    }
+    let run = Interceptor.chainUnary(interceptors, send)
+    // Cleared on success, failure and exceptions from interceptors alike
+    await run({method, headers, body: request})->Promise.finally(() => Deadline.clear(deadline))
+  }
+
+  // Internal streaming helper, called by the innermost interceptor
+  let fetchStream = (
+    ~config: config,
+    request: Interceptor.request,
+    handler: streamHandler<Js.Json.t>,
+  ): streamCancel => {
+    let cancelled = ref(false)
+    let url = `${config.baseUrl}/${request.method}`
+
+    // Start the streaming request
+    let _ = Streaming.fetchNdjson(
+      ~url,
+      ~method=#POST,
+      ~headers=request.headers,
+      ~body=Js.Json.stringify(request.body),
+      ~onMessage=json => {
+        if !cancelled.contents {
+          handler.onMessage(json)
+        }
+      },
+      ~onError=msg => {
+        if !cancelled.contents {
+          handler.onError(NetworkError(msg))
+        }
+      },
+      ~onComplete=() => {
+        if !cancelled.contents {
+          handler.onComplete()
+        }
+      },
+    )
+
+    {cancel: () => cancelled := true}
+  }
+
+  // Server-streaming call through config.interceptors
+  let callStream = (
+    ~config: config,
+    ~method: string,
+    ~request: Js.Json.t,
+    ~handler: streamHandler<Js.Json.t>,
+  ): streamCancel => {
+    let interceptors = config.interceptors->Option.getOr([])
+    let run = Interceptor.chainStream(interceptors, (request, handler) =>
+      fetchStream(~config, request, handler)
+    )
+    let headers = requestHeaders(~config, ~accept="application/x-ndjson")
+    run({method, headers, body: request}, handler)
+  }
+
+  // GetUser RPC (unary)
+  let getUser = async (
+    ~config: config=defaultConfig,
+    ~request: GetUserRequest.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+  ): result<User.t, error> => {
+    let requestJson = GetUserRequest.toJson(request)
+    let response = await call(~config, ~method="UserService/GetUser", ~request=requestJson, ~timeoutMs?, ~signal?)
+    switch response {
+    | Ok(json) =>
+      switch User.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+  // ListUsers RPC (unary)
+  let listUsers = async (
+    ~config: config=defaultConfig,
+    ~request: ListUsersRequest.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+  ): result<ListUsersResponse.t, error> => {
+    let requestJson = ListUsersRequest.toJson(request)
+    let response = await call(~config, ~method="UserService/ListUsers", ~request=requestJson, ~timeoutMs?, ~signal?)
+    switch response {
+    | Ok(json) =>
+      switch ListUsersResponse.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+}
+
+/** Example service (for future gRPC support) */
+module UserServiceServer = {
+  // Server error types
+  type grpcStatus =
+    | Ok
+    | Cancelled
+    | Unknown
+    | InvalidArgument
+    | DeadlineExceeded
+    | NotFound
+    | AlreadyExists
+    | PermissionDenied
+    | ResourceExhausted
+    | FailedPrecondition
+    | Aborted
+    | OutOfRange
+    | Unimplemented
+    | Internal
+    | Unavailable
+    | DataLoss
+    | Unauthenticated
+
+  // Request context with metadata
+  type context = {
+    headers: Js.Dict.t<string>,
+    metadata: Js.Dict.t<string>,
+  }
+
+  type serverError = {
+    status: grpcStatus,
+    message: string,
+    // Typed google.rpc error details, e.g. BadRequest field violations
+    details?: array<RpcStatus.detail>,
+  }
+
+  // Stream writer for server-streaming responses
+  type streamWriter<'a> = {
+    send: 'a => promise<unit>,
+    complete: unit => unit,
+    error: serverError => unit,
+  }
+
+  // Trailers (grpc-status, ...) and JSON body of a handled request
+  type response = {
+    headers: array<(string, string)>,
+    body: Js.Json.t,
+  }
+
+  // Handler type definitions
+  type getUserHandler = (~request: GetUserRequest.t, ~context: context) => promise<result<User.t, serverError>>
+  type listUsersHandler = (~request: ListUsersRequest.t, ~context: context) => promise<result<ListUsersResponse.t, serverError>>
+
+  // Service implementation type
+  type service = {
+    getUser: getUserHandler,
+    listUsers: listUsersHandler,
+  }
+
+  let statusToCode = (status: grpcStatus): int => {
+    switch status {
+    | Ok => 0
+    | Cancelled => 1
+    | Unknown => 2
+    | InvalidArgument => 3
+    | DeadlineExceeded => 4
+    | NotFound => 5
+    | AlreadyExists => 6
+    | PermissionDenied => 7
+    | ResourceExhausted => 8
+    | FailedPrecondition => 9
+    | Aborted => 10
+    | OutOfRange => 11
+    | Unimplemented => 12
+    | Internal => 13
+    | Unavailable => 14
+    | DataLoss => 15
+    | Unauthenticated => 16
+    }
+  }
+
+  let errorStatus = (error: serverError): RpcStatus.t => {
+    code: statusToCode(error.status),
+    message: error.message,
+    details: error.details->Option.getOr([]),
+  }
+
+  // google.rpc.Status JSON body for an error response
+  let errorToJson = (error: serverError): Js.Json.t => RpcStatus.toJson(errorStatus(error))
+
+  // grpc-status, grpc-message and grpc-status-details-bin for an error response
+  let errorHeaders = (error: serverError): array<(string, string)> =>
+    RpcStatus.toHeaders(errorStatus(error))
+
+  // Route method name to handler
+  let methodNames = [
+    "UserService/GetUser",
+    "UserService/ListUsers",
+  ]
+
+  // Handle incoming JSON request
+  let handleRequest = async (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+  ): result<Js.Json.t, serverError> => {
+    switch method {
+    | "UserService/GetUser" =>
+      switch GetUserRequest.fromJson(body) {
+      | Some(request) =>
+        let response = await service.getUser(~request, ~context)
+        switch response {
+        | Ok(msg) => Ok(User.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | "UserService/ListUsers" =>
+      switch ListUsersRequest.fromJson(body) {
+      | Some(request) =>
+        let response = await service.listUsers(~request, ~context)
+        switch response {
+        | Ok(msg) => Ok(ListUsersResponse.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | _ => Error({status: Unimplemented, message: "Method not found"})
+    }
+  }
+
+  // handleRequest with the trailers and body to send back
+  let respond = async (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+  ): response => {
+    switch await handleRequest(~service, ~method, ~body, ~context) {
+    | Ok(body) => {headers: [("grpc-status", "0")], body}
+    | Error(error) => {headers: errorHeaders(error), body: errorToJson(error)}
+    }
+  }
+
+  // Check if method requires streaming response
+  let isStreamingMethod = (method: string): bool => {
+    switch method {
+    | _ => false
+    }
   }
 }
 
@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: MPL-2.0
+//! `(rescript.*)` custom options declared in `proto/rescript.proto`
+//!
+//! prost-types drops extension fields when decoding descriptors, so the
+//! request is decoded a second time into the descriptor subsets below, which
+//! only keep names, nesting and the rescript extensions.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use prost::Message;
+
+/// `CodeGeneratorRequest` with only the proto files
+#[derive(Clone, PartialEq, Message)]
+pub struct RequestOptions {
+    #[prost(message, repeated, tag = "15")]
+    pub proto_file: Vec<FileOptions>,
+}
+
+/// `FileDescriptorProto` with only names and options
+#[derive(Clone, PartialEq, Message)]
+pub struct FileOptions {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub package: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub message_type: Vec<MessageOptions>,
+    #[prost(message, optional, tag = "8")]
+    pub options: Option<FileExtensions>,
+}
+
+/// `DescriptorProto` with only names and options
+#[derive(Clone, PartialEq, Message)]
+pub struct MessageOptions {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    pub field: Vec<FieldOptions>,
+    #[prost(message, repeated, tag = "3")]
+    pub nested_type: Vec<MessageOptions>,
+    #[prost(message, optional, tag = "7")]
+    pub options: Option<MessageExtensions>,
+}
+
+/// `FieldDescriptorProto` with only the name and options
+#[derive(Clone, PartialEq, Message)]
+pub struct FieldOptions {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(message, optional, tag = "8")]
+    pub options: Option<FieldExtensions>,
+}
+
+/// `google.protobuf.FileOptions` extensions
+#[derive(Clone, PartialEq, Message)]
+pub struct FileExtensions {
+    #[prost(string, optional, tag = "51201")]
+    pub module_name: Option<String>,
+}
+
+/// `google.protobuf.MessageOptions` extensions
+#[derive(Clone, PartialEq, Message)]
+pub struct MessageExtensions {
+    #[prost(string, optional, tag = "51201")]
+    pub type_name: Option<String>,
+    #[prost(bool, optional, tag = "51202")]
+    pub skip_message: Option<bool>,
+}
+
+/// `google.protobuf.FieldOptions` extensions
+#[derive(Clone, PartialEq, Message)]
+pub struct FieldExtensions {
+    #[prost(string, optional, tag = "51201")]
+    pub field_name: Option<String>,
+    #[prost(message, optional, tag = "51202")]
+    pub r#type: Option<TypeMapping>,
+    #[prost(bool, optional, tag = "51203")]
+    pub skip: Option<bool>,
+}
+
+/// `rescript.TypeMapping`: a custom ReScript type with its JSON codec
+#[derive(Clone, PartialEq, Message)]
+pub struct TypeMapping {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub encode: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub decode: Option<String>,
+}
+
+/// Options of one message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageOverrides {
+    pub type_name: Option<String>,
+    pub skip: bool,
+}
+
+/// Options of one field
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldOverrides {
+    pub field_name: Option<String>,
+    pub custom_type: Option<TypeMapping>,
+    pub skip: bool,
+}
+
+/// All `(rescript.*)` options of a request, keyed by file name or
+/// fully-qualified element name (without the leading `.`)
+#[derive(Debug, Clone, Default)]
+pub struct CustomOptions {
+    modules: HashMap<String, String>,
+    messages: HashMap<String, MessageOverrides>,
+    fields: HashMap<String, FieldOverrides>,
+}
+
+impl CustomOptions {
+    /// Read the options from an encoded `CodeGeneratorRequest`
+    pub fn decode(request: &[u8]) -> Result<Self> {
+        let request =
+            RequestOptions::decode(request).context("Failed to parse (rescript.*) options")?;
+        Ok(Self::from_request(&request))
+    }
+
+    pub fn from_request(request: &RequestOptions) -> Self {
+        let mut custom = Self::default();
+        for file in &request.proto_file {
+            let file_name = file.name.clone().unwrap_or_default();
+            if let Some(module_name) = file.options.as_ref().and_then(|o| o.module_name.clone()) {
+                custom.modules.insert(file_name, module_name);
+            }
+            for message in &file.message_type {
+                custom.add_message(file.package.as_deref().unwrap_or(""), message);
+            }
+        }
+        custom
+    }
+
+    fn add_message(&mut self, scope: &str, message: &MessageOptions) {
+        let name = message.name.as_deref().unwrap_or("");
+        let full_name = if scope.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", scope, name)
+        };
+
+        if let Some(options) = &message.options {
+            let overrides = MessageOverrides {
+                type_name: options.type_name.clone(),
+                skip: options.skip_message.unwrap_or(false),
+            };
+            if overrides != MessageOverrides::default() {
+                self.messages.insert(full_name.clone(), overrides);
+            }
+        }
+
+        for field in &message.field {
+            if let Some(options) = &field.options {
+                let overrides = FieldOverrides {
+                    field_name: options.field_name.clone(),
+                    custom_type: options.r#type.clone(),
+                    skip: options.skip.unwrap_or(false),
+                };
+                if overrides != FieldOverrides::default() {
+                    let field_name = field.name.as_deref().unwrap_or("");
+                    self.fields
+                        .insert(format!("{}.{}", full_name, field_name), overrides);
+                }
+            }
+        }
+
+        for nested in &message.nested_type {
+            self.add_message(&full_name, nested);
+        }
+    }
+
+    /// `(rescript.module_name)` of a proto file
+    pub fn module_name(&self, file_name: &str) -> Option<&str> {
+        self.modules.get(file_name).map(String::as_str)
+    }
+
+    /// Options of a message, by fully-qualified name
+    pub fn message(&self, full_name: &str) -> Option<&MessageOverrides> {
+        self.messages.get(full_name.trim_start_matches('.'))
+    }
+
+    /// Options of a field, by fully-qualified name
+    pub fn field(&self, full_name: &str) -> Option<&FieldOverrides> {
+        self.fields.get(full_name.trim_start_matches('.'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_from_request_bytes() {
+        // Extensions arrive as plain fields of the options messages
+        let request = RequestOptions {
+            proto_file: vec![FileOptions {
+                name: Some("user.proto".to_string()),
+                package: Some("example".to_string()),
+                message_type: vec![MessageOptions {
+                    name: Some("User".to_string()),
+                    field: vec![
+                        FieldOptions {
+                            name: Some("user_id".to_string()),
+                            options: Some(FieldExtensions {
+                                field_name: Some("uid".to_string()),
+                                r#type: None,
+                                skip: None,
+                            }),
+                        },
+                        FieldOptions {
+                            name: Some("plain".to_string()),
+                            options: None,
+                        },
+                    ],
+                    nested_type: vec![MessageOptions {
+                        name: Some("Internal".to_string()),
+                        field: vec![],
+                        nested_type: vec![],
+                        options: Some(MessageExtensions {
+                            type_name: None,
+                            skip_message: Some(true),
+                        }),
+                    }],
+                    options: None,
+                }],
+                options: Some(FileExtensions {
+                    module_name: Some("Users".to_string()),
+                }),
+            }],
+        };
+
+        let custom = CustomOptions::decode(&request.encode_to_vec()).unwrap();
+        assert_eq!(custom.module_name("user.proto"), Some("Users"));
+        assert_eq!(
+            custom
+                .field(".example.User.user_id")
+                .unwrap()
+                .field_name
+                .as_deref(),
+            Some("uid")
+        );
+        assert!(custom.field("example.User.plain").is_none());
+        assert!(custom.message(".example.User.Internal").unwrap().skip);
+        assert!(custom.message("example.User").is_none());
+    }
+
+    #[test]
+    fn test_decode_ignores_other_request_fields() {
+        let request = prost_types::compiler::CodeGeneratorRequest {
+            file_to_generate: vec!["user.proto".to_string()],
+            parameter: Some("grpc".to_string()),
+            proto_file: vec![prost_types::FileDescriptorProto {
+                name: Some("user.proto".to_string()),
+                message_type: vec![prost_types::DescriptorProto {
+                    name: Some("User".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let custom = CustomOptions::decode(&request.encode_to_vec()).unwrap();
+        assert!(custom.module_name("user.proto").is_none());
+        assert!(custom.message("User").is_none());
+    }
+}
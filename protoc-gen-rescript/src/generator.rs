@@ -3,191 +3,524 @@
 
 use std::collections::{HashMap, HashSet};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use prost_types::compiler::{code_generator_response, CodeGeneratorRequest, CodeGeneratorResponse};
-use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+use prost_types::method_options::IdempotencyLevel;
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto, ServiceDescriptorProto,
+};
+
+use crate::custom_options::CustomOptions;
+use crate::naming;
+use crate::templates::{
+    AnyTypesTemplate, EnumTemplate, EnumVariant, ExtensionInfo, ExtensionsTemplate, FieldInfo,
+    MessageTemplate, MethodInfo, ModuleCode, ModuleTemplate, OneOfInfo, ServiceTemplate, nest_modules,
+};
+use crate::{FieldStyle, Layout, Options};
+
+// Descriptor field numbers used in SourceCodeInfo location paths
+const FILE_MESSAGE_TYPE: i32 = 4;
+const FILE_ENUM_TYPE: i32 = 5;
+const FILE_SERVICE: i32 = 6;
+const FILE_EXTENSION: i32 = 7;
+const MESSAGE_FIELD: i32 = 2;
+const MESSAGE_NESTED_TYPE: i32 = 3;
+const MESSAGE_ENUM_TYPE: i32 = 4;
+const MESSAGE_EXTENSION: i32 = 6;
+const MESSAGE_ONEOF_DECL: i32 = 8;
+const ENUM_VALUE: i32 = 2;
+const SERVICE_METHOD: i32 = 2;
+
+/// Leading and trailing comments from `source_code_info`, keyed by element path
+#[derive(Default)]
+pub struct SourceComments {
+    by_path: HashMap<Vec<i32>, String>,
+}
+
+impl SourceComments {
+    pub fn from_file(file: &FileDescriptorProto) -> Self {
+        let mut by_path = HashMap::new();
+
+        for location in file.source_code_info.iter().flat_map(|info| &info.location) {
+            let text: Vec<&str> = [&location.leading_comments, &location.trailing_comments]
+                .into_iter()
+                .flatten()
+                .map(|c| c.trim_end())
+                .filter(|c| !c.trim().is_empty())
+                .collect();
+
+            if !text.is_empty() {
+                by_path.insert(location.path.clone(), text.join("\n\n"));
+            }
+        }
+
+        Self { by_path }
+    }
+
+    pub fn get(&self, path: &[i32]) -> Option<String> {
+        self.by_path.get(path).cloned()
+    }
+}
 
-use crate::templates::{EnumTemplate, FieldInfo, MessageTemplate, MethodInfo, ModuleTemplate, OneOfInfo, ServiceTemplate};
-use crate::Options;
-use prost_types::ServiceDescriptorProto;
+/// Path of the `index`-th element of a repeated descriptor field
+fn child_path(path: &[i32], field: i32, index: usize) -> Vec<i32> {
+    let mut child = path.to_vec();
+    child.push(field);
+    child.push(index as i32);
+    child
+}
 
 pub struct Generator {
     options: Options,
+    custom: CustomOptions,
 }
 
 impl Generator {
     pub fn new(options: Options) -> Self {
-        Self { options }
+        Self { options, custom: CustomOptions::default() }
+    }
+
+    /// Apply `(rescript.*)` options read from the request
+    pub fn with_custom_options(mut self, custom: CustomOptions) -> Self {
+        self.custom = custom;
+        self
     }
 
     pub fn generate(&self, request: &CodeGeneratorRequest) -> Result<CodeGeneratorResponse> {
-        let mut response = CodeGeneratorResponse::default();
+        let mut response = CodeGeneratorResponse {
+            // Set supported features
+            supported_features: Some(code_generator_response::Feature::Proto3Optional as u64),
+            ..Default::default()
+        };
 
-        // Set supported features
-        response.supported_features = Some(
-            code_generator_response::Feature::Proto3Optional as u64,
-        );
+        // References may point into any file of the request, not only the generated ones
+        let types = self.type_index(&request.proto_file);
 
-        // Process each file to generate
+        // Find the descriptor of each file to generate
+        let mut files = Vec::new();
         for file_name in &request.file_to_generate {
-            // Find the file descriptor
-            let file_desc = request
+            let desc = request
                 .proto_file
                 .iter()
-                .find(|f| f.name.as_deref() == Some(file_name.as_str()));
+                .find(|f| f.name.as_deref() == Some(file_name.as_str()))
+                .ok_or_else(|| anyhow!("{}: file is not among the request's proto files", file_name))?;
+            files.push(desc);
+        }
+
+        // ReScript module names are global, so two outputs may never share one
+        let mut owners: HashMap<String, String> = HashMap::new();
+        let mut claim = |module_name: &str, owner: String| -> Result<()> {
+            match owners.get(module_name) {
+                Some(existing) if *existing != owner => bail!(
+                    "{} and {} both generate module `{}`; use layout=mirror or layout=package to keep them apart",
+                    existing,
+                    owner,
+                    module_name
+                ),
+                _ => {
+                    owners.insert(module_name.to_string(), owner);
+                    Ok(())
+                }
+            }
+        };
+
+        if self.options.layout == Layout::Package {
+            // protoc lists proto_file in dependency order, so keep that order when merging
+            files.sort_by_key(|f| request.proto_file.iter().position(|p| std::ptr::eq(p, *f)));
+
+            let mut roots: Vec<(String, PackageTree)> = Vec::new();
+            for file in files {
+                let file_name = file.name.as_deref().unwrap_or("unknown");
+                let package = file.package.as_deref().unwrap_or("");
+                if package.is_empty() {
+                    // Files without a package fall back to the flat layout
+                    claim(&self.proto_to_module_name(file_name), file_name.to_string())?;
+                    response.file.extend(self.generate_file(file, &types).with_context(|| file_name.to_string())?);
+                    continue;
+                }
+
+                let segments = package
+                    .split('.')
+                    .map(|segment| {
+                        let module_name = self.to_rescript_type_name(segment);
+                        check_identifier("package", package, &module_name, Case::Upper)?;
+                        Ok(module_name)
+                    })
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| file_name.to_string())?;
+                claim(&segments[0], format!("package {}", package.split('.').next().unwrap_or(package)))?;
+
+                let modules = self.file_modules(file, &types).with_context(|| file_name.to_string())?;
+                let index = match roots.iter().position(|(name, _)| *name == segments[0]) {
+                    Some(index) => index,
+                    None => {
+                        roots.push((segments[0].clone(), PackageTree::default()));
+                        roots.len() - 1
+                    }
+                };
+                roots[index].1.insert(&segments[1..], package, file_name, modules)?;
+            }
 
-            if let Some(desc) = file_desc {
-                let generated = self.generate_file(desc)?;
-                response.file.push(generated);
+            for (name, tree) in roots {
+                let template = ModuleTemplate {
+                    package: tree.packages().join(", "),
+                    source_file: tree.source_files().join(", "),
+                    modules: tree.into_modules(),
+                    use_core: self.options.use_core,
+                };
+                response.file.extend(self.output_files(&name, &template));
+            }
+        } else {
+            for file in files {
+                let file_name = file.name.as_deref().unwrap_or("unknown");
+                let stem = self.output_stem(file_name);
+                claim(stem.rsplit('/').next().unwrap_or(&stem), file_name.to_string())?;
+                response.file.extend(self.generate_file(file, &types).with_context(|| file_name.to_string())?);
             }
         }
 
         Ok(response)
     }
 
-    fn generate_file(&self, file: &FileDescriptorProto) -> Result<code_generator_response::File> {
+    /// Generate the output files for one proto file (flat and mirror layouts)
+    fn generate_file(&self, file: &FileDescriptorProto, types: &TypeIndex) -> Result<Vec<code_generator_response::File>> {
         let file_name = file.name.as_deref().unwrap_or("unknown");
         let package = file.package.as_deref().unwrap_or("");
+        let stem = self.output_stem(file_name);
+        let module_name = stem.rsplit('/').next().unwrap_or(&stem);
+        check_identifier("file", file_name, module_name, Case::Upper)?;
+        if naming::RESERVED_MODULES.contains(&module_name) {
+            bail!("file `{}`: module `{}` would shadow the module of the same name used by generated code", file_name, module_name);
+        }
+
+        let template = ModuleTemplate {
+            package: package.to_string(),
+            source_file: file_name.to_string(),
+            modules: self.file_modules(file, types)?,
+            use_core: self.options.use_core,
+        };
 
-        // Convert file name to ReScript module name
-        // e.g., "user.proto" -> "UserProto.res"
+        Ok(self.output_files(&stem, &template))
+    }
+
+    /// Render a module as a `.res` file and, if enabled, its `.resi` interface
+    fn output_files(&self, stem: &str, template: &ModuleTemplate) -> Vec<code_generator_response::File> {
+        let mut files = vec![code_generator_response::File {
+            name: Some(self.output_path(&format!("{}.res", stem))),
+            content: Some(template.render()),
+            ..Default::default()
+        }];
+
+        // Optional interface exposing only types, make, codecs and RPC functions
+        if self.options.interface {
+            files.push(code_generator_response::File {
+                name: Some(self.output_path(&format!("{}.resi", stem))),
+                content: Some(template.render_interface()),
+                ..Default::default()
+            });
+        }
+
+        files
+    }
+
+    /// Output path (without extension) of a proto file in the flat or mirror layout
+    ///
+    /// "path/to/user.proto" -> "UserProto" (flat) or "path/to/PathToUserProto" (mirror).
+    /// The mirror layout also folds the directories into the module name because
+    /// ReScript module names must be unique across a project.
+    fn output_stem(&self, file_name: &str) -> String {
         let module_name = self.proto_to_module_name(file_name);
+        match file_name.rsplit_once('/') {
+            Some((dir, _)) if self.options.layout == Layout::Mirror => {
+                let prefix: String = dir.split('/').map(pascal_case).collect();
+                format!("{}/{}{}", dir, prefix, module_name)
+            }
+            _ => module_name,
+        }
+    }
+
+    /// Path of the top-level module a proto file's definitions are generated into
+    ///
+    /// The file module (`UserProto`, `PathToUserProto`) or, in the package
+    /// layout, the package modules (`Acme.Users`).
+    fn file_root(&self, file: &FileDescriptorProto) -> Vec<String> {
+        let file_name = file.name.as_deref().unwrap_or("unknown");
+        let package = file.package.as_deref().unwrap_or("");
+        if self.options.layout == Layout::Package && !package.is_empty() {
+            return package.split('.').map(|segment| self.to_rescript_type_name(segment)).collect();
+        }
+        let stem = self.output_stem(file_name);
+        vec![stem.rsplit('/').next().unwrap_or(&stem).to_string()]
+    }
+
+    /// Module paths of all messages and enums declared in `files`
+    fn type_index<'a>(&self, files: &'a [FileDescriptorProto]) -> TypeIndex<'a> {
+        fn add_enums(types: &mut TypeIndex, generator: &Generator, enums: &[EnumDescriptorProto], scope: &str, path: &[String]) {
+            for enum_desc in enums {
+                let name = enum_desc.name.as_deref().unwrap_or("");
+                if enum_desc.options.as_ref().is_some_and(|o| o.deprecated()) {
+                    types.deprecated.insert(qualify(scope, name));
+                }
+                types.paths.insert(qualify(scope, name), [path, &[generator.to_rescript_type_name(name)]].concat());
+            }
+        }
+
+        fn add_messages<'a>(
+            types: &mut TypeIndex<'a>,
+            generator: &Generator,
+            messages: &'a [DescriptorProto],
+            proto3: bool,
+            scope: &str,
+            path: &[String],
+        ) {
+            for msg in messages {
+                let full_name = qualify(scope, msg.name.as_deref().unwrap_or(""));
+                let msg_path = [path, &[generator.referenced_type_name(&full_name)]].concat();
+                add_enums(types, generator, &msg.enum_type, &full_name, &msg_path);
+                add_messages(types, generator, &msg.nested_type, proto3, &full_name, &msg_path);
+                types.messages.insert(full_name.clone(), (msg, proto3));
+                if msg.options.as_ref().is_some_and(|o| o.deprecated()) {
+                    types.deprecated.insert(full_name.clone());
+                }
+                types.paths.insert(full_name, msg_path);
+            }
+        }
+
+        let mut types = TypeIndex::default();
+        for file in files {
+            let package = file.package.as_deref().unwrap_or("");
+            let root = self.file_root(file);
+            add_enums(&mut types, self, &file.enum_type, package, &root);
+            add_messages(&mut types, self, &file.message_type, file.syntax() == "proto3", package, &root);
+        }
+        types
+    }
+
+    /// Modules generated for the top-level definitions of a proto file
+    fn file_modules(&self, file: &FileDescriptorProto, types: &TypeIndex) -> Result<Vec<ModuleCode>> {
+        let package = file.package.as_deref().unwrap_or("");
+        let comments = SourceComments::from_file(file);
+        let root = ModuleScope { types, path: self.file_root(file) };
 
         let mut modules = Vec::new();
 
         // Generate enums first (they have no dependencies)
-        for enum_desc in &file.enum_type {
-            modules.push(self.generate_enum(enum_desc)?);
+        for (i, enum_desc) in file.enum_type.iter().enumerate() {
+            let path = child_path(&[], FILE_ENUM_TYPE, i);
+            modules.push(self.generate_enum(enum_desc, package, &comments, &path)?);
         }
 
         // Topologically sort messages by dependencies
-        let sorted_messages = self.topological_sort_messages(&file.message_type);
+        let sorted_messages = self.topological_sort_messages(&file.message_type, package);
 
         // Generate messages in dependency order
         for msg_desc in sorted_messages {
-            modules.push(self.generate_message(msg_desc, &self.options)?);
+            if self.is_skipped(&qualify(package, msg_desc.name.as_deref().unwrap_or(""))) {
+                continue;
+            }
+            let index = file.message_type.iter().position(|m| std::ptr::eq(m, msg_desc)).unwrap_or(0);
+            let path = child_path(&[], FILE_MESSAGE_TYPE, index);
+            modules.push(self.generate_message(msg_desc, &self.options, package, &root, &comments, &path)?);
+        }
+
+        // Extensions come after messages since their values may be message types
+        let extensions = self.collect_extensions(
+            &root.child("Extensions"),
+            &file.extension,
+            &file.message_type,
+            package,
+            &comments,
+            (&[], FILE_EXTENSION, FILE_MESSAGE_TYPE),
+        )?;
+        if !extensions.is_empty() {
+            modules.push(extensions.render_module());
+        }
+
+        if self.options.any_types {
+            let mut messages = Vec::new();
+            self.collect_any_types(&file.message_type, package, "", &mut messages);
+            if !messages.is_empty() {
+                modules.push(AnyTypesTemplate { messages }.render_module());
+            }
         }
 
         // Generate services if grpc option is enabled
         if self.options.grpc {
-            for service_desc in &file.service {
-                modules.push(self.generate_service(service_desc)?);
+            for (i, service_desc) in file.service.iter().enumerate() {
+                let path = child_path(&[], FILE_SERVICE, i);
+                modules.push(self.generate_service(service_desc, package, &root, &comments, &path)?);
             }
         }
 
-        let template = ModuleTemplate {
-            package: package.to_string(),
-            source_file: file_name.to_string(),
-            modules,
-            use_wasm: self.options.wasm,
-        };
-
-        let content = template.render();
+        Ok(modules)
+    }
 
-        Ok(code_generator_response::File {
-            name: Some(format!("{}.res", module_name)),
-            content: Some(content),
-            ..Default::default()
-        })
+    /// Prefix a generated file name with the `out=` directory
+    fn output_path(&self, file_name: &str) -> String {
+        let dir = self
+            .options
+            .out_dir
+            .as_deref()
+            .map(|d| d.trim_start_matches("./").trim_end_matches('/'))
+            .unwrap_or("");
+
+        if dir.is_empty() || dir == "." {
+            file_name.to_string()
+        } else {
+            format!("{}/{}", dir, file_name)
+        }
     }
 
     /// Topologically sort messages so dependencies come before dependents
+    ///
+    /// A message depends on a sibling when it or one of its nested messages
+    /// references the sibling or a type nested in it. Messages without a
+    /// dependency between them keep their declaration order.
     fn topological_sort_messages<'a>(
         &self,
         messages: &'a [DescriptorProto],
+        scope: &str,
     ) -> Vec<&'a DescriptorProto> {
-        // Build a map of message name -> message
-        let msg_map: HashMap<&str, &DescriptorProto> = messages
-            .iter()
-            .filter_map(|m| m.name.as_deref().map(|n| (n, m)))
-            .collect();
-
-        // Build dependency graph: message name -> set of message names it depends on
-        let mut deps: HashMap<&str, HashSet<&str>> = HashMap::new();
-        for msg in messages {
-            let name = msg.name.as_deref().unwrap_or("");
-            let mut msg_deps = HashSet::new();
-
-            for field in &msg.field {
-                if let Some(type_name) = &field.type_name {
-                    // Extract simple name from fully qualified name
-                    let simple_name = type_name.rsplit('.').next().unwrap_or(type_name);
-                    // Only add as dependency if it's a message in this file
-                    if msg_map.contains_key(simple_name) && simple_name != name {
-                        msg_deps.insert(simple_name);
-                    }
-                }
+        // Every type name referenced by a message's fields, nested messages included
+        fn referenced<'m>(msg: &'m DescriptorProto, out: &mut Vec<&'m str>) {
+            out.extend(msg.field.iter().filter_map(|f| f.type_name.as_deref()));
+            for nested in &msg.nested_type {
+                referenced(nested, out);
             }
-
-            deps.insert(name, msg_deps);
         }
 
-        // Kahn's algorithm for topological sort
-        let mut result = Vec::new();
-        let mut no_deps: Vec<&str> = deps
+        let full_names: Vec<String> = messages
             .iter()
-            .filter(|(_, d)| d.is_empty())
-            .map(|(n, _)| *n)
+            .map(|m| format!(".{}", qualify(scope, m.name.as_deref().unwrap_or(""))))
             .collect();
 
-        while let Some(name) = no_deps.pop() {
-            if let Some(msg) = msg_map.get(name) {
-                result.push(*msg);
-            }
+        // Build dependency graph: message index -> indices of the siblings it depends on
+        let deps: Vec<HashSet<usize>> = messages
+            .iter()
+            .enumerate()
+            .map(|(index, msg)| {
+                let mut type_names = Vec::new();
+                referenced(msg, &mut type_names);
+                type_names
+                    .into_iter()
+                    .filter_map(|type_name| {
+                        full_names.iter().position(|full_name| {
+                            type_name == full_name
+                                || type_name.strip_prefix(full_name.as_str()).is_some_and(|rest| rest.starts_with('.'))
+                        })
+                    })
+                    .filter(|&dep| dep != index)
+                    .collect()
+            })
+            .collect();
 
-            // Remove this node from all dependency sets
-            for (_, d) in deps.iter_mut() {
-                d.remove(name);
-            }
+        // Kahn's algorithm, always taking the first ready message in declaration
+        // order so the output is stable across runs
+        let mut result = Vec::new();
+        let mut emitted: HashSet<usize> = HashSet::new();
+        let mut remaining: Vec<usize> = (0..messages.len()).collect();
 
-            // Find new nodes with no dependencies
-            for (n, d) in deps.iter() {
-                if d.is_empty() && !result.iter().any(|m| m.name.as_deref() == Some(*n)) && !no_deps.contains(n) {
-                    no_deps.push(*n);
-                }
-            }
+        while let Some(position) = remaining.iter().position(|m| deps[*m].iter().all(|d| emitted.contains(d))) {
+            let index = remaining.remove(position);
+            emitted.insert(index);
+            result.push(&messages[index]);
         }
 
         // If we didn't get all messages, there's a cycle - just append remaining
-        for msg in messages {
-            if !result.iter().any(|m| m.name == msg.name) {
-                result.push(msg);
-            }
-        }
+        result.extend(remaining.into_iter().map(|index| &messages[index]));
 
         result
     }
 
-    fn generate_enum(&self, desc: &EnumDescriptorProto) -> Result<String> {
+    fn generate_enum(
+        &self,
+        desc: &EnumDescriptorProto,
+        scope: &str,
+        comments: &SourceComments,
+        path: &[i32],
+    ) -> Result<ModuleCode> {
         let name = desc.name.as_deref().unwrap_or("UnknownEnum");
+        let full_name = qualify(scope, name);
+        let type_name = self.to_rescript_type_name(name);
+        check_identifier("enum", &full_name, &type_name, Case::Upper)?;
 
-        let variants: Vec<(String, i32)> = desc
+        let variants = desc
             .value
             .iter()
-            .map(|v| {
+            .enumerate()
+            .map(|(i, v)| {
                 let variant_name = v.name.as_deref().unwrap_or("UNKNOWN");
-                let number = v.number.unwrap_or(0);
-                (self.to_rescript_variant(variant_name), number)
+                let rescript_name = self.to_rescript_variant(variant_name);
+                check_identifier("enum value", &qualify(&full_name, variant_name), &rescript_name, Case::Upper)?;
+                Ok(EnumVariant {
+                    name: rescript_name,
+                    proto_name: variant_name.to_string(),
+                    number: v.number.unwrap_or(0),
+                    doc: comments.get(&child_path(path, ENUM_VALUE, i)),
+                    deprecated: v.options.as_ref().map(|o| o.deprecated()).unwrap_or(false),
+                })
             })
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
 
         let template = EnumTemplate {
-            name: self.to_rescript_type_name(name),
+            name: type_name,
             variants,
+            doc: comments.get(path),
+            deprecated: desc.options.as_ref().map(|o| o.deprecated()).unwrap_or(false),
         };
 
-        Ok(template.render())
+        Ok(template.render_module())
     }
 
-    fn generate_message(&self, desc: &DescriptorProto, options: &Options) -> Result<String> {
+    fn generate_message(
+        &self,
+        desc: &DescriptorProto,
+        options: &Options,
+        scope: &str,
+        parent: &ModuleScope,
+        comments: &SourceComments,
+        path: &[i32],
+    ) -> Result<ModuleCode> {
         let name = desc.name.as_deref().unwrap_or("UnknownMessage");
+        let full_name = qualify(scope, name);
+        let type_name = self.referenced_type_name(&full_name);
+        check_identifier("message", &full_name, &type_name, Case::Upper)?;
+        let modules = parent.child(&type_name);
 
         // Collect all fields with their oneof index
-        let fields: Vec<FieldInfo> = desc
-            .field
-            .iter()
-            .map(|f| self.field_to_info(f))
-            .collect();
+        let mut fields = Vec::new();
+        for (i, f) in desc.field.iter().enumerate() {
+            let field_name = qualify(&full_name, f.name.as_deref().unwrap_or("unknown"));
+            let overrides = self.custom.field(&field_name).cloned().unwrap_or_default();
+            if overrides.skip {
+                continue;
+            }
+
+            let mut info = FieldInfo {
+                doc: comments.get(&child_path(path, MESSAGE_FIELD, i)),
+                ..self.field_to_info(f, &modules)
+            };
+            if let Some(name) = overrides.field_name {
+                info.name = name;
+            }
+            if let Some(mapping) = overrides.custom_type {
+                match (mapping.name, mapping.encode, mapping.decode) {
+                    (Some(rescript_type), Some(encode), Some(decode)) => {
+                        info.rescript_type = rescript_type;
+                        info.custom_codec = Some((encode, decode));
+                    }
+                    _ => bail!("field `{}`: (rescript.type) needs name, encode and decode", field_name),
+                }
+            } else if let Some(referenced) = f.type_name.as_deref().filter(|t| self.is_skipped(t)) {
+                bail!(
+                    "field `{}`: uses message `{}` marked (rescript.skip_message); skip the field or map it with (rescript.type)",
+                    field_name,
+                    referenced.trim_start_matches('.')
+                );
+            }
+            check_identifier("field", &field_name, &info.name, Case::Lower)?;
+            fields.push(info);
+        }
 
         // Build oneof information
         let mut oneofs: Vec<OneOfInfo> = Vec::new();
@@ -216,79 +549,204 @@ impl Generator {
             }
 
             if !oneof_fields.is_empty() {
+                // The oneof name is both a record field and a type in the message module
+                let reserved = [naming::RESERVED_FIELDS, naming::RESERVED_TYPES].concat();
+                let rescript_name = naming::value_name(oneof_name, self.options.field_style, &reserved);
+                check_identifier("oneof", &qualify(&full_name, oneof_name), &rescript_name, Case::Lower)?;
+                for field in &oneof_fields {
+                    // Oneof members become variant constructors
+                    let constructor = naming::constructor_name(&field.name);
+                    check_identifier("field", &qualify(&full_name, &field.proto_name), &constructor, Case::Upper)?;
+                }
                 oneofs.push(OneOfInfo {
-                    name: self.to_rescript_field_name(oneof_name),
-                    rescript_name: self.to_rescript_type_name(oneof_name),
+                    name: rescript_name,
                     fields: oneof_fields,
+                    doc: comments.get(&child_path(path, MESSAGE_ONEOF_DECL, idx)),
                 });
             }
         }
 
         // Handle nested types
         let mut nested = Vec::new();
-        for nested_enum in &desc.enum_type {
-            nested.push(self.generate_enum(nested_enum)?);
+        for (i, nested_enum) in desc.enum_type.iter().enumerate() {
+            let nested_path = child_path(path, MESSAGE_ENUM_TYPE, i);
+            nested.push(self.generate_enum(nested_enum, &full_name, comments, &nested_path)?);
         }
-        for nested_msg in &desc.nested_type {
+        for nested_msg in self.topological_sort_messages(&desc.nested_type, &full_name) {
             // Skip map entry types (auto-generated)
             if nested_msg.options.as_ref().map(|o| o.map_entry()).unwrap_or(false) {
                 continue;
             }
-            nested.push(self.generate_message(nested_msg, options)?);
+            if self.is_skipped(&qualify(&full_name, nested_msg.name.as_deref().unwrap_or(""))) {
+                continue;
+            }
+            let index = desc.nested_type.iter().position(|m| std::ptr::eq(m, nested_msg)).unwrap_or(0);
+            let nested_path = child_path(path, MESSAGE_NESTED_TYPE, index);
+            nested.push(self.generate_message(nested_msg, options, &full_name, &modules, comments, &nested_path)?);
         }
 
         let template = MessageTemplate {
-            name: self.to_rescript_type_name(name),
+            name: type_name,
             fields,
             oneofs,
             nested,
-            use_wasm: options.wasm,
+            wasm_schema: if options.wasm { Some(self.wasm_fields(&full_name, modules.types, &mut Vec::new())) } else { None },
+            any_type: if options.any_types { Some(full_name.clone()) } else { None },
+            field_masks: options.field_masks,
+            extendee: if desc.extension_range.is_empty() { None } else { Some(full_name) },
+            doc: comments.get(path),
+            deprecated: desc.options.as_ref().map(|o| o.deprecated()).unwrap_or(false),
         };
 
-        Ok(template.render())
+        Ok(template.render_module())
+    }
+
+    /// Module paths of the messages that get an `anyType`, nested ones included
+    fn collect_any_types(&self, messages: &[DescriptorProto], scope: &str, prefix: &str, out: &mut Vec<String>) {
+        for msg in messages {
+            let full_name = qualify(scope, msg.name.as_deref().unwrap_or(""));
+            let is_map_entry = msg.options.as_ref().map(|o| o.map_entry()).unwrap_or(false);
+            if is_map_entry || self.is_skipped(&full_name) {
+                continue;
+            }
+            let path = format!("{}{}", prefix, self.referenced_type_name(&full_name));
+            out.push(path.clone());
+            self.collect_any_types(&msg.nested_type, &full_name, &format!("{}.", path), out);
+        }
+    }
+
+    /// Collect `extend` fields declared at this scope and inside nested messages
+    ///
+    /// `modules` is the extensions module being collected, its last segment
+    /// the module name. `paths` is the element path of this scope plus the
+    /// descriptor field numbers of its extensions and nested messages (they
+    /// differ between files and messages).
+    fn collect_extensions(
+        &self,
+        modules: &ModuleScope,
+        extensions: &[FieldDescriptorProto],
+        messages: &[DescriptorProto],
+        scope: &str,
+        comments: &SourceComments,
+        paths: (&[i32], i32, i32),
+    ) -> Result<ExtensionsTemplate> {
+        let (path, extension_field, message_field) = paths;
+
+        let extensions = extensions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let name = f.name.as_deref().unwrap_or("unknown");
+                let field = FieldInfo {
+                    doc: comments.get(&child_path(path, extension_field, i)),
+                    ..self.field_to_info(f, modules)
+                };
+                check_identifier("extension", &qualify(scope, name), &field.name, Case::Lower)?;
+                Ok(ExtensionInfo {
+                    field,
+                    full_name: qualify(scope, name),
+                    extendee: f.extendee.as_deref().unwrap_or("").trim_start_matches('.').to_string(),
+                    wasm_type: self.proto_type_to_wasm(f, modules.types, &mut Vec::new()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let nested = messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let name = m.name.as_deref().unwrap_or("UnknownMessage");
+                self.collect_extensions(
+                    &modules.child(&self.to_rescript_type_name(name)),
+                    &m.extension,
+                    &m.nested_type,
+                    &qualify(scope, name),
+                    comments,
+                    (&child_path(path, message_field, i), MESSAGE_EXTENSION, MESSAGE_NESTED_TYPE),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ExtensionsTemplate {
+            name: modules.path.last().cloned().unwrap_or_default(),
+            extensions,
+            nested,
+        })
     }
 
-    fn generate_service(&self, desc: &ServiceDescriptorProto) -> Result<String> {
+    fn generate_service(
+        &self,
+        desc: &ServiceDescriptorProto,
+        scope: &str,
+        parent: &ModuleScope,
+        comments: &SourceComments,
+        path: &[i32],
+    ) -> Result<ModuleCode> {
         let name = desc.name.as_deref().unwrap_or("UnknownService");
+        let full_name = qualify(scope, name);
+        let type_name = self.to_rescript_type_name(name);
+        check_identifier("service", &full_name, &type_name, Case::Upper)?;
+        let modules = parent.child(&type_name);
 
-        let methods: Vec<MethodInfo> = desc
+        let methods = desc
             .method
             .iter()
-            .map(|m| {
+            .enumerate()
+            .map(|(i, m)| {
                 let method_name = m.name.as_deref().unwrap_or("unknownMethod");
+                let function_name =
+                    naming::value_name(method_name, FieldStyle::Camel, naming::RESERVED_CLIENT_VALUES);
+                check_identifier("method", &qualify(&full_name, method_name), &function_name, Case::Lower)?;
                 let input = m.input_type.as_deref().unwrap_or(".Unknown");
                 let output = m.output_type.as_deref().unwrap_or(".Unknown");
 
-                // Extract simple type names from fully qualified names
-                let input_simple = input.rsplit('.').next().unwrap_or(input);
-                let output_simple = output.rsplit('.').next().unwrap_or(output);
+                for message in [input, output] {
+                    if self.is_skipped(message) {
+                        bail!(
+                            "method `{}`: uses message `{}` marked (rescript.skip_message)",
+                            qualify(&full_name, method_name),
+                            message.trim_start_matches('.')
+                        );
+                    }
+                }
 
-                MethodInfo {
+                Ok(MethodInfo {
                     name: method_name.to_string(),
-                    input_type: self.to_rescript_type_name(input_simple),
-                    output_type: self.to_rescript_type_name(output_simple),
+                    function_name,
+                    input_type: self.qualified_type_name(input, &modules),
+                    output_type: self.qualified_type_name(output, &modules),
                     client_streaming: m.client_streaming.unwrap_or(false),
                     server_streaming: m.server_streaming.unwrap_or(false),
-                }
+                    doc: comments.get(&child_path(path, SERVICE_METHOD, i)),
+                    deprecated: m.options.as_ref().map(|o| o.deprecated()).unwrap_or(false),
+                    deprecated_types: modules.is_deprecated(input) || modules.is_deprecated(output),
+                    idempotent: m.options.as_ref().is_some_and(|o| {
+                        matches!(o.idempotency_level(), IdempotencyLevel::NoSideEffects | IdempotencyLevel::Idempotent)
+                    }),
+                })
             })
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
 
         let template = ServiceTemplate {
-            name: self.to_rescript_type_name(name),
+            name: type_name,
+            full_name,
             methods,
+            doc: comments.get(path),
+            deprecated: desc.options.as_ref().map(|o| o.deprecated()).unwrap_or(false),
         };
 
-        Ok(template.render())
+        Ok(template.render_module())
     }
 
-    fn field_to_info(&self, field: &FieldDescriptorProto) -> FieldInfo {
+    fn field_to_info(&self, field: &FieldDescriptorProto, modules: &ModuleScope) -> FieldInfo {
         use prost_types::field_descriptor_proto::Type;
 
         let name = field.name.as_deref().unwrap_or("unknown");
         let number = field.number.unwrap_or(0);
         let is_repeated = field.label() == prost_types::field_descriptor_proto::Label::Repeated;
 
-        let is_message = matches!(field.r#type(), Type::Message);
+        // Groups are generated as nested message types
+        let is_message = matches!(field.r#type(), Type::Message | Type::Group);
         let is_enum = matches!(field.r#type(), Type::Enum);
 
         // Get oneof index if this field is part of a oneof
@@ -323,7 +781,7 @@ impl Generator {
             field.proto3_optional.unwrap_or(false)
         };
 
-        let rescript_type = self.proto_type_to_rescript(field);
+        let rescript_type = self.proto_type_to_rescript(field, modules);
 
         FieldInfo {
             name: self.to_rescript_field_name(name),
@@ -336,10 +794,14 @@ impl Generator {
             is_enum,
             oneof_index,
             well_known_type,
+            custom_codec: None,
+            doc: None,
+            deprecated: field.options.as_ref().map(|o| o.deprecated()).unwrap_or(false),
+            deprecated_type: modules.is_deprecated(type_name),
         }
     }
 
-    fn proto_type_to_rescript(&self, field: &FieldDescriptorProto) -> String {
+    fn proto_type_to_rescript(&self, field: &FieldDescriptorProto, modules: &ModuleScope) -> String {
         use prost_types::field_descriptor_proto::Type;
 
         match field.r#type() {
@@ -353,7 +815,7 @@ impl Generator {
             Type::Bool => "bool".to_string(),
             Type::String => "string".to_string(),
             Type::Bytes => "Js.Typed_array.Uint8Array.t".to_string(),
-            Type::Message | Type::Enum => {
+            Type::Message | Type::Enum | Type::Group => {
                 // Extract type name from fully qualified name
                 let type_name = field.type_name.as_deref().unwrap_or("unknown");
 
@@ -362,16 +824,126 @@ impl Generator {
                     return wkt_type;
                 }
 
-                let simple_name = type_name.rsplit('.').next().unwrap_or(type_name);
-                format!("{}.t", self.to_rescript_type_name(simple_name))
+                format!("{}.t", self.qualified_type_name(type_name, modules))
             }
-            Type::Group => "unit".to_string(), // Deprecated, treat as unit
         }
     }
 
+    /// Map a field's proto type to the WasmCodec.fieldType constructor
+    ///
+    /// Message and group values get an inline schema of their type; None if
+    /// the type is recursive (or missing from the request), since an inline
+    /// schema cannot express that. `visiting` holds the enclosing types.
+    fn proto_type_to_wasm(&self, field: &FieldDescriptorProto, types: &TypeIndex, visiting: &mut Vec<String>) -> Option<String> {
+        use prost_types::field_descriptor_proto::Type;
+
+        let name = match field.r#type() {
+            Type::Double => "Double",
+            Type::Float => "Float",
+            Type::Int64 => "Int64",
+            Type::Uint64 => "Uint64",
+            Type::Int32 => "Int32",
+            Type::Fixed64 => "Fixed64",
+            Type::Fixed32 => "Fixed32",
+            Type::Bool => "Bool",
+            Type::String => "String",
+            Type::Bytes => "Bytes",
+            Type::Uint32 => "Uint32",
+            Type::Enum => "Enum",
+            Type::Sfixed32 => "Sfixed32",
+            Type::Sfixed64 => "Sfixed64",
+            Type::Sint32 => "Sint32",
+            Type::Sint64 => "Sint64",
+            // Well-known types are built into the codec and use the same JSON
+            // as their WellKnown.* codecs
+            Type::Message => {
+                let type_name = field.type_name.as_deref().unwrap_or("");
+                if self.well_known_type_to_rescript(type_name).is_some() {
+                    return Some(format!("WellKnown(\"{}\")", type_name.trim_start_matches('.')));
+                }
+                return Some(format!("Message({})", self.wasm_schema(type_name, types, visiting)?));
+            }
+            Type::Group => {
+                let type_name = field.type_name.as_deref().unwrap_or("");
+                return Some(format!("Group({})", self.wasm_schema(type_name, types, visiting)?));
+            }
+        };
+        Some(name.to_string())
+    }
+
+    /// Record field and constructor of a oneof member, as in the generated
+    /// variant type; None for other fields and proto3 `optional` ones
+    fn oneof_member(&self, message: &str, desc: &DescriptorProto, field: &FieldDescriptorProto) -> Option<(String, String)> {
+        if field.proto3_optional() {
+            return None;
+        }
+        let oneof = desc.oneof_decl.get(usize::try_from(field.oneof_index?).ok()?)?;
+        let reserved = [naming::RESERVED_FIELDS, naming::RESERVED_TYPES].concat();
+        let group = naming::value_name(oneof.name(), self.options.field_style, &reserved);
+        let field_name = qualify(message, field.name());
+        let name = match self.custom.field(&field_name).and_then(|o| o.field_name.clone()) {
+            Some(name) => name,
+            None => self.to_rescript_field_name(field.name()),
+        };
+        Some((group, naming::constructor_name(&name)))
+    }
+
+    /// Inline `array<WasmCodec.fieldDescriptor>` of a message, keyed by proto
+    /// field names like the generated `toJson`
+    fn wasm_schema(&self, type_name: &str, types: &TypeIndex, visiting: &mut Vec<String>) -> Option<String> {
+        Some(format!("[{}]", self.wasm_fields(type_name, types, visiting)?.join(", ")))
+    }
+
+    /// The `WasmCodec.fieldDescriptor` records of a message's fields
+    fn wasm_fields(&self, type_name: &str, types: &TypeIndex, visiting: &mut Vec<String>) -> Option<Vec<String>> {
+        use prost_types::field_descriptor_proto::{Label, Type};
+
+        let full_name = type_name.trim_start_matches('.');
+        let (desc, proto3) = *types.messages.get(full_name)?;
+        if visiting.iter().any(|t| t == full_name) {
+            return None;
+        }
+
+        visiting.push(full_name.to_string());
+        let fields = desc
+            .field
+            .iter()
+            .map(|f| {
+                let field_type = self.proto_type_to_wasm(f, types, visiting)?;
+                let repeated = f.label() == Label::Repeated;
+                // Proto2 fields have explicit presence, proto3 ones only with `optional`
+                let optional = !repeated && (!proto3 || f.proto3_optional());
+                let mut out = format!(
+                    "{{n: {}, name: \"{}\", fieldType: WasmCodec.{}, repeated: {}, optional: {}",
+                    f.number.unwrap_or(0),
+                    f.name.as_deref().unwrap_or("unknown"),
+                    field_type,
+                    repeated,
+                    optional
+                );
+                // Scalars are packed by default in proto3 only
+                let packable = !matches!(f.r#type(), Type::String | Type::Bytes | Type::Message | Type::Group);
+                if repeated && packable && !f.options.as_ref().and_then(|o| o.packed).unwrap_or(proto3) {
+                    out.push_str(", packed: false");
+                }
+                if let Some((group, variant)) = self.oneof_member(full_name, desc, f) {
+                    out.push_str(&format!(", oneof: \"{}\", variant: \"{}\"", group, variant));
+                }
+                out.push('}');
+                Some(out)
+            })
+            .collect::<Option<Vec<_>>>();
+        visiting.pop();
+
+        fields
+    }
+
     /// Map well-known types to ReScript types
     fn well_known_type_to_rescript(&self, type_name: &str) -> Option<String> {
         match type_name {
+            // Exact seconds/nanos records
+            ".google.protobuf.Timestamp" if self.options.precise_time => Some("WellKnown.PreciseTimestamp.t".to_string()),
+            ".google.protobuf.Duration" if self.options.precise_time => Some("WellKnown.PreciseDuration.t".to_string()),
             // Timestamp -> Js.Date.t
             ".google.protobuf.Timestamp" => Some("Js.Date.t".to_string()),
             // Duration -> float (seconds)
@@ -398,6 +970,8 @@ impl Generator {
             ".google.protobuf.NullValue" => Some("Js.Null.t<unit>".to_string()),
             // Any -> special type with @type field
             ".google.protobuf.Any" => Some("WellKnown.Any.t".to_string()),
+            // FieldMask -> paths with proto field names
+            ".google.protobuf.FieldMask" => Some("WellKnown.FieldMask.t".to_string()),
             _ => None,
         }
     }
@@ -407,109 +981,260 @@ impl Generator {
         type_name.starts_with(".google.protobuf.")
     }
 
+    /// Module name of a proto file, "path/to/user.proto" -> "UserProto"
+    ///
+    /// Names that would shadow a reserved module get a `_` (`json.proto` with
+    /// `module_suffix=none` -> `Json_`); names that are not identifiers at all
+    /// (`2fa.proto` -> `2faProto`) are reported by `generate_file`.
     fn proto_to_module_name(&self, file_name: &str) -> String {
-        // "path/to/user.proto" -> "UserProto"
+        if let Some(module_name) = self.custom.module_name(file_name) {
+            return module_name.to_string();
+        }
+
         let base = file_name
             .rsplit('/')
             .next()
             .unwrap_or(file_name)
             .trim_end_matches(".proto");
 
-        let mut result = String::new();
-        let mut capitalize_next = true;
-
-        for c in base.chars() {
-            if c == '_' || c == '-' {
-                capitalize_next = true;
-            } else if capitalize_next {
-                result.push(c.to_ascii_uppercase());
-                capitalize_next = false;
-            } else {
-                result.push(c);
-            }
-        }
-
-        format!("{}Proto", result)
+        naming::module_name(format!("{}{}", pascal_case(base), self.options.module_suffix))
     }
 
     fn to_rescript_type_name(&self, name: &str) -> String {
-        // PascalCase for type/module names
-        let mut result = String::new();
-        let mut capitalize_next = true;
-
-        for c in name.chars() {
-            if c == '_' {
-                capitalize_next = true;
-            } else if capitalize_next {
-                result.push(c.to_ascii_uppercase());
-                capitalize_next = false;
-            } else {
-                result.push(c);
-            }
+        naming::type_name(name)
+    }
+
+    /// Module name of a message or enum referenced by its fully-qualified name
+    fn referenced_type_name(&self, type_name: &str) -> String {
+        match self.custom.message(type_name).and_then(|m| m.type_name.clone()) {
+            Some(name) => name,
+            None => self.to_rescript_type_name(type_name.rsplit('.').next().unwrap_or(type_name)),
         }
+    }
 
-        result
+    /// Module path of a referenced message or enum as seen from `modules`
+    ///
+    /// Types missing from the index keep their own module name.
+    fn qualified_type_name(&self, type_name: &str, modules: &ModuleScope) -> String {
+        modules.reference(type_name).unwrap_or_else(|| self.referenced_type_name(type_name))
+    }
+
+    /// Whether a message is marked `(rescript.skip_message)`
+    fn is_skipped(&self, full_name: &str) -> bool {
+        self.custom.message(full_name).is_some_and(|m| m.skip)
     }
 
     fn to_rescript_field_name(&self, name: &str) -> String {
-        // camelCase for field names
-        let mut result = String::new();
-        let mut capitalize_next = false;
-
-        for c in name.chars() {
-            if c == '_' {
-                capitalize_next = true;
-            } else if capitalize_next {
-                result.push(c.to_ascii_uppercase());
-                capitalize_next = false;
-            } else {
-                result.push(c);
-            }
+        naming::value_name(name, self.options.field_style, naming::RESERVED_FIELDS)
+    }
+
+    fn to_rescript_variant(&self, name: &str) -> String {
+        naming::variant_name(name)
+    }
+}
+
+/// PascalCase a file or directory name ("user_service" -> "UserService")
+fn pascal_case(name: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize_next = true;
+
+    for c in name.chars() {
+        if c == '_' || c == '-' || c == '.' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            result.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            result.push(c);
         }
+    }
 
-        // Escape ReScript keywords
-        match result.as_str() {
-            "type" | "open" | "let" | "module" | "switch" | "if" | "else" | "while" | "for"
-            | "try" | "catch" | "as" | "and" | "or" | "true" | "false" | "rec" | "external"
-            | "mutable" | "include" | "private" | "constraint" | "lazy" | "assert"
-            | "exception" => {
-                format!("{}_", result)
-            }
-            _ => result,
+    result
+}
+
+/// Module paths of messages and enums by fully-qualified name, from their
+/// top-level module
+#[derive(Default)]
+struct TypeIndex<'a> {
+    paths: HashMap<String, Vec<String>>,
+    /// Message descriptors and whether their file is proto3, for inline WASM schemas
+    messages: HashMap<String, (&'a DescriptorProto, bool)>,
+    /// Messages and enums marked `deprecated = true`
+    deprecated: HashSet<String>,
+}
+
+/// The module code is being generated for, used to qualify type references
+struct ModuleScope<'a> {
+    types: &'a TypeIndex<'a>,
+    /// Module path from the top-level module
+    path: Vec<String>,
+}
+
+impl ModuleScope<'_> {
+    fn child(&self, name: &str) -> Self {
+        let mut path = self.path.clone();
+        path.push(name.to_string());
+        ModuleScope { types: self.types, path }
+    }
+
+    /// Module path that names `type_name` from inside this module
+    ///
+    /// Enclosing modules cannot be named from their own body, so the path
+    /// starts below the innermost module shared with this one: `Users.User`
+    /// from `Acme.Orders`, `UserProto.User` from another file.
+    fn reference(&self, type_name: &str) -> Option<String> {
+        let target = self.types.paths.get(type_name.trim_start_matches('.'))?;
+        let common = target.iter().zip(&self.path).take_while(|(a, b)| a == b).count();
+        if common == target.len() {
+            // The module itself or one enclosing it, which is not in scope yet
+            return target.last().cloned();
         }
+        Some(target[common..].join("."))
     }
 
-    fn to_rescript_variant(&self, name: &str) -> String {
-        // Variant names should be PascalCase and start with uppercase
-        let pascal = self.to_rescript_type_name(name);
-
-        // Handle SCREAMING_SNAKE_CASE enum values
-        if name.chars().all(|c| c.is_uppercase() || c == '_' || c.is_numeric()) {
-            // SOME_VALUE -> SomeValue
-            let mut result = String::new();
-            let mut capitalize_next = true;
-
-            for c in name.chars() {
-                if c == '_' {
-                    capitalize_next = true;
-                } else if capitalize_next {
-                    result.push(c.to_ascii_uppercase());
-                    capitalize_next = false;
-                } else {
-                    result.push(c.to_ascii_lowercase());
+    /// Whether a referenced message or enum is marked `deprecated = true`
+    fn is_deprecated(&self, type_name: &str) -> bool {
+        self.types.deprecated.contains(type_name.trim_start_matches('.'))
+    }
+}
+
+/// Modules of one proto package and its sub-packages (package layout)
+#[derive(Default)]
+struct PackageTree {
+    modules: Vec<ModuleCode>,
+    /// Proto file that generated each top-level module, for collision reports
+    origins: HashMap<String, String>,
+    packages: Vec<String>,
+    source_files: Vec<String>,
+    children: Vec<(String, PackageTree)>,
+}
+
+impl PackageTree {
+    fn insert(&mut self, segments: &[String], package: &str, file_name: &str, modules: Vec<ModuleCode>) -> Result<()> {
+        match segments.split_first() {
+            Some((first, rest)) => {
+                let index = match self.children.iter().position(|(name, _)| name == first) {
+                    Some(index) => index,
+                    None => {
+                        if let Some(existing) = self.origins.get(first) {
+                            bail!("{}: sub-package module `{}` collides with a definition in {}", file_name, first, existing);
+                        }
+                        self.children.push((first.clone(), PackageTree::default()));
+                        self.children.len() - 1
+                    }
+                };
+                self.children[index].1.insert(rest, package, file_name, modules)
+            }
+            None => {
+                for module in modules {
+                    if let Some(existing) = self.origins.get(&module.name) {
+                        bail!(
+                            "{} and {} both generate module `{}` in package {}",
+                            existing,
+                            file_name,
+                            module.name,
+                            package
+                        );
+                    }
+                    // Sub-package modules share the scope with this package's definitions
+                    if self.children.iter().any(|(name, _)| *name == module.name) {
+                        bail!("{}: module `{}` collides with a sub-package of {}", file_name, module.name, package);
+                    }
+                    self.origins.insert(module.name.clone(), file_name.to_string());
+                    self.modules.push(module);
+                }
+                if !self.packages.iter().any(|p| p == package) {
+                    self.packages.push(package.to_string());
                 }
+                self.source_files.push(file_name.to_string());
+                Ok(())
             }
+        }
+    }
 
-            result
-        } else {
-            pascal
+    fn packages(&self) -> Vec<String> {
+        let mut packages = self.packages.clone();
+        packages.extend(self.children.iter().flat_map(|(_, child)| child.packages()));
+        packages
+    }
+
+    fn source_files(&self) -> Vec<String> {
+        let mut files = self.source_files.clone();
+        files.extend(self.children.iter().flat_map(|(_, child)| child.source_files()));
+        files
+    }
+
+    /// This package's modules followed by one nested module per sub-package
+    fn into_modules(self) -> Vec<ModuleCode> {
+        let mut modules = self.modules;
+        for (name, child) in self.children {
+            modules.push(nest_modules(&name, &child.into_modules()));
         }
+        modules
+    }
+}
+
+/// Expected case of the first letter of a generated identifier
+#[derive(Clone, Copy)]
+enum Case {
+    /// Modules, types and constructors
+    Upper,
+    /// Values, record fields and labels
+    Lower,
+}
+
+/// Fail with the element's proto name if `generated` is not a valid ReScript identifier
+fn check_identifier(kind: &str, full_name: &str, generated: &str, case: Case) -> Result<()> {
+    let mut chars = generated.chars();
+    let first_ok = match (chars.next(), case) {
+        (Some(c), Case::Upper) => c.is_ascii_uppercase(),
+        (Some(c), Case::Lower) => c.is_ascii_lowercase() || c == '_',
+        (None, _) => false,
+    };
+
+    let valid = first_ok && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '\'');
+    if valid && !naming::KEYWORDS.contains(&generated) {
+        Ok(())
+    } else {
+        bail!(
+            "{} `{}`: cannot generate a ReScript identifier (got `{}`)",
+            kind,
+            full_name,
+            generated
+        )
+    }
+}
+
+/// Join a proto scope and a simple name into a fully-qualified name
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use prost_types::field_descriptor_proto::{Label, Type};
+
+    /// An optional field; set the label, `oneof_index` and the rest with struct update
+    fn field(name: &str, number: i32, r#type: Type, type_name: Option<&str>) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(r#type as i32),
+            type_name: type_name.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// A repeated field
+    fn repeated(name: &str, number: i32, r#type: Type, type_name: Option<&str>) -> FieldDescriptorProto {
+        FieldDescriptorProto { label: Some(Label::Repeated as i32), ..field(name, number, r#type, type_name) }
+    }
 
     #[test]
     fn test_proto_to_module_name() {
@@ -528,6 +1253,899 @@ mod tests {
         assert_eq!(generator.to_rescript_field_name("type"), "type_");
     }
 
+    #[test]
+    fn test_module_suffix() {
+        let generator = Generator::new(Options { module_suffix: String::new(), ..Options::default() });
+        assert_eq!(generator.proto_to_module_name("user.proto"), "User");
+        // Runtime and stdlib modules are never shadowed
+        assert_eq!(generator.proto_to_module_name("json.proto"), "Json_");
+        assert_eq!(generator.proto_to_module_name("retry.proto"), "Retry_");
+        assert_eq!(generator.proto_to_module_name("result.proto"), "Result_");
+        assert_eq!(generator.proto_to_module_name("well_known.proto"), "WellKnown_");
+
+        let generator = Generator::new(Options { module_suffix: "Pb".to_string(), ..Options::default() });
+        assert_eq!(generator.proto_to_module_name("path/to/user_service.proto"), "UserServicePb");
+    }
+
+    #[test]
+    fn test_invalid_file_module_names() {
+        let file = |name: &str| FileDescriptorProto { name: Some(name.to_string()), ..Default::default() };
+        let error = |generator: &Generator, name: &str| {
+            generator.generate_file(&file(name), &TypeIndex::default()).unwrap_err().to_string()
+        };
+
+        let generator = Generator::new(Options::default());
+        assert_eq!(error(&generator, "2fa.proto"), "file `2fa.proto`: cannot generate a ReScript identifier (got `2faProto`)");
+    }
+
+    #[test]
+    fn test_field_naming_styles() {
+        let generator = Generator::new(Options::default());
+        assert_eq!(generator.to_rescript_field_name("await"), "await_");
+        assert_eq!(generator.to_rescript_field_name("unknown_fields"), "unknownFields_");
+
+        let generator = Generator::new(Options { field_style: FieldStyle::Snake, ..Options::default() });
+        assert_eq!(generator.to_rescript_field_name("user_name"), "user_name");
+        assert_eq!(generator.to_rescript_field_name("type"), "type_");
+    }
+
+    #[test]
+    fn test_extensions() {
+        use prost_types::descriptor_proto::ExtensionRange;
+
+        let ext = |name: &str, number: i32, r#type: Type, label: Label| FieldDescriptorProto {
+            label: Some(label as i32),
+            extendee: Some(".example.Task".to_string()),
+            ..field(name, number, r#type, None)
+        };
+
+        let file = FileDescriptorProto {
+            name: Some("task.proto".to_string()),
+            package: Some("example".to_string()),
+            syntax: Some("proto2".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("Task".to_string()),
+                    extension_range: vec![ExtensionRange {
+                        start: Some(100),
+                        end: Some(200),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Audit".to_string()),
+                    extension: vec![ext("audit_tags", 101, Type::String, Label::Repeated)],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Note".to_string()),
+                    field: vec![
+                        FieldDescriptorProto { extendee: None, ..ext("text", 1, Type::String, Label::Optional) },
+                        FieldDescriptorProto { extendee: None, ..ext("ids", 2, Type::Int32, Label::Repeated) },
+                    ],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Tree".to_string()),
+                    field: vec![FieldDescriptorProto {
+                        type_name: Some(".example.Tree".to_string()),
+                        extendee: None,
+                        ..ext("children", 1, Type::Message, Label::Repeated)
+                    }],
+                    ..Default::default()
+                },
+            ],
+            extension: vec![
+                ext("priority", 100, Type::Int32, Label::Optional),
+                FieldDescriptorProto {
+                    type_name: Some(".google.protobuf.Timestamp".to_string()),
+                    ..ext("due", 102, Type::Message, Label::Optional)
+                },
+                FieldDescriptorProto {
+                    type_name: Some(".example.Note".to_string()),
+                    ..ext("note", 103, Type::Message, Label::Optional)
+                },
+                FieldDescriptorProto {
+                    type_name: Some(".example.Tree".to_string()),
+                    ..ext("tree", 104, Type::Message, Label::Optional)
+                },
+            ],
+            ..Default::default()
+        };
+
+        let generator = Generator::new(Options::default());
+        let types = generator.type_index(std::slice::from_ref(&file));
+        let output = generator.generate_file(&file, &types).unwrap()[0].content.clone().unwrap();
+
+        // Extendable messages carry extension values
+        assert!(output.contains("    extensions: Extension.values,\n"));
+        assert!(output.contains("~extensions=Extension.empty()"));
+        assert!(output.contains("Extension.fromJsonObject(obj, ~extendee=\"example.Task\")"));
+        assert!(output.contains("Extension.toJsonFields(msg.extensions)"));
+
+        // Typed descriptors, with nested extensions scoped by message
+        assert!(output.contains("  let priority: Extension.t<int> = Extension.make(\n"));
+        assert!(output.contains("    ~name=\"example.priority\",\n"));
+        assert!(output.contains("    ~wasmType=WasmCodec.Int32,\n"));
+        assert!(output.contains("    ~wasmType=WasmCodec.WellKnown(\"google.protobuf.Timestamp\"),\n"));
+        assert!(output.contains("  module Audit = {\n"));
+        assert!(output.contains("    let auditTags: Extension.t<array<string>> = Extension.make(\n"));
+        assert!(output.contains("      ~name=\"example.Audit.audit_tags\",\n"));
+        assert!(output.contains("    Audit.register()\n"));
+
+        // Message values get an inline schema, unless their type is recursive
+        assert!(output.contains(
+            "    ~wasmType=WasmCodec.Message([\
+             {n: 1, name: \"text\", fieldType: WasmCodec.String, repeated: false, optional: true}, \
+             {n: 2, name: \"ids\", fieldType: WasmCodec.Int32, repeated: true, optional: false, packed: false}]),\n"
+        ));
+        assert!(output.contains(
+            "  // Recursive value type without an inline WASM schema, the binary codec skips it\n\
+             \x20 let tree: Extension.t<Tree.t> = Extension.make(\n"
+        ));
+        assert_eq!(output.matches("~wasmType=").count(), 4);
+    }
+
+    #[test]
+    fn test_any_types() {
+        use prost_types::MessageOptions;
+
+        let file = FileDescriptorProto {
+            name: Some("event.proto".to_string()),
+            package: Some("events".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("UserCreated".to_string()),
+                field: vec![repeated("labels", 1, Type::Message, Some(".events.UserCreated.LabelsEntry"))],
+                nested_type: vec![
+                    DescriptorProto {
+                        name: Some("LabelsEntry".to_string()),
+                        options: Some(MessageOptions { map_entry: Some(true), ..Default::default() }),
+                        ..Default::default()
+                    },
+                    DescriptorProto { name: Some("Source".to_string()), ..Default::default() },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let output = |options: Options| {
+            let files = Generator::new(options).generate_file(&file, &TypeIndex::default()).unwrap();
+            files.into_iter().map(|f| f.content.unwrap()).collect::<Vec<_>>()
+        };
+
+        let default = output(Options::default());
+        assert!(!default[0].contains("anyType"));
+        assert!(!default[0].contains("module AnyTypes"));
+
+        let files = output(Options { any_types: true, interface: true, ..Options::default() });
+        assert!(files[0].contains(
+            "  let anyType: WellKnown.Any.messageType<t> = {typeName: \"events.UserCreated\", toJson, fromJson}\n"
+        ));
+        assert!(files[0].contains("{typeName: \"events.UserCreated.Source\", toJson, fromJson}"));
+        assert!(files[0].contains(
+            "module AnyTypes = {\n  // Register every type so WellKnown.Any.isKnown recognizes it\n  let register = () => {\n    \
+             WellKnown.Any.register(UserCreated.anyType)\n    WellKnown.Any.register(UserCreated.Source.anyType)\n  }\n}\n"
+        ));
+        assert!(!files[0].contains("LabelsEntry.anyType"));
+        assert!(files[1].contains("  let anyType: WellKnown.Any.messageType<t>\n"));
+        assert!(files[1].contains("module AnyTypes: {\n  let register: unit => unit\n}\n"));
+    }
+
+    #[test]
+    fn test_precise_time() {
+        let file = FileDescriptorProto {
+            name: Some("audit.proto".to_string()),
+            package: Some("audit".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("Entry".to_string()),
+                field: vec![
+                    field("recorded_at", 1, Type::Message, Some(".google.protobuf.Timestamp")),
+                    field("elapsed", 2, Type::Message, Some(".google.protobuf.Duration")),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let output = |options: Options| {
+            let files = Generator::new(options).generate_file(&file, &TypeIndex::default()).unwrap();
+            files.into_iter().next().unwrap().content.unwrap()
+        };
+
+        let default = output(Options::default());
+        assert!(default.contains("    recordedAt: option<Js.Date.t>,\n"));
+        assert!(default.contains("    elapsed: option<float>,\n"));
+
+        let precise = output(Options { precise_time: true, wasm: true, ..Options::default() });
+        assert!(precise.contains("    recordedAt: option<WellKnown.PreciseTimestamp.t>,\n"));
+        assert!(precise.contains("    elapsed: option<WellKnown.PreciseDuration.t>,\n"));
+        assert!(precise.contains("WellKnown.PreciseTimestamp.toJson"));
+        assert!(precise.contains("WellKnown.PreciseTimestamp.fromJson"));
+        assert!(precise.contains("WellKnown.PreciseDuration.toJson"));
+        assert!(precise.contains("WellKnown.PreciseDuration.fromJson"));
+        assert!(!precise.contains("WellKnown.Timestamp."));
+    }
+
+    #[test]
+    fn test_field_masks() {
+        use prost_types::OneofDescriptorProto;
+
+        let file = FileDescriptorProto {
+            name: Some("user.proto".to_string()),
+            package: Some("example".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("User".to_string()),
+                    field: vec![
+                        field("display_name", 1, Type::String, None),
+                        repeated("tags", 2, Type::String, None),
+                        field("home_address", 3, Type::Message, Some(".example.Address")),
+                        FieldDescriptorProto { oneof_index: Some(0), ..field("email", 4, Type::String, None) },
+                    ],
+                    oneof_decl: vec![OneofDescriptorProto { name: Some("contact".to_string()), ..Default::default() }],
+                    ..Default::default()
+                },
+                DescriptorProto { name: Some("Address".to_string()), ..Default::default() },
+                DescriptorProto {
+                    name: Some("UpdateUserRequest".to_string()),
+                    field: vec![field("update_mask", 1, Type::Message, Some(".google.protobuf.FieldMask"))],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let output = |options: Options| {
+            let files = Generator::new(options).generate_file(&file, &TypeIndex::default()).unwrap();
+            files.into_iter().map(|f| f.content.unwrap()).collect::<Vec<_>>()
+        };
+
+        let default = output(Options::default());
+        assert!(default[0].contains("    updateMask: option<WellKnown.FieldMask.t>,\n"));
+        assert!(default[0].contains("WellKnown.FieldMask.toJson"));
+        assert!(default[0].contains("WellKnown.FieldMask.fromJson"));
+        assert!(!default[0].contains("applyMask"));
+
+        let files = output(Options { field_masks: true, interface: true, ..Options::default() });
+        assert!(files[0].contains(
+            "  let applyMask = (target: t, source: t, ~mask: WellKnown.FieldMask.t): t => {\n    ...target,\n    \
+             displayName: WellKnown.FieldMask.covers(mask, \"display_name\") ? source.displayName : target.displayName,\n    \
+             tags: WellKnown.FieldMask.covers(mask, \"tags\") ? source.tags : target.tags,\n    \
+             homeAddress: if WellKnown.FieldMask.covers(mask, \"home_address\") {\n      source.homeAddress\n    } else {\n      \
+             switch (target.homeAddress, source.homeAddress, WellKnown.FieldMask.subMask(mask, \"home_address\")) {\n      \
+             | (_, _, []) => target.homeAddress\n      \
+             | (Some(target), Some(source), mask) => Some(Address.applyMask(target, source, ~mask))\n      \
+             | (_, source, _) => source\n      }\n    },\n    \
+             contact: [\"email\"]->Array.some(WellKnown.FieldMask.covers(mask, _)) ? source.contact : target.contact,\n  }\n"
+        ));
+        assert!(files[0].contains(
+            "    tags: WellKnown.FieldMask.covers(mask, \"tags\") ? Array.concat(target.tags, source.tags) : target.tags,\n"
+        ));
+        assert!(files[0].contains("Some(Address.merge(target, source, ~mask))"));
+        assert!(files[0].contains("  let merge = (target: t, _source: t, ~mask as _: WellKnown.FieldMask.t): t => target\n"));
+        // The mask field itself is an ordinary well-known field
+        assert!(files[0].contains("? source.updateMask : target.updateMask"));
+        assert!(files[1].contains(
+            "  let applyMask: (t, t, ~mask: WellKnown.FieldMask.t) => t\n  let merge: (t, t, ~mask: WellKnown.FieldMask.t) => t\n"
+        ));
+    }
+
+    #[test]
+    fn test_groups_as_nested_messages() {
+        // message SearchResponse { repeated group Result = 1 { optional string url = 2; } }
+        let desc = DescriptorProto {
+            name: Some("SearchResponse".to_string()),
+            field: vec![repeated("result", 1, Type::Group, Some(".example.SearchResponse.Result"))],
+            nested_type: vec![DescriptorProto {
+                name: Some("Result".to_string()),
+                field: vec![field("url", 2, Type::String, None)],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let generator = Generator::new(Options::default());
+        let output = generator
+            .generate_message(
+                &desc,
+                &Options::default(),
+                "example",
+                &ModuleScope { types: &TypeIndex::default(), path: vec![] },
+                &SourceComments::default(),
+                &[],
+            )
+            .unwrap()
+            .implementation;
+        // `Result` would shadow the Result module used by fromJson
+        assert!(output.contains("  module Result_ = {\n"));
+        assert!(output.contains("    result: array<Result_.t>,\n"));
+        assert!(output.contains(
+            "Json.Encode.repeated(\"result\", msg.result, v => Result_.toJsonWith(v, ~unknownFields))"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_fields_carried() {
+        let desc = DescriptorProto {
+            name: Some("Order".to_string()),
+            field: vec![field("item", 1, Type::Message, Some(".example.Item"))],
+            ..Default::default()
+        };
+
+        let generator = Generator::new(Options::default());
+        let output = generator
+            .generate_message(
+                &desc,
+                &Options::default(),
+                "example",
+                &ModuleScope { types: &TypeIndex::default(), path: vec![] },
+                &SourceComments::default(),
+                &[],
+            )
+            .unwrap()
+            .implementation;
+        assert!(output.contains("    unknownFields?: UnknownFields.t,\n"));
+        assert!(output.contains("    ~unknownFields=?\n"));
+        assert!(output.contains("let unknownFields = UnknownFields.fromJsonObject(obj)"));
+        assert!(output.contains("unknownFields: ?unknownFields,"));
+
+        // Only the codec encoding carries "$unknown", down through nested messages
+        assert!(output.contains("  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {\n"));
+        assert!(output.contains("unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],"));
+        assert!(output.contains("Json.Encode.optional(\"item\", msg.item, v => Item.toJsonWith(v, ~unknownFields))"));
+        assert!(output.contains("  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)\n"));
+    }
+
+    #[test]
+    fn test_source_comments() {
+        use prost_types::source_code_info::Location;
+        use prost_types::{EnumValueDescriptorProto, MethodDescriptorProto, SourceCodeInfo};
+
+        let location = |path: Vec<i32>, leading: &str, trailing: Option<&str>| Location {
+            path,
+            leading_comments: Some(leading.to_string()),
+            trailing_comments: trailing.map(str::to_string),
+            ..Default::default()
+        };
+
+        let file = FileDescriptorProto {
+            name: Some("user.proto".to_string()),
+            package: Some("example".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("User".to_string()),
+                field: vec![field("name", 1, Type::String, None)],
+                ..Default::default()
+            }],
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Status".to_string()),
+                value: vec![EnumValueDescriptorProto {
+                    name: Some("ACTIVE".to_string()),
+                    number: Some(0),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            service: vec![ServiceDescriptorProto {
+                name: Some("UserService".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("GetUser".to_string()),
+                    input_type: Some(".example.User".to_string()),
+                    output_type: Some(".example.User".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            source_code_info: Some(SourceCodeInfo {
+                location: vec![
+                    location(vec![4, 0], " A registered user.\n", None),
+                    location(vec![4, 0, 2, 0], " Display name.\n", Some(" Never empty.\n")),
+                    location(vec![5, 0, 2, 0], " The user can sign in.\n", None),
+                    location(vec![6, 0], " Manages users.\n", None),
+                    location(vec![6, 0, 2, 0], " Look up a user by id.\n", None),
+                ],
+            }),
+            ..Default::default()
+        };
+
+        let generator = Generator::new(Options { grpc: true, ..Options::default() });
+        let output = generator.generate_file(&file, &TypeIndex::default()).unwrap()[0].content.clone().unwrap();
+        assert!(output.contains("/** A registered user. */\nmodule User = {"));
+        assert!(output.contains("    /**\n     * Display name.\n     *\n     * Never empty.\n     */\n    name: string,"));
+        assert!(output.contains("    /** The user can sign in. */\n    | #Active"));
+        assert!(output.contains("/** Manages users. */\nmodule UserServiceClient = {"));
+        assert!(output.contains("  /** Look up a user by id. */\n  let getUser"));
+    }
+
+    #[test]
+    fn test_deprecated_annotations() {
+        use prost_types::{FieldOptions, MessageOptions, MethodDescriptorProto, MethodOptions};
+
+        let message = DescriptorProto {
+            name: Some("User".to_string()),
+            field: vec![FieldDescriptorProto {
+                options: Some(FieldOptions { deprecated: Some(true), ..Default::default() }),
+                ..field("legacy_id", 1, Type::String, None)
+            }],
+            options: Some(MessageOptions { deprecated: Some(true), ..Default::default() }),
+            ..Default::default()
+        };
+
+        let mut types = TypeIndex::default();
+        types.paths.insert("example.User".to_string(), vec!["ExampleProto".to_string(), "User".to_string()]);
+        types.deprecated.insert("example.User".to_string());
+        let root = ModuleScope { types: &types, path: vec!["ExampleProto".to_string()] };
+
+        let generator = Generator::new(Options::default());
+        let output = generator
+            .generate_message(&message, &Options::default(), "example", &root, &SourceComments::default(), &[])
+            .unwrap()
+            .implementation;
+        // Only modules using deprecated definitions silence the warnings
+        assert!(output.starts_with("module User = {\n  @@warning(\"-3\")\n\n"));
+        assert!(output.contains(
+            "    @deprecated(\"legacy_id is deprecated in the proto schema\")\n    legacyId: string,"
+        ));
+        assert!(output.contains("  @deprecated(\"User is deprecated in the proto schema\")\n  type t = {"));
+        assert!(output.contains("  /** Deprecated labels: `~legacyId` */\n"));
+
+        let service = ServiceDescriptorProto {
+            name: Some("UserService".to_string()),
+            method: vec![MethodDescriptorProto {
+                name: Some("GetUser".to_string()),
+                input_type: Some(".example.User".to_string()),
+                output_type: Some(".example.User".to_string()),
+                options: Some(MethodOptions { deprecated: Some(true), ..Default::default() }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let output = generator
+            .generate_service(&service, "example", &root, &SourceComments::default(), &[])
+            .unwrap()
+            .implementation;
+        assert!(output.contains("module UserServiceClient = {\n  @@warning(\"-3\")\n"));
+        assert!(output.contains("module UserServiceServer = {\n  @@warning(\"-3\")\n"));
+        assert!(output.contains(
+            "  @deprecated(\"UserService.GetUser is deprecated in the proto schema\")\n  let getUser"
+        ));
+    }
+
+    #[test]
+    fn test_idempotent_methods() {
+        use prost_types::{MethodDescriptorProto, MethodOptions};
+
+        let method = |name: &str, level: Option<IdempotencyLevel>| MethodDescriptorProto {
+            name: Some(name.to_string()),
+            input_type: Some(".example.User".to_string()),
+            output_type: Some(".example.User".to_string()),
+            options: level.map(|level| MethodOptions {
+                idempotency_level: Some(level as i32),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let service = ServiceDescriptorProto {
+            name: Some("UserService".to_string()),
+            method: vec![
+                method("GetUser", Some(IdempotencyLevel::NoSideEffects)),
+                method("PutUser", Some(IdempotencyLevel::Idempotent)),
+                method("CreateUser", None),
+            ],
+            ..Default::default()
+        };
+        let output = Generator::new(Options::default())
+            .generate_service(
+                &service,
+                "example",
+                &ModuleScope { types: &TypeIndex::default(), path: vec![] },
+                &SourceComments::default(),
+                &[],
+            )
+            .unwrap()
+            .implementation;
+
+        assert!(output.contains("  let serviceName = \"example.UserService\"\n"));
+        assert!(output.contains(
+            "call(~config, ~method=\"UserService/GetUser\", ~request=requestJson, ~timeoutMs?, ~signal?, ~idempotent=true)"
+        ));
+        assert!(output.contains(
+            "call(~config, ~method=\"UserService/PutUser\", ~request=requestJson, ~timeoutMs?, ~signal?, ~idempotent=true)"
+        ));
+        assert!(output.contains("call(~config, ~method=\"UserService/CreateUser\", ~request=requestJson, ~timeoutMs?, ~signal?)"));
+    }
+
+    #[test]
+    fn test_server_error_trailers() {
+        use prost_types::MethodDescriptorProto;
+
+        let service = ServiceDescriptorProto {
+            name: Some("UserService".to_string()),
+            method: vec![MethodDescriptorProto {
+                name: Some("GetUser".to_string()),
+                input_type: Some(".example.User".to_string()),
+                output_type: Some(".example.User".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let output = Generator::new(Options::default())
+            .generate_service(
+                &service,
+                "example",
+                &ModuleScope { types: &TypeIndex::default(), path: vec![] },
+                &SourceComments::default(),
+                &[],
+            )
+            .unwrap();
+
+        // Details reach the response through grpc-status-details-bin
+        assert!(output.implementation.contains(
+            "  let errorHeaders = (error: serverError): array<(string, string)> =>\n    RpcStatus.toHeaders(errorStatus(error))\n"
+        ));
+        assert!(output.implementation.contains(
+            "    | Ok(body) => {headers: [(\"grpc-status\", \"0\")], body}\n    | Error(error) => {headers: errorHeaders(error), body: errorToJson(error)}\n"
+        ));
+        let interface = output.interface;
+        assert!(interface.contains("  let errorHeaders: serverError => array<(string, string)>\n"));
+        assert!(interface.contains("  ) => promise<response>\n"));
+    }
+
+    #[test]
+    fn test_interface_files() {
+        use prost_types::{MethodDescriptorProto, OneofDescriptorProto};
+
+        let file = FileDescriptorProto {
+            name: Some("user.proto".to_string()),
+            package: Some("example".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("User".to_string()),
+                field: vec![
+                    field("name", 1, Type::String, None),
+                    FieldDescriptorProto { oneof_index: Some(0), ..field("email", 2, Type::String, None) },
+                    FieldDescriptorProto { oneof_index: Some(0), ..field("phone", 3, Type::String, None) },
+                ],
+                oneof_decl: vec![OneofDescriptorProto {
+                    name: Some("contact".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            service: vec![ServiceDescriptorProto {
+                name: Some("UserService".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("WatchUsers".to_string()),
+                    input_type: Some(".example.User".to_string()),
+                    output_type: Some(".example.User".to_string()),
+                    server_streaming: Some(true),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let generator = Generator::new(Options { grpc: true, interface: true, ..Options::default() });
+        let files = generator.generate_file(&file, &TypeIndex::default()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].name.as_deref(), Some("UserProto.resi"));
+
+        let resi = files[1].content.as_deref().unwrap();
+        assert!(resi.contains("module User: {\n"));
+        assert!(resi.contains("  type contact =\n    | Email(string)\n    | Phone(string)\n"));
+        assert!(resi.contains(
+            "  let make: (\n    ~name: string,\n    ~contact: contact=?,\n    ~unknownFields: UnknownFields.t=?,\n  ) => t\n"
+        ));
+        assert!(resi.contains("  let fromJson: Js.Json.t => option<t>\n"));
+        assert!(resi.contains("module UserServiceClient: {\n"));
+        assert!(resi.contains(
+            "  let watchUsers: (\n    ~config: config=?,\n    ~request: User.t,\n    ~handler: streamHandler<User.t>,\n  ) => streamCancel\n"
+        ));
+        assert!(resi.contains("  let handleStreamingRequest: (\n"));
+        assert!(!resi.contains("let call"));
+        assert!(!resi.contains("callStream"));
+        assert!(!resi.contains("fetchUnary"));
+
+        // Without the option only the implementation is emitted
+        let generator = Generator::new(Options { grpc: true, ..Options::default() });
+        assert_eq!(generator.generate_file(&file, &TypeIndex::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_out_and_core_options() {
+        let file = FileDescriptorProto {
+            name: Some("user.proto".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("User".to_string()),
+                field: vec![field("avatar", 1, Type::Bytes, None)],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let generator = Generator::new(Options {
+            out_dir: Some("./src/generated/".to_string()),
+            use_core: true,
+            ..Options::default()
+        });
+        let files = generator.generate_file(&file, &TypeIndex::default()).unwrap();
+        assert_eq!(files[0].name.as_deref(), Some("src/generated/UserProto.res"));
+
+        let output = files[0].content.as_deref().unwrap();
+        assert!(output.contains("    avatar: Uint8Array.t,\n"));
+        assert!(output.contains("let toJson = (msg: t): JSON.t =>"));
+        assert!(!output.contains("Js."));
+
+        let generator = Generator::new(Options { out_dir: Some(".".to_string()), ..Options::default() });
+        let files = generator.generate_file(&file, &TypeIndex::default()).unwrap();
+        assert_eq!(files[0].name.as_deref(), Some("UserProto.res"));
+        assert!(files[0].content.as_deref().unwrap().contains("Js.Json.t"));
+    }
+
+    #[test]
+    fn test_output_layouts() {
+        let file = |name: &str, package: &str, message: &str| FileDescriptorProto {
+            name: Some(name.to_string()),
+            package: Some(package.to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some(message.to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["a/user.proto".to_string(), "b/user.proto".to_string()],
+            proto_file: vec![
+                file("a/user.proto", "acme.users", "User"),
+                file("b/user.proto", "acme.orders", "Order"),
+            ],
+            ..Default::default()
+        };
+        let generate = |layout: Layout| {
+            Generator::new(Options { layout, ..Options::default() }).generate(&request)
+        };
+        let names = |response: CodeGeneratorResponse| -> Vec<String> {
+            response.file.into_iter().filter_map(|f| f.name).collect()
+        };
+
+        let error = generate(Layout::Flat).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "a/user.proto and b/user.proto both generate module `UserProto`; \
+             use layout=mirror or layout=package to keep them apart"
+        );
+
+        let mirror = generate(Layout::Mirror).unwrap();
+        assert_eq!(names(mirror), ["a/AUserProto.res", "b/BUserProto.res"]);
+
+        let package = generate(Layout::Package).unwrap();
+        let content = package.file[0].content.clone().unwrap();
+        assert_eq!(names(package), ["Acme.res"]);
+        assert!(content.contains("// Package: acme.users, acme.orders\n"));
+        assert!(content.contains("module Users = {\n  module User = {\n"));
+        assert!(content.contains("module Orders = {\n  module Order = {\n"));
+
+        // Two files of one package defining the same ReScript module
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["a/user.proto".to_string(), "b/user.proto".to_string()],
+            proto_file: vec![file("a/user.proto", "acme", "User"), file("b/user.proto", "acme", "user")],
+            ..Default::default()
+        };
+        let error = Generator::new(Options { layout: Layout::Package, ..Options::default() })
+            .generate(&request)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "a/user.proto and b/user.proto both generate module `User` in package acme");
+    }
+
+    #[test]
+    fn test_nested_references() {
+        let message = |name: &str, field: Vec<FieldDescriptorProto>, nested_type: Vec<DescriptorProto>| {
+            DescriptorProto { name: Some(name.to_string()), field, nested_type, ..Default::default() }
+        };
+
+        // Sibling and Holder are declared before the types they use
+        let file = FileDescriptorProto {
+            name: Some("example.proto".to_string()),
+            package: Some("example".to_string()),
+            message_type: vec![
+                message(
+                    "Sibling",
+                    vec![
+                        field("inner", 1, Type::Message, Some(".example.Outer.Middle.Inner")),
+                        field("kind", 2, Type::Enum, Some(".example.Outer.Kind")),
+                    ],
+                    vec![],
+                ),
+                DescriptorProto {
+                    enum_type: vec![EnumDescriptorProto { name: Some("Kind".to_string()), ..Default::default() }],
+                    ..message(
+                        "Outer",
+                        vec![],
+                        vec![
+                            message("Holder", vec![field("middle", 1, Type::Message, Some(".example.Outer.Middle"))], vec![]),
+                            message("Middle", vec![], vec![message("Inner", vec![], vec![])]),
+                        ],
+                    )
+                },
+            ],
+            ..Default::default()
+        };
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["example.proto".to_string()],
+            proto_file: vec![file],
+            ..Default::default()
+        };
+        let response = Generator::new(Options::default()).generate(&request).unwrap();
+        let content = response.file[0].content.clone().unwrap();
+
+        let position = |needle: &str| content.find(needle).unwrap_or_else(|| panic!("missing {:?}", needle));
+        assert!(position("module Outer = {") < position("module Sibling = {"));
+        assert!(position("  module Middle = {") < position("  module Holder = {"));
+        assert!(content.contains("    inner: option<Outer.Middle.Inner.t>,\n    kind: Outer.Kind.t,\n"));
+        assert!(content.contains("      middle: option<Middle.t>,\n"));
+    }
+
+    #[test]
+    fn test_cross_file_references() {
+        let order = DescriptorProto {
+            name: Some("Order".to_string()),
+            field: vec![field("user", 1, Type::Message, Some(".acme.users.User"))],
+            ..Default::default()
+        };
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["b/order.proto".to_string()],
+            proto_file: vec![
+                FileDescriptorProto {
+                    name: Some("a/user.proto".to_string()),
+                    package: Some("acme.users".to_string()),
+                    message_type: vec![DescriptorProto { name: Some("User".to_string()), ..Default::default() }],
+                    ..Default::default()
+                },
+                FileDescriptorProto {
+                    name: Some("b/order.proto".to_string()),
+                    package: Some("acme.orders".to_string()),
+                    dependency: vec!["a/user.proto".to_string()],
+                    message_type: vec![order],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let generate = |layout: Layout| {
+            let response = Generator::new(Options { layout, ..Options::default() }).generate(&request).unwrap();
+            response.file[0].content.clone().unwrap()
+        };
+
+        assert!(generate(Layout::Flat).contains("    user: option<UserProto.User.t>,\n"));
+        assert!(generate(Layout::Mirror).contains("    user: option<AUserProto.User.t>,\n"));
+
+        // Sibling packages share the top-level module
+        let package = generate(Layout::Package);
+        assert!(package.contains("      user: option<Users.User.t>,\n"));
+        assert!(package.contains("Users.User.toJsonWith(v, ~unknownFields)"));
+    }
+
+    #[test]
+    fn test_custom_options() {
+        use crate::custom_options::{
+            FieldExtensions, FieldOptions, FileExtensions, FileOptions, MessageExtensions, MessageOptions,
+            RequestOptions, TypeMapping,
+        };
+
+        let field_options = |name: &str, options: FieldExtensions| FieldOptions {
+            name: Some(name.to_string()),
+            options: Some(options),
+        };
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["user.proto".to_string()],
+            proto_file: vec![FileDescriptorProto {
+                name: Some("user.proto".to_string()),
+                package: Some("example".to_string()),
+                message_type: vec![
+                    DescriptorProto {
+                        name: Some("Audit".to_string()),
+                        ..Default::default()
+                    },
+                    DescriptorProto {
+                        name: Some("Event".to_string()),
+                        field: vec![
+                            field("user_id", 1, Type::String, None),
+                            field("at", 2, Type::Int64, None),
+                            field("audit", 3, Type::Message, Some(".example.Audit")),
+                        ],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let options = RequestOptions {
+            proto_file: vec![FileOptions {
+                name: Some("user.proto".to_string()),
+                package: Some("example".to_string()),
+                message_type: vec![
+                    MessageOptions {
+                        name: Some("Audit".to_string()),
+                        field: vec![],
+                        nested_type: vec![],
+                        options: Some(MessageExtensions { type_name: None, skip_message: Some(true) }),
+                    },
+                    MessageOptions {
+                        name: Some("Event".to_string()),
+                        field: vec![
+                            field_options(
+                                "user_id",
+                                FieldExtensions { field_name: Some("uid".to_string()), r#type: None, skip: None },
+                            ),
+                            field_options(
+                                "at",
+                                FieldExtensions {
+                                    field_name: None,
+                                    r#type: Some(TypeMapping {
+                                        name: Some("Js.Date.t".to_string()),
+                                        encode: Some("DateCodec.toJson".to_string()),
+                                        decode: Some("DateCodec.fromJson".to_string()),
+                                    }),
+                                    skip: None,
+                                },
+                            ),
+                            field_options("audit", FieldExtensions { field_name: None, r#type: None, skip: Some(true) }),
+                        ],
+                        nested_type: vec![],
+                        options: Some(MessageExtensions { type_name: Some("UserEvent".to_string()), skip_message: None }),
+                    },
+                ],
+                options: Some(FileExtensions { module_name: Some("Users".to_string()) }),
+            }],
+        };
+
+        let response = Generator::new(Options::default())
+            .with_custom_options(CustomOptions::from_request(&options))
+            .generate(&request)
+            .unwrap();
+        assert_eq!(response.file[0].name.as_deref(), Some("Users.res"));
+        let output = response.file[0].content.as_deref().unwrap();
+        assert!(output.contains("module UserEvent = {"));
+        assert!(!output.contains("module Audit"));
+        assert!(output.contains("    uid: string,\n"));
+        assert!(output.contains("    at: Js.Date.t,\n"));
+        assert!(!output.contains("audit"));
+        assert!(output.contains("DateCodec.toJson"));
+        assert!(output.contains("Json.Decode.required(obj, \"at\", DateCodec.fromJson)"));
+
+        // A field using a skipped message must itself be skipped or mapped
+        let mut options = options;
+        options.proto_file[0].message_type[1].field.pop();
+        let err = Generator::new(Options::default())
+            .with_custom_options(CustomOptions::from_request(&options))
+            .generate(&request)
+            .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "user.proto: field `example.Event.audit`: uses message `example.Audit` marked (rescript.skip_message); \
+             skip the field or map it with (rescript.type)"
+        );
+
+        // Overrides are checked like generated names
+        let mut options = RequestOptions { proto_file: vec![FileOptions { options: None, ..options.proto_file[0].clone() }] };
+        options.proto_file[0].message_type[1].field[0] =
+            field_options("user_id", FieldExtensions { field_name: Some("type".to_string()), r#type: None, skip: None });
+        options.proto_file[0].message_type[1].field.push(field_options(
+            "audit",
+            FieldExtensions { field_name: None, r#type: None, skip: Some(true) },
+        ));
+        let err = Generator::new(Options::default())
+            .with_custom_options(CustomOptions::from_request(&options))
+            .generate(&request)
+            .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "user.proto: field `example.Event.user_id`: cannot generate a ReScript identifier (got `type`)"
+        );
+    }
+
     #[test]
     fn test_to_rescript_variant() {
         let generator = Generator::new(Options::default());
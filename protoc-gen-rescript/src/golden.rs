@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Golden-file tests: checked-in `CodeGeneratorRequest`s run through the
+//! plugin and compared with the expected `.res` files
+//!
+//! Requests live in `tests/golden/requests` (see `tests/golden/README.adoc`).
+//! After an intentional output change, rewrite the expected files with
+//! `UPDATE_GOLDEN=1 cargo test golden` and review the diff.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use prost::Message;
+use prost_types::compiler::CodeGeneratorRequest;
+
+use crate::custom_options::CustomOptions;
+use crate::run;
+
+/// Run `tests/golden/requests/<request>.pb` with `parameter`, returning the
+/// generated files by name
+fn generate(request: &str, parameter: &str) -> BTreeMap<String, String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let bytes = fs::read(root.join("tests/golden/requests").join(format!("{}.pb", request)))
+        .unwrap_or_else(|err| panic!("{}: cannot read request: {}", request, err));
+
+    let mut decoded = CodeGeneratorRequest::decode(bytes.as_slice()).unwrap();
+    decoded.parameter = Some(parameter.to_string());
+    let response = run(&decoded, CustomOptions::decode(&bytes));
+    if let Some(error) = response.error {
+        panic!("{}: plugin error: {}", request, error);
+    }
+
+    response
+        .file
+        .into_iter()
+        .map(|f| (f.name.unwrap_or_default(), f.content.unwrap_or_default()))
+        .collect()
+}
+
+/// Run `tests/golden/requests/<request>.pb` with `parameter` and compare the
+/// output with the files in `expected` (relative to the crate root)
+fn check(request: &str, parameter: &str, expected: &str) {
+    let actual = generate(request, parameter);
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(expected);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        update(&dir, &actual);
+        return;
+    }
+
+    let mut problems = Vec::new();
+    for (name, content) in &actual {
+        match fs::read_to_string(dir.join(name)) {
+            Ok(golden) if golden == *content => {}
+            Ok(golden) => problems.push(format!("{}: {}", name, first_difference(&golden, content))),
+            Err(_) => problems.push(format!("{}: not in {}", name, expected)),
+        }
+    }
+    for name in expected_files(&dir) {
+        if !actual.contains_key(&name) {
+            problems.push(format!("{}: no longer generated", name));
+        }
+    }
+
+    assert!(
+        problems.is_empty(),
+        "{} differs from {} (rerun with UPDATE_GOLDEN=1 if intended):\n  {}",
+        request,
+        expected,
+        problems.join("\n  ")
+    );
+}
+
+/// Replace the contents of `dir` with the generated files
+fn update(dir: &Path, actual: &BTreeMap<String, String>) {
+    for name in expected_files(dir) {
+        if !actual.contains_key(&name) {
+            fs::remove_file(dir.join(&name)).unwrap();
+        }
+    }
+    for (name, content) in actual {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
+
+/// Generated-file names (relative paths) currently in `dir`
+fn expected_files(dir: &Path) -> Vec<String> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, files);
+            } else if matches!(path.extension().and_then(|e| e.to_str()), Some("res" | "resi")) {
+                files.push(path);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(dir, &mut files);
+    files
+        .iter()
+        .map(|p| p.strip_prefix(dir).unwrap().to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+fn first_difference(golden: &str, actual: &str) -> String {
+    let mut golden_lines = golden.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (golden_lines.next(), actual_lines.next()) {
+            (Some(g), Some(a)) if g == a => continue,
+            (None, None) => break,
+            (g, a) => {
+                return format!(
+                    "line {}: expected {:?}, got {:?}",
+                    line,
+                    g.unwrap_or("<end of file>"),
+                    a.unwrap_or("<end of file>")
+                )
+            }
+        }
+    }
+    "trailing whitespace differs".to_string()
+}
+
+#[test]
+fn golden_basic_example() {
+    check("basic", "grpc", "../examples/basic/expected");
+}
+
+#[test]
+fn golden_maps() {
+    check("maps", "", "tests/golden/expected/maps");
+}
+
+#[test]
+fn golden_oneofs() {
+    check("oneofs", "resi", "tests/golden/expected/oneofs");
+}
+
+#[test]
+fn golden_nested() {
+    check("nested", "", "tests/golden/expected/nested");
+}
+
+#[test]
+fn golden_recursion() {
+    check("recursion", "", "tests/golden/expected/recursion");
+}
+
+#[test]
+fn golden_proto2() {
+    check("proto2", "", "tests/golden/expected/proto2");
+}
+
+#[test]
+fn golden_wasm() {
+    check("oneofs", "resi,wasm", "tests/golden/expected/oneofs-wasm");
+    check("proto2", "wasm", "tests/golden/expected/proto2-wasm");
+    check("recursion", "wasm", "tests/golden/expected/recursion-wasm");
+    // Compiled and run against a stubbed codec by the runtime tests
+    check("oneofs", "wasm", "../runtime/tests/generated/oneofs");
+    check("basic", "wasm", "../runtime/tests/generated/basic");
+}
+
+#[test]
+fn golden_services() {
+    check("services", "grpc,resi", "tests/golden/expected/services");
+}
+
+#[test]
+fn golden_package_layout() {
+    check("nested", "layout=package,core", "tests/golden/expected/nested-package");
+}
+
+#[test]
+fn core_bindings_cover_generated_code() {
+    // Every option that adds code, so each template goes through the rewrite
+    let parameter = "grpc,resi,wasm,any_types,field_masks,core";
+    for request in ["basic", "services", "maps", "oneofs", "proto2"] {
+        for (name, content) in generate(request, parameter) {
+            let leftover: Vec<&str> = content.lines().filter(|line| line.contains("Js.")).collect();
+            assert!(leftover.is_empty(), "{}: {} keeps Js.* bindings:\n  {}", request, name, leftover.join("\n  "));
+        }
+    }
+}
+
+#[test]
+fn reserved_modules_cover_generated_code() {
+    // Every module generated code reaches for by name must be reserved, so a
+    // proto file or message can never shadow it
+    let parameter = "grpc,resi,wasm,any_types,field_masks";
+    for request in ["basic", "services", "maps", "oneofs", "proto2", "nested", "recursion"] {
+        for parameter in [parameter.to_string(), format!("{},core", parameter)] {
+            let files = generate(request, &parameter);
+            // File modules and modules named after proto declarations
+            let mut local: Vec<String> = files
+                .keys()
+                .map(|name| name.rsplit('/').next().unwrap().split('.').next().unwrap().to_string())
+                .collect();
+            for content in files.values() {
+                for line in content.lines() {
+                    if let Some(rest) = line.trim_start().strip_prefix("module ") {
+                        let rest = rest.strip_prefix("rec ").unwrap_or(rest);
+                        local.push(rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect());
+                    }
+                }
+            }
+            local.extend(declared_names(request));
+            for (name, content) in &files {
+                for module in qualified_modules(content) {
+                    assert!(
+                        local.contains(&module) || crate::naming::RESERVED_MODULES.contains(&module.as_str()),
+                        "{} ({}): {} uses `{}`, which is missing from RESERVED_MODULES",
+                        request,
+                        parameter,
+                        name,
+                        module
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Names of every message and enum declared in a request, nested ones included
+fn declared_names(request: &str) -> Vec<String> {
+    fn walk(messages: &[prost_types::DescriptorProto], out: &mut Vec<String>) {
+        for msg in messages {
+            out.push(msg.name().to_string());
+            out.extend(msg.enum_type.iter().map(|e| e.name().to_string()));
+            walk(&msg.nested_type, out);
+        }
+    }
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let bytes = fs::read(root.join("tests/golden/requests").join(format!("{}.pb", request))).unwrap();
+    let mut out = Vec::new();
+    for file in CodeGeneratorRequest::decode(bytes.as_slice()).unwrap().proto_file {
+        out.extend(file.enum_type.iter().map(|e| e.name().to_string()));
+        walk(&file.message_type, &mut out);
+    }
+    out
+}
+
+/// Modules at the start of a qualified path (`Json.Decode.int` -> `Json`),
+/// outside comments and string literals
+fn qualified_modules(content: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    for line in content.lines() {
+        let code = line.split("//").next().unwrap();
+        let chars: Vec<char> = code.chars().collect();
+        let mut quote = None;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) if c == '\\' => i += 1,
+                Some(_) => {}
+                None if c == '"' || c == '`' => quote = Some(c),
+                None if c.is_ascii_uppercase() && (i == 0 || !(chars[i - 1].is_alphanumeric() || "_.'".contains(chars[i - 1]))) => {
+                    let end = chars[i..].iter().position(|c| !(c.is_alphanumeric() || *c == '_')).map_or(chars.len(), |n| i + n);
+                    if chars.get(end) == Some(&'.') {
+                        modules.push(chars[i..end].iter().collect());
+                    }
+                    i = end;
+                    continue;
+                }
+                None => {}
+            }
+            i += 1;
+        }
+    }
+    modules
+}
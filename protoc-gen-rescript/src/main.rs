@@ -7,45 +7,208 @@
 
 use std::io::{self, Read, Write};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use bytes::Bytes;
 use prost::Message;
+use prost_types::compiler::{CodeGeneratorRequest, CodeGeneratorResponse};
 
+mod custom_options;
 mod generator;
+#[cfg(test)]
+mod golden;
+mod naming;
 mod templates;
 
+use custom_options::CustomOptions;
 use generator::Generator;
 
 /// Plugin options parsed from --rescript_opt=...
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Options {
     /// Generate WASM codec bindings (default: false, types only)
     pub wasm: bool,
-    /// Output directory for generated .res files
+    /// Path prefix for generated file names, relative to the protoc output directory
     pub out_dir: Option<String>,
     /// Generate gRPC service stubs
     pub grpc: bool,
     /// Use @rescript/core instead of Js.* bindings
     pub use_core: bool,
+    /// Also emit a .resi interface for each generated .res file
+    pub interface: bool,
+    /// How generated files map onto proto files and packages
+    pub layout: Layout,
+    /// Naming style of record fields and labels
+    pub field_style: FieldStyle,
+    /// Suffix of per-file module names (`user.proto` -> `UserProto`)
+    pub module_suffix: String,
+    /// Emit `anyType` codecs and a per-file `AnyTypes` registry for google.protobuf.Any
+    pub any_types: bool,
+    /// Emit `applyMask`/`merge` helpers driven by google.protobuf.FieldMask
+    pub field_masks: bool,
+    /// Map Timestamp and Duration to exact `{seconds, nanos}` records
+    pub precise_time: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            wasm: false,
+            out_dir: None,
+            grpc: false,
+            use_core: false,
+            interface: false,
+            layout: Layout::default(),
+            field_style: FieldStyle::default(),
+            module_suffix: "Proto".to_string(),
+            any_types: false,
+            field_masks: false,
+            precise_time: false,
+        }
+    }
+}
+
+/// Field naming style selected with `field_names=`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FieldStyle {
+    /// `user_id` -> `userId`
+    #[default]
+    Camel,
+    /// Keep the proto name (`user_id`)
+    Snake,
 }
 
+/// Output layout selected with `layout=`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// One `UserProto.res` per proto file, ignoring directories
+    #[default]
+    Flat,
+    /// Mirror the proto directory structure (`a/user.proto` -> `a/AUserProto.res`)
+    Mirror,
+    /// One file per root package, with package segments as nested modules
+    Package,
+}
+
+/// Flag options (no value)
+const FLAG_OPTIONS: &[&str] = &["wasm", "grpc", "core", "resi", "any_types", "field_masks", "precise_time"];
+/// Options that take a value (`name=value`)
+const VALUE_OPTIONS: &[&str] = &["out", "layout", "field_names", "module_suffix"];
+
 impl Options {
-    fn parse(parameter: &str) -> Self {
+    fn parse(parameter: &str) -> Result<Self> {
         let mut opts = Options::default();
         for part in parameter.split(',') {
             let part = part.trim();
-            match part {
-                "wasm" => opts.wasm = true,
-                "grpc" => opts.grpc = true,
-                "core" => opts.use_core = true,
-                _ if part.starts_with("out=") => {
-                    opts.out_dir = Some(part[4..].to_string());
+            if part.is_empty() {
+                continue;
+            }
+
+            let (name, value) = match part.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (part, None),
+            };
+
+            match (name, value) {
+                ("wasm", None) => opts.wasm = true,
+                ("grpc", None) => opts.grpc = true,
+                ("core", None) => opts.use_core = true,
+                ("resi", None) => opts.interface = true,
+                ("any_types", None) => opts.any_types = true,
+                ("field_masks", None) => opts.field_masks = true,
+                ("precise_time", None) => opts.precise_time = true,
+                ("out", Some(dir)) if !dir.is_empty() => opts.out_dir = Some(dir.to_string()),
+                ("out", _) => bail!("option `out` requires a directory, e.g. `out=generated`"),
+                ("layout", Some("flat")) => opts.layout = Layout::Flat,
+                ("layout", Some("mirror")) => opts.layout = Layout::Mirror,
+                ("layout", Some("package")) => opts.layout = Layout::Package,
+                ("layout", value) => bail!(
+                    "option `layout` must be one of flat, mirror, package (got `{}`)",
+                    value.unwrap_or("")
+                ),
+                ("field_names", Some("camel")) => opts.field_style = FieldStyle::Camel,
+                ("field_names", Some("snake")) => opts.field_style = FieldStyle::Snake,
+                ("field_names", value) => bail!(
+                    "option `field_names` must be camel or snake (got `{}`)",
+                    value.unwrap_or("")
+                ),
+                ("module_suffix", Some("none")) => opts.module_suffix = String::new(),
+                ("module_suffix", Some(suffix))
+                    if suffix.starts_with(|c: char| c.is_ascii_uppercase())
+                        && suffix.chars().all(|c| c.is_ascii_alphanumeric()) =>
+                {
+                    opts.module_suffix = suffix.to_string()
                 }
-                _ => {} // Ignore unknown options
+                ("module_suffix", value) => bail!(
+                    "option `module_suffix` must be `none` or a capitalized name like `Proto` (got `{}`)",
+                    value.unwrap_or("")
+                ),
+                (flag, Some(_)) if FLAG_OPTIONS.contains(&flag) => {
+                    bail!("option `{}` does not take a value", flag)
+                }
+                (unknown, _) => match suggest_option(unknown) {
+                    Some(suggestion) => bail!("unknown option `{}` (did you mean `{}`?)", unknown, suggestion),
+                    None => bail!(
+                        "unknown option `{}` (supported: {}, out=<dir>, layout=flat|mirror|package, \
+                         field_names=camel|snake, module_suffix=<Name>|none)",
+                        unknown,
+                        FLAG_OPTIONS.join(", ")
+                    ),
+                },
+            }
+        }
+        Ok(opts)
+    }
+}
+
+/// Closest known option name to a misspelled one, if any is close enough
+fn suggest_option(name: &str) -> Option<&'static str> {
+    FLAG_OPTIONS
+        .iter()
+        .chain(VALUE_OPTIONS)
+        .map(|option| (edit_distance(name, option), *option))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| option)
+}
+
+/// Levenshtein distance, treating an adjacent transposition as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
             }
         }
-        opts
     }
+
+    d[a.len()][b.len()]
+}
+
+/// Run the plugin on a request
+///
+/// Invalid options, malformed `(rescript.*)` options and generation failures
+/// are reported through `CodeGeneratorResponse.error` so protoc prints them cleanly.
+fn run(request: &CodeGeneratorRequest, custom: Result<CustomOptions>) -> CodeGeneratorResponse {
+    let result = Options::parse(request.parameter.as_deref().unwrap_or(""))
+        .context("invalid --rescript_opt")
+        .and_then(|options| Generator::new(options).with_custom_options(custom?).generate(request));
+
+    result.unwrap_or_else(|err| CodeGeneratorResponse {
+        error: Some(format!("{:#}", err)),
+        ..Default::default()
+    })
 }
 
 fn main() -> Result<()> {
@@ -55,15 +218,12 @@ fn main() -> Result<()> {
         .read_to_end(&mut input)
         .context("Failed to read from stdin")?;
 
-    let request = prost_types::compiler::CodeGeneratorRequest::decode(Bytes::from(input))
+    let custom = CustomOptions::decode(&input);
+    let request = CodeGeneratorRequest::decode(Bytes::from(input))
         .context("Failed to parse CodeGeneratorRequest")?;
 
-    // Parse options
-    let options = Options::parse(request.parameter.as_deref().unwrap_or(""));
-
-    // Generate code
-    let generator = Generator::new(options);
-    let response = generator.generate(&request)?;
+    // Generate code (errors are returned in the response)
+    let response = run(&request, custom);
 
     // Write CodeGeneratorResponse to stdout
     let mut output = Vec::new();
@@ -84,17 +244,131 @@ mod tests {
 
     #[test]
     fn test_parse_options() {
-        let opts = Options::parse("wasm,grpc,out=./gen");
+        let opts = Options::parse("wasm,grpc,out=./gen").unwrap();
         assert!(opts.wasm);
         assert!(opts.grpc);
         assert_eq!(opts.out_dir, Some("./gen".to_string()));
     }
 
+    #[test]
+    fn test_parse_output_options() {
+        let opts = Options::parse("resi,core,layout=package").unwrap();
+        assert_eq!(opts.layout, Layout::Package);
+        assert!(opts.use_core);
+        assert!(opts.interface);
+        assert_eq!(Options::parse("layout=mirror").unwrap().layout, Layout::Mirror);
+        assert_eq!(Options::parse("").unwrap().layout, Layout::Flat);
+    }
+
+    #[test]
+    fn test_parse_naming_options() {
+        let opts = Options::parse("field_names=snake,module_suffix=none").unwrap();
+        assert_eq!(opts.field_style, FieldStyle::Snake);
+        assert_eq!(opts.module_suffix, "");
+
+        let opts = Options::parse("module_suffix=Pb").unwrap();
+        assert_eq!(opts.field_style, FieldStyle::Camel);
+        assert_eq!(opts.module_suffix, "Pb");
+        assert_eq!(Options::parse("").unwrap().module_suffix, "Proto");
+    }
+
+    #[test]
+    fn test_parse_type_options() {
+        let opts = Options::parse("any_types,field_masks,precise_time").unwrap();
+        assert!(opts.any_types);
+        assert!(opts.field_masks);
+        assert!(opts.precise_time);
+        let opts = Options::parse("").unwrap();
+        assert!(!opts.any_types && !opts.field_masks && !opts.precise_time);
+    }
+
     #[test]
     fn test_parse_empty_options() {
-        let opts = Options::parse("");
+        let opts = Options::parse("").unwrap();
         assert!(!opts.wasm);
         assert!(!opts.grpc);
         assert!(opts.out_dir.is_none());
     }
+
+    #[test]
+    fn test_parse_invalid_options() {
+        let error = |parameter: &str| Options::parse(parameter).unwrap_err().to_string();
+
+        assert_eq!(error("wasm,grcp"), "unknown option `grcp` (did you mean `grpc`?)");
+        assert_eq!(error("ot=gen"), "unknown option `ot` (did you mean `out`?)");
+        assert_eq!(
+            error("typescript"),
+            "unknown option `typescript` (supported: wasm, grpc, core, resi, any_types, field_masks, precise_time, out=<dir>, layout=flat|mirror|package, \
+             field_names=camel|snake, module_suffix=<Name>|none)"
+        );
+        assert_eq!(error("field_names=kebab"), "option `field_names` must be camel or snake (got `kebab`)");
+        assert_eq!(
+            error("module_suffix=_pb"),
+            "option `module_suffix` must be `none` or a capitalized name like `Proto` (got `_pb`)"
+        );
+        assert_eq!(error("layout=tree"), "option `layout` must be one of flat, mirror, package (got `tree`)");
+        assert_eq!(error("grpc=true"), "option `grpc` does not take a value");
+        assert_eq!(error("out="), "option `out` requires a directory, e.g. `out=generated`");
+        assert_eq!(error("out"), "option `out` requires a directory, e.g. `out=generated`");
+    }
+
+    #[test]
+    fn test_errors_reported_in_response() {
+        use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+
+        let response = run(
+            &CodeGeneratorRequest {
+                parameter: Some("grcp".to_string()),
+                ..Default::default()
+            },
+            Ok(CustomOptions::default()),
+        );
+        assert_eq!(
+            response.error.as_deref(),
+            Some("invalid --rescript_opt: unknown option `grcp` (did you mean `grpc`?)")
+        );
+        assert!(response.file.is_empty());
+
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["user.proto".to_string()],
+            proto_file: vec![FileDescriptorProto {
+                name: Some("user.proto".to_string()),
+                package: Some("example".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("User".to_string()),
+                    field: vec![FieldDescriptorProto {
+                        name: Some("user-name".to_string()),
+                        number: Some(1),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let response = run(&request, Ok(CustomOptions::default()));
+        assert_eq!(
+            response.error.as_deref(),
+            Some("user.proto: field `example.User.user-name`: cannot generate a ReScript identifier (got `user-name`)")
+        );
+
+        // (rescript.module_name) written as a varint: protoc's descriptors skip the
+        // unknown field, the custom options decoder rejects it
+        let mut file_options = Vec::new();
+        prost::encoding::encode_key(51201, prost::encoding::WireType::Varint, &mut file_options);
+        prost::encoding::encode_varint(1, &mut file_options);
+        let mut file = Vec::new();
+        prost::encoding::string::encode(1, &"user.proto".to_string(), &mut file);
+        prost::encoding::bytes::encode(8, &file_options, &mut file);
+        let mut input = Vec::new();
+        prost::encoding::bytes::encode(15, &file, &mut input);
+
+        let request = CodeGeneratorRequest::decode(input.as_slice()).unwrap();
+        let response = run(&request, CustomOptions::decode(&input));
+        let error = response.error.unwrap();
+        assert!(error.starts_with("Failed to parse (rescript.*) options: "), "{}", error);
+        assert!(response.file.is_empty());
+    }
 }
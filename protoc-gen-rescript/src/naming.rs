@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Mapping proto names onto valid, non-colliding ReScript identifiers
+
+use crate::FieldStyle;
+
+/// ReScript keywords (including ones reserved for future use)
+pub const KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "catch", "constraint", "downto", "else", "exception", "external",
+    "false", "for", "if", "in", "include", "lazy", "let", "module", "mutable", "of", "open", "or", "private",
+    "rec", "switch", "to", "true", "try", "type", "unpack", "when", "while", "with",
+];
+
+/// Modules referenced by generated code; proto types and files must not shadow them
+///
+/// `reserved_modules_cover_generated_code` (golden.rs) fails when generated
+/// code starts using a module missing here.
+pub const RESERVED_MODULES: &[&str] = &[
+    // runtime/src
+    "Deadline", "Extension", "Fetch", "Interceptor", "Json", "Retry", "RpcStatus", "Streaming", "UnknownFields",
+    "WasmCodec", "WellKnown",
+    // Stdlib and @rescript/core
+    "Array", "Console", "Date", "Dict", "Exn", "Int", "JSON", "Js", "Null", "Nullable", "Obj", "Option", "Promise",
+    "Result", "String", "Uint8Array",
+    // Generated once per file
+    "AnyTypes", "Extensions",
+];
+
+/// Names bound by generated message code (decode temporaries and extra record fields)
+pub const RESERVED_FIELDS: &[&str] = &["extensions", "json", "msg", "obj", "unknownFields"];
+
+/// Type names generated inside every message module
+pub const RESERVED_TYPES: &[&str] = &["t"];
+
+/// Helpers generated inside every service client module
+pub const RESERVED_CLIENT_VALUES: &[&str] = &["call", "callStream", "defaultConfig", "fetchStream", "fetchUnary", "requestHeaders", "retryStatus", "serviceName"];
+
+/// Append `_` if `ident` is a keyword or one of the `reserved` names
+pub fn escape(ident: String, reserved: &[&str]) -> String {
+    if KEYWORDS.contains(&ident.as_str()) || reserved.contains(&ident.as_str()) {
+        format!("{}_", ident)
+    } else {
+        ident
+    }
+}
+
+/// Value-level name (record field, label, function) for a proto name
+///
+/// camelCase joins words on `_`; extra underscores in a run are kept so that
+/// `foo__bar` (`foo_Bar`) never collides with `foo_bar` (`fooBar`).
+pub fn value_name(name: &str, style: FieldStyle, reserved: &[&str]) -> String {
+    let joined = match style {
+        FieldStyle::Camel => camel_case(name),
+        FieldStyle::Snake => name.to_string(),
+    };
+
+    let mut ident = lower_leading_capitals(&joined);
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    escape(ident, reserved)
+}
+
+/// Module or type name (PascalCase) for a proto name
+pub fn type_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize_next = true;
+
+    for c in name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            result.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    module_name(result)
+}
+
+/// Append `_` to a module name that would shadow one of the `RESERVED_MODULES`
+pub fn module_name(name: String) -> String {
+    if RESERVED_MODULES.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Polymorphic variant name for an enum value (`SOME_VALUE` -> `SomeValue`)
+pub fn variant_name(name: &str) -> String {
+    // Only SCREAMING_SNAKE_CASE values are re-cased; others keep their letters
+    let screaming = name.chars().all(|c| c.is_uppercase() || c == '_' || c.is_numeric());
+    let mut result = String::new();
+    let mut capitalize_next = true;
+
+    for c in name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            result.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else if screaming {
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Variant constructor for a oneof member field name
+pub fn constructor_name(field_name: &str) -> String {
+    let trimmed = field_name.trim_start_matches('_');
+    let mut chars = trimmed.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_digit() => format!("N{}", trimmed),
+        Some(c) => c.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Join `_`-separated words in camelCase, dropping leading underscores
+fn camel_case(name: &str) -> String {
+    let mut result = String::new();
+    let mut underscores = 0;
+
+    for c in name.chars() {
+        if c == '_' {
+            underscores += 1;
+            continue;
+        }
+        if result.is_empty() {
+            result.push(c);
+        } else if underscores > 0 {
+            result.extend(std::iter::repeat_n('_', underscores - 1));
+            result.push(c.to_ascii_uppercase());
+        } else {
+            result.push(c);
+        }
+        underscores = 0;
+    }
+    result.extend(std::iter::repeat_n('_', underscores));
+
+    result
+}
+
+/// Lowercase a leading run of capitals, keeping the start of the next word
+///
+/// `ID` -> `id`, `URLPath` -> `urlPath`, `Name` -> `name`
+fn lower_leading_capitals(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let run = chars.iter().take_while(|c| c.is_ascii_uppercase()).count();
+    let lower = if run > 1 && chars.get(run).is_some_and(|c| c.is_ascii_lowercase()) {
+        run - 1
+    } else {
+        run
+    };
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| if i < lower { c.to_ascii_lowercase() } else { *c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_name_camel() {
+        let name = |n: &str| value_name(n, FieldStyle::Camel, RESERVED_FIELDS);
+        assert_eq!(name("user_id"), "userId");
+        assert_eq!(name("foo__bar"), "foo_Bar");
+        assert_eq!(name("_private"), "private_");
+        assert_eq!(name("trailing_"), "trailing_");
+        assert_eq!(name("ID"), "id");
+        assert_eq!(name("URLPath"), "urlPath");
+        assert_eq!(name("Name"), "name");
+        assert_eq!(name("_2fa_code"), "_2faCode");
+        assert_eq!(name("__"), "__");
+    }
+
+    #[test]
+    fn test_value_name_escapes() {
+        let name = |n: &str| value_name(n, FieldStyle::Camel, RESERVED_FIELDS);
+        for keyword in ["await", "async", "of", "with", "constraint", "type", "when"] {
+            assert_eq!(name(keyword), format!("{}_", keyword));
+        }
+        assert_eq!(name("unknown_fields"), "unknownFields_");
+        assert_eq!(name("obj"), "obj_");
+        assert_eq!(value_name("call", FieldStyle::Camel, RESERVED_CLIENT_VALUES), "call_");
+        assert_eq!(value_name("make", FieldStyle::Camel, RESERVED_TYPES), "make");
+    }
+
+    #[test]
+    fn test_value_name_snake() {
+        let name = |n: &str| value_name(n, FieldStyle::Snake, RESERVED_FIELDS);
+        assert_eq!(name("user_id"), "user_id");
+        assert_eq!(name("Display_Name"), "display_Name");
+        assert_eq!(name("of"), "of_");
+    }
+
+    #[test]
+    fn test_type_and_variant_names() {
+        assert_eq!(type_name("user_profile"), "UserProfile");
+        assert_eq!(type_name("Result"), "Result_");
+        assert_eq!(type_name("json"), "Json_");
+        assert_eq!(type_name("RpcStatus"), "RpcStatus_");
+        assert_eq!(type_name("Retry"), "Retry_");
+        assert_eq!(type_name("Deadline"), "Deadline_");
+        assert_eq!(type_name("Interceptor"), "Interceptor_");
+        assert_eq!(type_name("AnyTypes"), "AnyTypes_");
+        assert_eq!(type_name("Extensions"), "Extensions_");
+        assert_eq!(variant_name("STATUS_ACTIVE"), "StatusActive");
+        assert_eq!(variant_name("Active"), "Active");
+        assert_eq!(constructor_name("email"), "Email");
+        assert_eq!(constructor_name("_2fa"), "N2fa");
+    }
+}
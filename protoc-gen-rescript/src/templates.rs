@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 //! ReScript code templates for generated output
 
+use crate::naming;
+use crate::FieldStyle;
+
 /// Information about a message field
 #[derive(Debug, Clone)]
 pub struct FieldInfo {
@@ -15,17 +18,30 @@ pub struct FieldInfo {
     pub oneof_index: Option<i32>,
     /// Well-known type name if applicable (e.g., ".google.protobuf.Timestamp")
     pub well_known_type: Option<String>,
+    /// `(rescript.type)` JSON encoder and decoder replacing the built-in ones
+    pub custom_codec: Option<(String, String)>,
+    /// Comment from the proto source, rendered as a doc comment
+    pub doc: Option<String>,
+    /// `[deprecated = true]` in the proto
+    pub deprecated: bool,
+    /// The referenced message or enum is `[deprecated = true]`
+    pub deprecated_type: bool,
 }
 
 /// Information about a oneof field group
 #[derive(Debug, Clone)]
 pub struct OneOfInfo {
     pub name: String,
-    pub rescript_name: String,
     pub fields: Vec<FieldInfo>,
+    pub doc: Option<String>,
 }
 
 impl FieldInfo {
+    /// Whether the codecs touch something deprecated through this field
+    fn uses_deprecated(&self) -> bool {
+        self.deprecated || self.deprecated_type
+    }
+
     /// Get the full ReScript type including option/array wrappers
     pub fn full_type(&self) -> String {
         let base = &self.rescript_type;
@@ -40,6 +56,10 @@ impl FieldInfo {
 
     /// Get the JSON encoder for this field's base type
     pub fn json_encoder(&self) -> String {
+        if let Some((encode, _)) = &self.custom_codec {
+            return encode.clone();
+        }
+
         // Check for well-known types first
         if let Some(ref wkt) = self.well_known_type {
             return self.wkt_json_encoder(wkt);
@@ -66,6 +86,10 @@ impl FieldInfo {
 
     /// Get the JSON decoder for this field's base type
     pub fn json_decoder(&self) -> String {
+        if let Some((_, decode)) = &self.custom_codec {
+            return decode.clone();
+        }
+
         // Check for well-known types first
         if let Some(ref wkt) = self.well_known_type {
             return self.wkt_json_decoder(wkt);
@@ -89,9 +113,18 @@ impl FieldInfo {
         }
     }
 
+    /// Timestamp or Duration mapped to a `WellKnown.Precise*` record
+    fn is_precise_time(&self) -> bool {
+        self.rescript_type.starts_with("WellKnown.Precise")
+    }
+
     /// Get JSON encoder for well-known types
     fn wkt_json_encoder(&self, wkt: &str) -> String {
         match wkt {
+            // Exact seconds/nanos records (`precise_time`)
+            ".google.protobuf.Timestamp" | ".google.protobuf.Duration" if self.is_precise_time() => {
+                format!("{}.toJson", self.rescript_type.trim_end_matches(".t"))
+            }
             // Timestamp -> RFC3339 string
             ".google.protobuf.Timestamp" => "WellKnown.Timestamp.toJson".to_string(),
             // Duration -> "Xs" format
@@ -118,6 +151,8 @@ impl FieldInfo {
             ".google.protobuf.NullValue" => "_ => Js.Json.null".to_string(),
             // Any -> special encoding
             ".google.protobuf.Any" => "WellKnown.Any.toJson".to_string(),
+            // FieldMask -> "a.b,c" with camelCase paths
+            ".google.protobuf.FieldMask" => "WellKnown.FieldMask.toJson".to_string(),
             _ => "Json.Encode.string".to_string(),
         }
     }
@@ -125,6 +160,9 @@ impl FieldInfo {
     /// Get JSON decoder for well-known types
     fn wkt_json_decoder(&self, wkt: &str) -> String {
         match wkt {
+            ".google.protobuf.Timestamp" | ".google.protobuf.Duration" if self.is_precise_time() => {
+                format!("{}.fromJson", self.rescript_type.trim_end_matches(".t"))
+            }
             // Timestamp -> parse RFC3339 string
             ".google.protobuf.Timestamp" => "WellKnown.Timestamp.fromJson".to_string(),
             // Duration -> parse "Xs" format
@@ -151,21 +189,66 @@ impl FieldInfo {
             ".google.protobuf.NullValue" => "_ => Some(Js.Null.empty)".to_string(),
             // Any -> special decoding
             ".google.protobuf.Any" => "WellKnown.Any.fromJson".to_string(),
+            // FieldMask -> split and convert paths back to proto names
+            ".google.protobuf.FieldMask" => "WellKnown.FieldMask.fromJson".to_string(),
             _ => "Json.Decode.string".to_string(),
         }
     }
 }
 
+/// Rendered `.res` implementation and `.resi` interface of one module
+#[derive(Debug, Clone, Default)]
+pub struct ModuleCode {
+    /// Top-level module name (services cover both their Client and Server modules)
+    pub name: String,
+    pub implementation: String,
+    pub interface: String,
+}
+
+/// Wrap modules in a named parent module (used for proto package segments)
+pub fn nest_modules(name: &str, modules: &[ModuleCode]) -> ModuleCode {
+    let wrap = |header: String, parts: Vec<&str>| {
+        let mut out = header;
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            for line in part.lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    out.push_str(&format!("  {}\n", line));
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    };
+
+    ModuleCode {
+        name: name.to_string(),
+        implementation: wrap(
+            format!("module {} = {{\n", name),
+            modules.iter().map(|m| m.implementation.as_str()).collect(),
+        ),
+        interface: wrap(
+            format!("module {}: {{\n", name),
+            modules.iter().map(|m| m.interface.as_str()).collect(),
+        ),
+    }
+}
+
 /// Template for generating a ReScript module from a proto file
 pub struct ModuleTemplate {
     pub package: String,
     pub source_file: String,
-    pub modules: Vec<String>,
-    pub use_wasm: bool,
+    pub modules: Vec<ModuleCode>,
+    /// Emit @rescript/core names instead of Js.* bindings
+    pub use_core: bool,
 }
 
 impl ModuleTemplate {
-    pub fn render(&self) -> String {
+    fn render_header(&self) -> String {
         let mut out = String::new();
 
         // Header comment
@@ -180,47 +263,103 @@ impl ModuleTemplate {
             out.push_str(&format!("// Package: {}\n\n", self.package));
         }
 
-        // WASM codec import if enabled
-        if self.use_wasm {
-            out.push_str("// WASM codec for encode/decode\n");
-            out.push_str("@module(\"./proto_codec.wasm\")\n");
-            out.push_str("external wasmCodec: Wasm.Instance.t = \"default\"\n\n");
-        }
+        out
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = self.render_header();
 
         // Render all modules (enums + messages)
         for module in &self.modules {
-            out.push_str(module);
+            out.push_str(&module.implementation);
             out.push_str("\n\n");
         }
 
-        out
+        self.finish(out)
+    }
+
+    /// Render the `.resi` interface (the WASM instance stays private)
+    pub fn render_interface(&self) -> String {
+        let mut out = self.render_header();
+
+        for module in &self.modules {
+            out.push_str(&module.interface);
+            out.push_str("\n\n");
+        }
+
+        self.finish(out)
+    }
+
+    fn finish(&self, out: String) -> String {
+        if self.use_core {
+            to_core_bindings(&out)
+        } else {
+            out
+        }
+    }
+}
+
+/// A single enum value
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    pub proto_name: String,
+    pub number: i32,
+    pub doc: Option<String>,
+    pub deprecated: bool,
+}
+
+impl EnumVariant {
+    /// Name of the constructor helper generated for a deprecated value
+    fn helper_name(&self) -> String {
+        naming::value_name(&self.name, FieldStyle::Camel, &["toInt", "fromInt"])
     }
 }
 
 /// Template for generating a ReScript enum from proto enum
 pub struct EnumTemplate {
     pub name: String,
-    pub variants: Vec<(String, i32)>,
+    pub variants: Vec<EnumVariant>,
+    pub doc: Option<String>,
+    pub deprecated: bool,
 }
 
 impl EnumTemplate {
+    pub fn render_module(&self) -> ModuleCode {
+        ModuleCode {
+            name: self.name.clone(),
+            implementation: self.render(),
+            interface: self.render_interface(),
+        }
+    }
+
     pub fn render(&self) -> String {
         let mut out = String::new();
 
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
         out.push_str(&format!("module {} = {{\n", self.name));
-
-        // Polymorphic variant type
-        out.push_str("  type t = [\n");
-        for (variant, _) in &self.variants {
-            out.push_str(&format!("    | #{}\n", variant));
+        if self.deprecated {
+            out.push_str(&render_allow_deprecated());
+        }
+        out.push_str(&self.render_type());
+
+        // Rows accept attributes such as doc comments, but deprecation is never
+        // reported for a tag, so deprecated values get a deprecated constructor
+        // helper instead
+        let deprecated: Vec<_> = self.variants.iter().filter(|v| v.deprecated).collect();
+        for variant in &deprecated {
+            out.push_str(&render_deprecated(&format!("{}.{}", self.name, variant.proto_name), "  "));
+            out.push_str(&format!("  let {}: t = #{}\n", variant.helper_name(), variant.name));
+        }
+        if !deprecated.is_empty() {
+            out.push('\n');
         }
-        out.push_str("  ]\n\n");
 
         // To int conversion
         out.push_str("  let toInt = (v: t): int => {\n");
         out.push_str("    switch v {\n");
-        for (variant, number) in &self.variants {
-            out.push_str(&format!("    | #{} => {}\n", variant, number));
+        for variant in &self.variants {
+            out.push_str(&format!("    | #{} => {}\n", variant.name, variant.number));
         }
         out.push_str("    }\n");
         out.push_str("  }\n\n");
@@ -228,8 +367,8 @@ impl EnumTemplate {
         // From int conversion
         out.push_str("  let fromInt = (i: int): option<t> => {\n");
         out.push_str("    switch i {\n");
-        for (variant, number) in &self.variants {
-            out.push_str(&format!("    | {} => Some(#{})\n", number, variant));
+        for variant in &self.variants {
+            out.push_str(&format!("    | {} => Some(#{})\n", variant.number, variant.name));
         }
         out.push_str("    | _ => None\n");
         out.push_str("    }\n");
@@ -239,6 +378,43 @@ impl EnumTemplate {
 
         out
     }
+
+    pub fn render_interface(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
+        out.push_str(&format!("module {}: {{\n", self.name));
+        if self.deprecated {
+            out.push_str(&render_allow_deprecated());
+        }
+        out.push_str(&self.render_type());
+        for variant in self.variants.iter().filter(|v| v.deprecated) {
+            out.push_str(&render_deprecated(&format!("{}.{}", self.name, variant.proto_name), "  "));
+            out.push_str(&format!("  let {}: t\n", variant.helper_name()));
+        }
+        out.push_str("  let toInt: t => int\n");
+        out.push_str("  let fromInt: int => option<t>\n");
+        out.push_str("}\n");
+
+        out
+    }
+
+    /// Polymorphic variant type, shared by the implementation and interface
+    fn render_type(&self) -> String {
+        let mut out = String::new();
+
+        if self.deprecated {
+            out.push_str(&render_deprecated(&self.name, "  "));
+        }
+        out.push_str("  type t = [\n");
+        for variant in &self.variants {
+            out.push_str(&render_doc(variant.doc.as_deref(), "    "));
+            out.push_str(&format!("    | #{}\n", variant.name));
+        }
+        out.push_str("  ]\n\n");
+
+        out
+    }
 }
 
 /// Template for generating a ReScript message module
@@ -246,11 +422,27 @@ pub struct MessageTemplate {
     pub name: String,
     pub fields: Vec<FieldInfo>,
     pub oneofs: Vec<OneOfInfo>,
-    pub nested: Vec<String>,
-    pub use_wasm: bool,
+    pub nested: Vec<ModuleCode>,
+    /// `WasmCodec.fieldDescriptor` records of the fields if binary codecs are
+    /// generated (`wasm`); None inside for recursive types, which an inline
+    /// schema cannot describe
+    pub wasm_schema: Option<Option<Vec<String>>>,
+    /// Fully-qualified proto name if the message declares extension ranges
+    pub extendee: Option<String>,
+    /// Fully-qualified proto name if an `anyType` codec is generated (`any_types`)
+    pub any_type: Option<String>,
+    /// Emit `applyMask`/`merge` field mask helpers (`field_masks`)
+    pub field_masks: bool,
+    pub doc: Option<String>,
+    pub deprecated: bool,
 }
 
 impl MessageTemplate {
+    /// Whether the generated code touches a deprecated type, field or value
+    fn uses_deprecated(&self) -> bool {
+        self.deprecated || self.fields.iter().any(|f| f.uses_deprecated())
+    }
+
     /// Get fields that are NOT part of any real oneof
     /// (proto3_optional fields have oneof_index but should be treated as regular fields)
     fn regular_fields(&self) -> Vec<&FieldInfo> {
@@ -269,28 +461,188 @@ impl MessageTemplate {
             .collect()
     }
 
+    pub fn render_module(&self) -> ModuleCode {
+        ModuleCode {
+            name: self.name.clone(),
+            implementation: self.render(),
+            interface: self.render_interface(),
+        }
+    }
+
     pub fn render(&self) -> String {
         let mut out = String::new();
 
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
         out.push_str(&format!("module {} = {{\n", self.name));
+        if self.uses_deprecated() {
+            out.push_str(&render_allow_deprecated());
+        }
 
         // Nested types first
         for nested in &self.nested {
             // Indent nested content
-            for line in nested.lines() {
+            for line in nested.implementation.lines() {
+                out.push_str(&format!("  {}\n", line));
+            }
+            out.push('\n');
+        }
+
+        out.push_str(&self.render_types());
+        out.push('\n');
+
+        // Default value constructor
+        let regular_fields = self.regular_fields();
+        out.push_str(&self.render_make_attributes());
+        out.push_str("  let make = (\n");
+        let mut params = Vec::new();
+
+        for field in &regular_fields {
+            if field.is_optional {
+                params.push(format!("    ~{}=?", field.name));
+            } else if field.is_repeated {
+                params.push(format!("    ~{}=[]", field.name));
+            } else {
+                params.push(format!("    ~{}", field.name));
+            }
+        }
+        // Add oneof parameters (always optional)
+        for oneof in &self.oneofs {
+            params.push(format!("    ~{}=?", oneof.name));
+        }
+        if self.extendee.is_some() {
+            params.push("    ~extensions=Extension.empty()".to_string());
+        }
+        params.push("    ~unknownFields=?".to_string());
+        out.push_str(&params.join(",\n"));
+        out.push('\n');
+        out.push_str("  ): t => {\n");
+        for field in &regular_fields {
+            out.push_str(&format!("    {},\n", field.name));
+        }
+        for oneof in &self.oneofs {
+            out.push_str(&format!("    {},\n", oneof.name));
+        }
+        if self.extendee.is_some() {
+            out.push_str("    extensions,\n");
+        }
+        out.push_str("    unknownFields: ?unknownFields,\n");
+        out.push_str("  }\n");
+
+        // JSON codec functions
+        out.push_str(&self.render_json_codec());
+
+        if let Some(full_name) = &self.any_type {
+            out.push_str("\n  // Packs into google.protobuf.Any with WellKnown.Any.pack/unpack\n");
+            out.push_str(&format!(
+                "  let anyType: WellKnown.Any.messageType<t> = {{typeName: \"{}\", toJson, fromJson}}\n",
+                full_name
+            ));
+        }
+
+        if self.field_masks {
+            out.push_str("\n  // Field mask helpers for Update RPCs, paths use proto field names\n");
+            out.push_str("  // Replace the masked fields of `target` with those of `source`; a path into\n");
+            out.push_str("  // a message field (\"address.city\") only replaces that subfield when both are set\n");
+            out.push_str(&self.render_mask_function("applyMask", false));
+            out.push_str("\n  // Like applyMask, but masked repeated fields are appended to the target's\n");
+            out.push_str(&self.render_mask_function("merge", true));
+        }
+
+        match &self.wasm_schema {
+            Some(Some(schema)) => out.push_str(&self.render_wasm_codec(schema)),
+            Some(None) => out.push_str("\n  // No binary codec: the WASM codec schema cannot describe recursive types\n"),
+            None => {}
+        }
+
+        out.push_str("}\n");
+
+        out
+    }
+
+    /// Render the module signature: types, `make` and the codecs
+    pub fn render_interface(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
+        out.push_str(&format!("module {}: {{\n", self.name));
+        if self.uses_deprecated() {
+            out.push_str(&render_allow_deprecated());
+        }
+
+        for nested in &self.nested {
+            for line in nested.interface.lines() {
                 out.push_str(&format!("  {}\n", line));
             }
             out.push('\n');
         }
 
+        out.push_str(&self.render_types());
+        out.push('\n');
+
+        let mut params = Vec::new();
+        for field in self.regular_fields() {
+            if field.is_optional {
+                params.push(format!("    ~{}: {}=?", field.name, field.rescript_type));
+            } else if field.is_repeated {
+                params.push(format!("    ~{}: {}=?", field.name, field.full_type()));
+            } else {
+                params.push(format!("    ~{}: {}", field.name, field.rescript_type));
+            }
+        }
+        for oneof in &self.oneofs {
+            params.push(format!("    ~{}: {}=?", oneof.name, oneof.name));
+        }
+        if self.extendee.is_some() {
+            params.push("    ~extensions: Extension.values=?".to_string());
+        }
+        params.push("    ~unknownFields: UnknownFields.t=?".to_string());
+
+        out.push_str(&self.render_make_attributes());
+        out.push_str("  let make: (\n");
+        out.push_str(&params.join(",\n"));
+        out.push_str(",\n  ) => t\n\n");
+
+        out.push_str("  let toJsonWith: (t, ~unknownFields: bool) => Js.Json.t\n");
+        out.push_str("  let toJson: t => Js.Json.t\n");
+        out.push_str("  let fromJson: Js.Json.t => option<t>\n");
+
+        if self.any_type.is_some() {
+            out.push_str("  let anyType: WellKnown.Any.messageType<t>\n");
+        }
+
+        if self.field_masks {
+            out.push_str("  let applyMask: (t, t, ~mask: WellKnown.FieldMask.t) => t\n");
+            out.push_str("  let merge: (t, t, ~mask: WellKnown.FieldMask.t) => t\n");
+        }
+
+        if let Some(Some(_)) = self.wasm_schema {
+            out.push_str("\n  let wasmSchema: array<WasmCodec.fieldDescriptor>\n");
+            out.push_str("  let encode: t => result<Js.TypedArray2.Uint8Array.t, string>\n");
+            out.push_str("  let decode: Js.TypedArray2.Uint8Array.t => result<t, string>\n");
+        }
+
+        out.push_str("}\n");
+
+        out
+    }
+
+    /// Oneof variant types and the record type, shared by the implementation and interface
+    fn render_types(&self) -> String {
+        let mut out = String::new();
+
         // Generate oneof variant types (use lowercase for type name in ReScript)
         for oneof in &self.oneofs {
+            out.push_str(&render_doc(oneof.doc.as_deref(), "  "));
             out.push_str(&format!("  type {} =\n", oneof.name));
             for field in &oneof.fields {
+                out.push_str(&render_doc(field.doc.as_deref(), "    "));
+                if field.deprecated {
+                    out.push_str(&render_deprecated(&field.proto_name, "    "));
+                }
                 // Variant with payload
                 out.push_str(&format!(
                     "    | {}({})\n",
-                    capitalize_first(&field.name),
+                    naming::constructor_name(&field.name),
                     field.rescript_type
                 ));
             }
@@ -298,8 +650,15 @@ impl MessageTemplate {
         }
 
         // Record type - exclude oneof fields from regular fields, add oneof as option
+        if self.deprecated {
+            out.push_str(&render_deprecated(&self.name, "  "));
+        }
         out.push_str("  type t = {\n");
         for field in self.regular_fields() {
+            out.push_str(&render_doc(field.doc.as_deref(), "    "));
+            if field.deprecated {
+                out.push_str(&render_deprecated(&field.proto_name, "    "));
+            }
             out.push_str(&format!(
                 "    {}: {},\n",
                 field.name,
@@ -308,52 +667,104 @@ impl MessageTemplate {
         }
         // Add oneof fields as option<oneofType>
         for oneof in &self.oneofs {
+            out.push_str(&render_doc(oneof.doc.as_deref(), "    "));
             out.push_str(&format!(
                 "    {}: option<{}>,\n",
                 oneof.name, oneof.name
             ));
         }
-        out.push_str("  }\n\n");
+        // Extension values keyed by "[full.name]"
+        if self.extendee.is_some() {
+            out.push_str("    extensions: Extension.values,\n");
+        }
+        // Raw bytes of unknown fields from binary decode, re-emitted on encode
+        out.push_str("    unknownFields?: UnknownFields.t,\n");
+        out.push_str("  }\n");
 
-        // Default value constructor
-        out.push_str("  let make = (\n");
+        out
+    }
+
+    /// `applyMask` or `merge`: copy masked fields of `source` over `target`
+    fn render_mask_function(&self, name: &str, append_repeated: bool) -> String {
+        let mut out = String::new();
         let regular_fields = self.regular_fields();
-        let total_params = regular_fields.len() + self.oneofs.len();
 
-        for (i, field) in regular_fields.iter().enumerate() {
-            let suffix = if i == total_params - 1 { "" } else { "," };
+        if regular_fields.is_empty() && self.oneofs.is_empty() {
+            out.push_str(&format!(
+                "  let {} = (target: t, _source: t, ~mask as _: WellKnown.FieldMask.t): t => target\n",
+                name
+            ));
+            return out;
+        }
 
-            if field.is_optional {
-                out.push_str(&format!("    ~{}=?{}\n", field.name, suffix));
-            } else if field.is_repeated {
-                out.push_str(&format!("    ~{}=[]{}\n", field.name, suffix));
+        out.push_str(&format!(
+            "  let {} = (target: t, source: t, ~mask: WellKnown.FieldMask.t): t => {{\n",
+            name
+        ));
+        out.push_str("    ...target,\n");
+        for field in &regular_fields {
+            let covers = format!("WellKnown.FieldMask.covers(mask, \"{}\")", field.proto_name);
+            let nested_module = field.rescript_type.strip_suffix(".t").filter(|_| {
+                field.is_message && field.is_optional && field.well_known_type.is_none() && field.custom_codec.is_none()
+            });
+
+            if field.is_repeated && append_repeated {
+                out.push_str(&format!(
+                    "    {0}: {1} ? Array.concat(target.{0}, source.{0}) : target.{0},\n",
+                    field.name, covers
+                ));
+            } else if let Some(module) = nested_module {
+                // Recurse into the submessage for paths below it
+                out.push_str(&format!("    {}: if {} {{\n", field.name, covers));
+                out.push_str(&format!("      source.{}\n", field.name));
+                out.push_str("    } else {\n");
+                out.push_str(&format!(
+                    "      switch (target.{0}, source.{0}, WellKnown.FieldMask.subMask(mask, \"{1}\")) {{\n",
+                    field.name, field.proto_name
+                ));
+                out.push_str(&format!("      | (_, _, []) => target.{}\n", field.name));
+                out.push_str(&format!(
+                    "      | (Some(target), Some(source), mask) => Some({}.{}(target, source, ~mask))\n",
+                    module, name
+                ));
+                out.push_str("      | (_, source, _) => source\n");
+                out.push_str("      }\n");
+                out.push_str("    },\n");
             } else {
-                out.push_str(&format!("    ~{}{}\n", field.name, suffix));
+                out.push_str(&format!("    {0}: {1} ? source.{0} : target.{0},\n", field.name, covers));
             }
         }
-        // Add oneof parameters (always optional)
-        for (i, oneof) in self.oneofs.iter().enumerate() {
-            let suffix = if regular_fields.len() + i == total_params - 1 { "" } else { "," };
-            out.push_str(&format!("    ~{}=?{}\n", oneof.name, suffix));
-        }
-        out.push_str("  ): t => {\n");
-        for field in &regular_fields {
-            out.push_str(&format!("    {},\n", field.name));
-        }
+        // A oneof is replaced as a whole when any of its members is masked
         for oneof in &self.oneofs {
-            out.push_str(&format!("    {},\n", oneof.name));
+            let members: Vec<String> = oneof.fields.iter().map(|f| format!("\"{}\"", f.proto_name)).collect();
+            out.push_str(&format!(
+                "    {0}: [{1}]->Array.some(WellKnown.FieldMask.covers(mask, _)) ? source.{0} : target.{0},\n",
+                oneof.name,
+                members.join(", ")
+            ));
         }
         out.push_str("  }\n");
 
-        // JSON codec functions
-        out.push_str(&self.render_json_codec());
+        out
+    }
 
-        // WASM encode/decode stubs if enabled
-        if self.use_wasm {
-            out.push_str(&self.render_wasm_codec());
-        }
+    /// Doc and `@deprecated` attribute placed on `make`
+    fn render_make_attributes(&self) -> String {
+        let mut out = String::new();
 
-        out.push_str("}\n");
+        // Labelled arguments can't carry attributes, so note deprecated labels in the doc
+        let deprecated_labels: Vec<String> = self
+            .regular_fields()
+            .iter()
+            .filter(|f| f.deprecated)
+            .map(|f| format!("`~{}`", f.name))
+            .collect();
+        if !deprecated_labels.is_empty() {
+            out.push_str(&format!("  /** Deprecated labels: {} */\n", deprecated_labels.join(", ")));
+        }
+        if self.deprecated {
+            out.push_str(&render_deprecated(&self.name, "  "));
+        }
 
         out
     }
@@ -362,9 +773,23 @@ impl MessageTemplate {
         let mut out = String::new();
         let regular_fields = self.regular_fields();
 
+        // Nested messages pass the unknown fields flag down
+        let encoder = |field: &FieldInfo| {
+            if field.is_message && field.well_known_type.is_none() && field.custom_codec.is_none() {
+                format!(
+                    "v => {}.toJsonWith(v, ~unknownFields)",
+                    field.rescript_type.trim_end_matches(".t")
+                )
+            } else {
+                field.json_encoder()
+            }
+        };
+
         // toJson function
         out.push_str("\n  // JSON serialization\n");
-        out.push_str("  let toJson = (msg: t): Js.Json.t => {\n");
+        out.push_str("  // Unknown fields are kept under \"$unknown\" only for the WASM codec,\n");
+        out.push_str("  // proto3 JSON has no representation for them\n");
+        out.push_str("  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {\n");
 
         // Generate oneof encoder helpers inline (returns array<(string, Js.Json.t)>)
         for oneof in &self.oneofs {
@@ -374,11 +799,15 @@ impl MessageTemplate {
             ));
             out.push_str("    | None => []\n");
             for field in &oneof.fields {
+                let value = match encoder(field).strip_prefix("v => ") {
+                    Some(call) => call.to_string(),
+                    None => format!("{}(v)", field.json_encoder()),
+                };
                 out.push_str(&format!(
-                    "    | Some({}(v)) => [(\"{}\", {}(v))]\n",
-                    capitalize_first(&field.name),
+                    "    | Some({}(v)) => [(\"{}\", {})]\n",
+                    naming::constructor_name(&field.name),
                     field.proto_name,
-                    field.json_encoder()
+                    value
                 ));
             }
             out.push_str("    }\n");
@@ -394,7 +823,7 @@ impl MessageTemplate {
                     "        Json.Encode.required(\"{}\", msg.{}, {}),\n",
                     field.proto_name,
                     field.name,
-                    field.json_encoder()
+                    encoder(field)
                 ));
             }
         }
@@ -409,14 +838,14 @@ impl MessageTemplate {
                     "        Json.Encode.optional(\"{}\", msg.{}, {}),\n",
                     field.proto_name,
                     field.name,
-                    field.json_encoder()
+                    encoder(field)
                 ));
             } else if field.is_repeated {
                 out.push_str(&format!(
                     "        Json.Encode.repeated(\"{}\", msg.{}, {}),\n",
                     field.proto_name,
                     field.name,
-                    field.json_encoder()
+                    encoder(field)
                 ));
             }
         }
@@ -426,9 +855,15 @@ impl MessageTemplate {
             out.push_str(&format!("        {}Fields,\n", oneof.name));
         }
 
+        if self.extendee.is_some() {
+            out.push_str("        Extension.toJsonFields(msg.extensions),\n");
+        }
+        out.push_str("        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],\n");
+
         out.push_str("      ],\n");
         out.push_str("    ))\n");
         out.push_str("  }\n\n");
+        out.push_str("  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)\n\n");
 
         // fromJson function
         out.push_str("  // JSON deserialization\n");
@@ -462,42 +897,42 @@ impl MessageTemplate {
             out.push('\n');
         }
 
-        // Decode oneof fields - try each field in order, first match wins
+        // Extension values registered for this message
+        if let Some(extendee) = &self.extendee {
+            out.push_str(&format!(
+                "        let extensions = Extension.fromJsonObject(obj, ~extendee=\"{}\")\n",
+                extendee
+            ));
+        }
+        out.push_str("        let unknownFields = UnknownFields.fromJsonObject(obj)\n");
+
+        // A oneof decodes when at most one member is set and that member is valid
         for oneof in &self.oneofs {
-            out.push_str(&format!("        let {} = {{\n", oneof.name));
-            let field_count = oneof.fields.len();
-            for (i, field) in oneof.fields.iter().enumerate() {
-                let is_last = i == field_count - 1;
-                if i == 0 {
-                    out.push_str(&format!(
-                        "          switch Json.Decode.optional(obj, \"{}\", {}) {{\n",
-                        field.proto_name, field.json_decoder()
-                    ));
-                } else {
-                    out.push_str(&format!(
-                        "          | Ok(None) =>\n            switch Json.Decode.optional(obj, \"{}\", {}) {{\n",
-                        field.proto_name, field.json_decoder()
-                    ));
-                }
+            out.push_str(&format!("        let {} = switch [\n", oneof.name));
+            for field in &oneof.fields {
                 out.push_str(&format!(
-                    "            | Ok(Some(v)) => Some({}(v))\n",
-                    capitalize_first(&field.name)
+                    "          Json.Decode.optional(obj, \"{}\", {})->Result.map(v => Option.map(v, v => {}(v))),\n",
+                    field.proto_name,
+                    field.json_decoder(),
+                    naming::constructor_name(&field.name)
                 ));
-                out.push_str("            | Error(_) => None\n");
-                if is_last {
-                    out.push_str("            | Ok(None) => None\n");
-                }
-            }
-            // Close all the nested switches
-            for _ in 0..field_count {
-                out.push_str("            }\n");
             }
+            out.push_str("        ]->Array.filter(member => Result.mapOr(member, true, Option.isSome)) {\n");
+            out.push_str("        | [] => Ok(None)\n");
+            out.push_str("        | [member] => member\n");
+            out.push_str(&format!(
+                "        | _ => Error(Json.Decode.InvalidValue(\"more than one member of {} is set\"))\n",
+                oneof.name
+            ));
             out.push_str("        }\n");
         }
 
-        // Check required fields and build result
-        let required_fields_check: Vec<_> = regular_fields.iter()
+        // Check required fields and oneofs, then build the result
+        let required_fields_check: Vec<&str> = regular_fields
+            .iter()
             .filter(|f| !f.is_optional && !f.is_repeated)
+            .map(|f| f.name.as_str())
+            .chain(self.oneofs.iter().map(|o| o.name.as_str()))
             .collect();
 
         if required_fields_check.is_empty() {
@@ -508,15 +943,19 @@ impl MessageTemplate {
             for oneof in &self.oneofs {
                 out.push_str(&format!("          {},\n", oneof.name));
             }
+            if self.extendee.is_some() {
+                out.push_str("          extensions,\n");
+            }
+            out.push_str("          unknownFields: ?unknownFields,\n");
             out.push_str("        })\n");
         } else {
-            // Check all required fields are Ok
+            // Check all required fields and oneofs are Ok
             out.push_str("        switch (");
             for (i, field) in required_fields_check.iter().enumerate() {
                 if i > 0 {
                     out.push_str(", ");
                 }
-                out.push_str(&field.name);
+                out.push_str(field);
             }
             out.push_str(") {\n");
             out.push_str("        | (");
@@ -524,7 +963,7 @@ impl MessageTemplate {
                 if i > 0 {
                     out.push_str(", ");
                 }
-                out.push_str(&format!("Ok({})", field.name));
+                out.push_str(&format!("Ok({})", field));
             }
             out.push_str(") =>\n");
             out.push_str("          Some({\n");
@@ -534,6 +973,10 @@ impl MessageTemplate {
             for oneof in &self.oneofs {
                 out.push_str(&format!("            {},\n", oneof.name));
             }
+            if self.extendee.is_some() {
+                out.push_str("            extensions,\n");
+            }
+            out.push_str("            unknownFields: ?unknownFields,\n");
             out.push_str("          })\n");
             out.push_str("        | _ => None\n");
             out.push_str("        }\n");
@@ -546,60 +989,227 @@ impl MessageTemplate {
         out
     }
 
-    fn render_wasm_codec(&self) -> String {
+    fn render_wasm_codec(&self, schema: &[String]) -> String {
         let mut out = String::new();
-        let lower_name = self.name.to_lowercase();
+        // Extensions are only read and written with the extendee's type name
+        let type_name = match &self.extendee {
+            Some(extendee) => format!(", ~typeName=\"{}\"", extendee),
+            None => String::new(),
+        };
 
-        out.push_str("\n  // WASM codec functions\n");
+        out.push_str("\n  // Protobuf binary through the WASM codec (after WasmCodec.initialize)\n");
+        out.push_str("  let wasmSchema: array<WasmCodec.fieldDescriptor> = [\n");
+        for field in schema {
+            out.push_str(&format!("    {},\n", field));
+        }
+        out.push_str("  ]\n\n");
 
-        // Encode function
+        // Unknown fields kept by an earlier decode are written back
         out.push_str(&format!(
-            "  let encode = async (msg: t): Js.Typed_array.Uint8Array.t => {{\n"
+            "  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>\n    \
+             WasmCodec.encodeBytes(~schema=wasmSchema{}, ~data=toJsonWith(msg, ~unknownFields=true))\n\n",
+            type_name
         ));
-        out.push_str("    let exports = Wasm.Instance.exports(wasmCodec)\n");
-        out.push_str("    let memory = %raw(`exports.memory`)\n");
-        out.push_str("    let allocator = Wasm.Allocator.fromExports(exports)\n");
-        out.push_str(&format!(
-            "    let encodeFn: Wasm.Allocator.ptr => (Wasm.Allocator.ptr, int) = %raw(`exports.encode_{}`)\n",
-            lower_name
-        ));
-        out.push_str("    // TODO: Serialize msg to memory, call encodeFn\n");
-        out.push_str("    Js.Typed_array.Uint8Array.make([])\n");
-        out.push_str("  }\n\n");
 
-        // Decode function
-        out.push_str(&format!(
-            "  let decode = async (bytes: Js.Typed_array.Uint8Array.t): t => {{\n"
-        ));
-        out.push_str("    let exports = Wasm.Instance.exports(wasmCodec)\n");
-        out.push_str("    let memory = %raw(`exports.memory`)\n");
-        out.push_str("    let allocator = Wasm.Allocator.fromExports(exports)\n");
-        out.push_str(&format!(
-            "    let decodeFn: (Wasm.Allocator.ptr, int) => Wasm.Allocator.ptr = %raw(`exports.decode_{}`)\n",
-            lower_name
-        ));
-        out.push_str("    // TODO: Copy bytes to memory, call decodeFn, deserialize result\n");
-        out.push_str(&format!("    make(\n"));
-        for field in &self.fields {
-            if field.is_optional {
-                out.push_str(&format!("      ~{}=None,\n", field.name));
-            } else if field.is_repeated {
-                out.push_str(&format!("      ~{}=[],\n", field.name));
+        out.push_str("  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>\n");
+        out.push_str(&format!("    switch WasmCodec.decodeBytes(~schema=wasmSchema{}, ~data=bytes) {{\n", type_name));
+        out.push_str("    | Ok(json) =>\n");
+        out.push_str("      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {\n");
+        out.push_str("      | Some(msg) => Ok(msg)\n");
+        out.push_str(&format!("      | None => Error(\"Decoded message is not a valid {}\")\n", self.name));
+        out.push_str("      }\n");
+        out.push_str("    | Error(err) => Error(err)\n");
+        out.push_str("    }\n");
+
+        out
+    }
+}
+
+/// Information about an extension field declared in an `extend` block
+#[derive(Debug, Clone)]
+pub struct ExtensionInfo {
+    pub field: FieldInfo,
+    /// Fully-qualified extension name (e.g., "example.priority")
+    pub full_name: String,
+    /// Fully-qualified name of the extended message (e.g., "example.Task")
+    pub extendee: String,
+    /// WasmCodec field type constructor, with an inline schema for message
+    /// values; None if the value type is recursive
+    pub wasm_type: Option<String>,
+}
+
+impl ExtensionInfo {
+    /// ReScript type of the extension value
+    fn value_type(&self) -> String {
+        if self.field.is_repeated {
+            format!("array<{}>", self.field.rescript_type)
+        } else {
+            self.field.rescript_type.clone()
+        }
+    }
+}
+
+/// Template for the `Extensions` module of a proto file
+///
+/// Extensions declared inside a message are placed in a submodule named
+/// after that message, mirroring proto scoping.
+pub struct ExtensionsTemplate {
+    pub name: String,
+    pub extensions: Vec<ExtensionInfo>,
+    pub nested: Vec<ExtensionsTemplate>,
+}
+
+impl ExtensionsTemplate {
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty() && self.nested.iter().all(|n| n.is_empty())
+    }
+
+    /// Whether `register` or a value type touches something deprecated
+    fn uses_deprecated(&self) -> bool {
+        self.extensions.iter().any(|ext| ext.field.uses_deprecated())
+    }
+
+    pub fn render_module(&self) -> ModuleCode {
+        ModuleCode {
+            name: self.name.clone(),
+            implementation: self.render(),
+            interface: self.render_interface(),
+        }
+    }
+
+    pub fn render_interface(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!("module {}: {{\n", self.name));
+        if self.uses_deprecated() {
+            out.push_str(&render_allow_deprecated());
+        }
+
+        for nested in self.nested.iter().filter(|n| !n.is_empty()) {
+            for line in nested.render_interface().lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    out.push_str(&format!("  {}\n", line));
+                }
+            }
+            out.push('\n');
+        }
+
+        for ext in &self.extensions {
+            let field = &ext.field;
+            out.push_str(&render_doc(field.doc.as_deref(), "  "));
+            if field.deprecated {
+                out.push_str(&render_deprecated(&ext.full_name, "  "));
+            }
+            out.push_str(&format!("  let {}: Extension.t<{}>\n", field.name, ext.value_type()));
+        }
+        out.push_str("  let register: unit => unit\n");
+        out.push_str("}\n");
+
+        out
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!("module {} = {{\n", self.name));
+        if self.uses_deprecated() {
+            out.push_str(&render_allow_deprecated());
+        }
+
+        for nested in self.nested.iter().filter(|n| !n.is_empty()) {
+            for line in nested.render().lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    out.push_str(&format!("  {}\n", line));
+                }
+            }
+            out.push('\n');
+        }
+
+        for ext in &self.extensions {
+            let field = &ext.field;
+            let value_type = ext.value_type();
+            let (encoder, decoder) = if field.is_repeated {
+                (
+                    format!("Json.Encode.array({})", field.json_encoder()),
+                    format!("Json.Decode.array({})", field.json_decoder()),
+                )
             } else {
-                // Default values for required fields
-                let default = match field.rescript_type.as_str() {
-                    "int" => "0",
-                    "float" => "0.0",
-                    "bool" => "false",
-                    "string" => "\"\"",
-                    _ => "Obj.magic(0)", // Placeholder for complex types
-                };
-                out.push_str(&format!("      ~{}={},\n", field.name, default));
+                (field.json_encoder(), field.json_decoder())
+            };
+
+            out.push_str(&format!("  // Extends {}\n", ext.extendee));
+            if ext.wasm_type.is_none() {
+                out.push_str("  // Recursive value type without an inline WASM schema, the binary codec skips it\n");
             }
+            out.push_str(&render_doc(field.doc.as_deref(), "  "));
+            if field.deprecated {
+                out.push_str(&render_deprecated(&ext.full_name, "  "));
+            }
+            out.push_str(&format!(
+                "  let {}: Extension.t<{}> = Extension.make(\n",
+                field.name, value_type
+            ));
+            out.push_str(&format!("    ~extendee=\"{}\",\n", ext.extendee));
+            out.push_str(&format!("    ~fieldNumber={},\n", field.number));
+            out.push_str(&format!("    ~name=\"{}\",\n", ext.full_name));
+            if field.is_repeated {
+                out.push_str("    ~repeated=true,\n");
+            }
+            out.push_str(&format!("    ~toJson={},\n", encoder));
+            out.push_str(&format!("    ~fromJson={},\n", decoder));
+            if let Some(wasm_type) = &ext.wasm_type {
+                out.push_str(&format!("    ~wasmType=WasmCodec.{},\n", wasm_type));
+            }
+            out.push_str("  )\n\n");
+        }
+
+        // Register every extension in this scope (and nested scopes)
+        out.push_str("  // Register extensions for fromJson and the WASM codec\n");
+        out.push_str("  let register = () => {\n");
+        for nested in self.nested.iter().filter(|n| !n.is_empty()) {
+            out.push_str(&format!("    {}.register()\n", nested.name));
+        }
+        for ext in &self.extensions {
+            out.push_str(&format!("    Extension.register({})\n", ext.field.name));
         }
-        out.push_str("    )\n");
         out.push_str("  }\n");
 
+        out.push_str("}\n");
+
+        out
+    }
+}
+
+/// Template for the `AnyTypes` module of a proto file (`any_types` option)
+pub struct AnyTypesTemplate {
+    /// Module paths of the file's messages, e.g. "User.Address"
+    pub messages: Vec<String>,
+}
+
+impl AnyTypesTemplate {
+    pub fn render_module(&self) -> ModuleCode {
+        ModuleCode {
+            name: "AnyTypes".to_string(),
+            implementation: self.render(),
+            interface: "module AnyTypes: {\n  let register: unit => unit\n}\n".to_string(),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str("// Message types of this file for google.protobuf.Any\n");
+        out.push_str("module AnyTypes = {\n");
+        out.push_str("  // Register every type so WellKnown.Any.isKnown recognizes it\n");
+        out.push_str("  let register = () => {\n");
+        for message in &self.messages {
+            out.push_str(&format!("    WellKnown.Any.register({}.anyType)\n", message));
+        }
+        out.push_str("  }\n");
+        out.push_str("}\n");
         out
     }
 }
@@ -608,121 +1218,161 @@ impl MessageTemplate {
 #[derive(Debug, Clone)]
 pub struct MethodInfo {
     pub name: String,
+    /// Client function, handler and service field name
+    pub function_name: String,
     pub input_type: String,
     pub output_type: String,
     pub client_streaming: bool,
     pub server_streaming: bool,
+    pub doc: Option<String>,
+    pub deprecated: bool,
+    /// The input or output message is `[deprecated = true]`
+    pub deprecated_types: bool,
+    /// `idempotency_level` is NO_SIDE_EFFECTS or IDEMPOTENT, so the default retry policy applies
+    pub idempotent: bool,
 }
 
 /// Template for generating a gRPC-web service client
 pub struct ServiceTemplate {
     pub name: String,
+    /// Fully-qualified proto name, e.g. "example.UserService"
+    pub full_name: String,
     pub methods: Vec<MethodInfo>,
+    pub doc: Option<String>,
+    pub deprecated: bool,
 }
 
 impl ServiceTemplate {
     pub fn render(&self) -> String {
         let mut out = String::new();
 
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
         out.push_str(&format!("module {}Client = {{\n", self.name));
-
-        // Client configuration type
-        out.push_str("  // gRPC-web client configuration\n");
-        out.push_str("  type config = {\n");
-        out.push_str("    baseUrl: string,\n");
-        out.push_str("    headers: option<Js.Dict.t<string>>,\n");
-        out.push_str("  }\n\n");
+        out.push_str(&self.render_allow_deprecated());
+        out.push_str(&self.render_client_types());
 
         // Default config
         out.push_str("  let defaultConfig = {\n");
         out.push_str("    baseUrl: \"http://localhost:8080\",\n");
         out.push_str("    headers: None,\n");
+        out.push_str("    retry: {Retry.policy: Retry.defaultPolicy},\n");
         out.push_str("  }\n\n");
 
-        // Error type
-        out.push_str("  type error =\n");
-        out.push_str("    | NetworkError(string)\n");
-        out.push_str("    | GrpcError(int, string)\n");
-        out.push_str("    | DecodeError(string)\n\n");
+        out.push_str("  // Service name in gRPC service configs\n");
+        out.push_str(&format!("  let serviceName = \"{}\"\n\n", self.full_name));
 
-        // Helper function for making requests
-        out.push_str("  // Internal fetch helper\n");
-        out.push_str("  let call = async (\n");
-        out.push_str("    ~config: config,\n");
-        out.push_str("    ~method: string,\n");
-        out.push_str("    ~request: Js.Json.t,\n");
-        out.push_str("  ): result<Js.Json.t, error> => {\n");
-        out.push_str("    let url = `${config.baseUrl}/${method}`\n");
+        out.push_str("  // gRPC status of a failed call, for retry policies\n");
+        out.push_str("  let retryStatus = (error: error): option<int> => {\n");
+        out.push_str("    switch error {\n");
+        out.push_str("    | NetworkError(_) => Some(14)\n");
+        out.push_str("    | GrpcError(status, _) => Some(Retry.codeOfHttpStatus(status))\n");
+        out.push_str("    | StatusError(status) => Some(status.code)\n");
+        out.push_str("    | DecodeError(_) | DeadlineExceeded | Cancelled => None\n");
+        out.push_str("    }\n");
+        out.push_str("  }\n\n");
+
+        // Helper functions for making requests
+        out.push_str("  // Default headers merged with config.headers\n");
+        out.push_str("  let requestHeaders = (~config: config, ~accept: string): Js.Dict.t<string> => {\n");
         out.push_str("    let headers = Js.Dict.fromArray([\n");
         out.push_str("      (\"Content-Type\", \"application/json\"),\n");
-        out.push_str("      (\"Accept\", \"application/json\"),\n");
+        out.push_str("      (\"Accept\", accept),\n");
         out.push_str("    ])\n");
         out.push_str("    // Merge custom headers\n");
         out.push_str("    switch config.headers {\n");
         out.push_str("    | Some(h) => Js.Dict.entries(h)->Array.forEach(((k, v)) => Js.Dict.set(headers, k, v))\n");
         out.push_str("    | None => ()\n");
-        out.push_str("    }\n\n");
+        out.push_str("    }\n");
+        out.push_str("    headers\n");
+        out.push_str("  }\n\n");
+
+        out.push_str("  // Internal fetch helper, called by the innermost interceptor\n");
+        out.push_str("  let fetchUnary = async (\n");
+        out.push_str("    ~config: config,\n");
+        out.push_str("    ~deadline: Deadline.t,\n");
+        out.push_str("    request: Interceptor.request,\n");
+        out.push_str("  ): result<Js.Json.t, error> => {\n");
+        out.push_str("    let url = `${config.baseUrl}/${request.method}`\n");
+        out.push_str("    // grpc-timeout counts from this attempt, so retries send the time left\n");
+        out.push_str("    let headers = Js.Dict.fromArray(Js.Dict.entries(request.headers))\n");
+        out.push_str("    Deadline.remainingMs(deadline)->Option.forEach(ms => Js.Dict.set(headers, \"grpc-timeout\", Deadline.header(ms)))\n");
         out.push_str("    try {\n");
         out.push_str("      let response = await Fetch.fetch(\n");
         out.push_str("        url,\n");
         out.push_str("        {\n");
         out.push_str("          method: #POST,\n");
         out.push_str("          headers: Fetch.Headers.fromDict(headers),\n");
-        out.push_str("          body: Fetch.Body.string(Js.Json.stringify(request)),\n");
+        out.push_str("          body: Fetch.Body.string(Js.Json.stringify(request.body)),\n");
+        out.push_str("          signal: Deadline.signal(deadline),\n");
         out.push_str("        },\n");
         out.push_str("      )\n");
-        out.push_str("      if Fetch.Response.ok(response) {\n");
+        out.push_str("      let header = name => Fetch.Headers.get(Fetch.Headers.ofResponse(response), name)->Nullable.toOption\n");
+        out.push_str("      if Fetch.Response.ok(response) && header(\"grpc-status\")->Option.getOr(\"0\") == \"0\" {\n");
         out.push_str("        let json = await Fetch.Response.json(response)\n");
         out.push_str("        Ok(json)\n");
         out.push_str("      } else {\n");
         out.push_str("        let status = Fetch.Response.status(response)\n");
         out.push_str("        let text = await Fetch.Response.text(response)\n");
-        out.push_str("        Error(GrpcError(status, text))\n");
+        out.push_str("        switch RpcStatus.fromResponse(~header, ~body=text) {\n");
+        out.push_str("        | Some(rpcStatus) => Error(StatusError(rpcStatus))\n");
+        out.push_str("        | None => Error(GrpcError(status, text))\n");
+        out.push_str("        }\n");
         out.push_str("      }\n");
         out.push_str("    } catch {\n");
+        out.push_str("    | _ if Deadline.timedOut(deadline) => Error(DeadlineExceeded)\n");
+        out.push_str("    | _ if Deadline.aborted(deadline) => Error(Cancelled)\n");
         out.push_str("    | Exn.Error(exn) => Error(NetworkError(Exn.message(exn)->Option.getOr(\"Unknown error\")))\n");
         out.push_str("    | _ => Error(NetworkError(\"Unknown error\"))\n");
         out.push_str("    }\n");
         out.push_str("  }\n\n");
 
-        // Stream callback type for server streaming
-        out.push_str("  // Stream handler for server-streaming RPCs\n");
-        out.push_str("  type streamHandler<'a> = {\n");
-        out.push_str("    onMessage: 'a => unit,\n");
-        out.push_str("    onError: error => unit,\n");
-        out.push_str("    onComplete: unit => unit,\n");
-        out.push_str("  }\n\n");
-
-        // Stream cancellation type
-        out.push_str("  // Cancellation handle for streaming RPCs\n");
-        out.push_str("  type streamCancel = {\n");
-        out.push_str("    cancel: unit => unit,\n");
+        out.push_str("  // Unary call through config.interceptors, aborted at the deadline or by `signal`\n");
+        out.push_str("  // and retried per config.retry\n");
+        out.push_str("  let call = async (\n");
+        out.push_str("    ~config: config,\n");
+        out.push_str("    ~method: string,\n");
+        out.push_str("    ~request: Js.Json.t,\n");
+        out.push_str("    ~timeoutMs: int=?,\n");
+        out.push_str("    ~signal: Fetch.AbortSignal.t=?,\n");
+        out.push_str("    ~idempotent: bool=false,\n");
+        out.push_str("  ): result<Js.Json.t, error> => {\n");
+        out.push_str("    let timeoutMs = timeoutMs->Option.orElse(config.timeoutMs)\n");
+        out.push_str("    let deadline = Deadline.make(~timeoutMs?, ~signal?, ())\n");
+        out.push_str("    let headers = requestHeaders(~config, ~accept=\"application/json\")\n");
+        out.push_str("    timeoutMs->Option.forEach(ms => Js.Dict.set(headers, \"grpc-timeout\", Deadline.header(ms)))\n");
+        out.push_str("    let interceptors = config.interceptors->Option.getOr([])\n");
+        out.push_str("    let send = switch Retry.policyFor(config.retry, ~method, ~idempotent) {\n");
+        out.push_str("    | Some(policy) =>\n");
+        out.push_str("      request =>\n");
+        out.push_str("        Retry.run(\n");
+        out.push_str("          policy,\n");
+        out.push_str("          ~status=retryStatus,\n");
+        out.push_str("          ~signal=Deadline.signal(deadline),\n");
+        out.push_str("          ~aborted=() => Deadline.timedOut(deadline) ? DeadlineExceeded : Cancelled,\n");
+        out.push_str("          () => fetchUnary(~config, ~deadline, request),\n");
+        out.push_str("        )\n");
+        out.push_str("    | None => request => fetchUnary(~config, ~deadline, request)\n");
+        out.push_str("    }\n");
+        out.push_str("    let run = Interceptor.chainUnary(interceptors, send)\n");
+        out.push_str("    // Cleared on success, failure and exceptions from interceptors alike\n");
+        out.push_str("    await run({method, headers, body: request})->Promise.finally(() => Deadline.clear(deadline))\n");
         out.push_str("  }\n\n");
 
         // Streaming call helper (for server-streaming)
-        out.push_str("  // Internal streaming helper for server-streaming RPCs\n");
-        out.push_str("  let callStream = (\n");
+        out.push_str("  // Internal streaming helper, called by the innermost interceptor\n");
+        out.push_str("  let fetchStream = (\n");
         out.push_str("    ~config: config,\n");
-        out.push_str("    ~method: string,\n");
-        out.push_str("    ~request: Js.Json.t,\n");
-        out.push_str("    ~handler: streamHandler<Js.Json.t>,\n");
+        out.push_str("    request: Interceptor.request,\n");
+        out.push_str("    handler: streamHandler<Js.Json.t>,\n");
         out.push_str("  ): streamCancel => {\n");
         out.push_str("    let cancelled = ref(false)\n");
-        out.push_str("    let url = `${config.baseUrl}/${method}`\n");
-        out.push_str("    let headers = Js.Dict.fromArray([\n");
-        out.push_str("      (\"Content-Type\", \"application/json\"),\n");
-        out.push_str("      (\"Accept\", \"application/x-ndjson\"),\n");
-        out.push_str("    ])\n");
-        out.push_str("    switch config.headers {\n");
-        out.push_str("    | Some(h) => Js.Dict.entries(h)->Array.forEach(((k, v)) => Js.Dict.set(headers, k, v))\n");
-        out.push_str("    | None => ()\n");
-        out.push_str("    }\n\n");
+        out.push_str("    let url = `${config.baseUrl}/${request.method}`\n\n");
         out.push_str("    // Start the streaming request\n");
         out.push_str("    let _ = Streaming.fetchNdjson(\n");
         out.push_str("      ~url,\n");
         out.push_str("      ~method=#POST,\n");
-        out.push_str("      ~headers,\n");
-        out.push_str("      ~body=Js.Json.stringify(request),\n");
+        out.push_str("      ~headers=request.headers,\n");
+        out.push_str("      ~body=Js.Json.stringify(request.body),\n");
         out.push_str("      ~onMessage=json => {\n");
         out.push_str("        if !cancelled.contents {\n");
         out.push_str("          handler.onMessage(json)\n");
@@ -742,6 +1392,21 @@ impl ServiceTemplate {
         out.push_str("    {cancel: () => cancelled := true}\n");
         out.push_str("  }\n\n");
 
+        out.push_str("  // Server-streaming call through config.interceptors\n");
+        out.push_str("  let callStream = (\n");
+        out.push_str("    ~config: config,\n");
+        out.push_str("    ~method: string,\n");
+        out.push_str("    ~request: Js.Json.t,\n");
+        out.push_str("    ~handler: streamHandler<Js.Json.t>,\n");
+        out.push_str("  ): streamCancel => {\n");
+        out.push_str("    let interceptors = config.interceptors->Option.getOr([])\n");
+        out.push_str("    let run = Interceptor.chainStream(interceptors, (request, handler) =>\n");
+        out.push_str("      fetchStream(~config, request, handler)\n");
+        out.push_str("    )\n");
+        out.push_str("    let headers = requestHeaders(~config, ~accept=\"application/x-ndjson\")\n");
+        out.push_str("    run({method, headers, body: request}, handler)\n");
+        out.push_str("  }\n\n");
+
         // Generate each RPC method
         for method in &self.methods {
             out.push_str(&self.render_method(method));
@@ -756,6 +1421,242 @@ impl ServiceTemplate {
         out
     }
 
+    /// Warning suppression for the client and server modules if a method uses a deprecated message
+    fn render_allow_deprecated(&self) -> String {
+        if self.methods.iter().any(|m| m.deprecated_types) {
+            render_allow_deprecated()
+        } else {
+            String::new()
+        }
+    }
+
+    /// `@deprecated` attribute for a client function if the method or service is deprecated
+    fn render_method_deprecated(&self, method: &MethodInfo) -> String {
+        if method.deprecated {
+            render_deprecated(&format!("{}.{}", self.name, method.name), "  ")
+        } else if self.deprecated {
+            render_deprecated(&self.name, "  ")
+        } else {
+            String::new()
+        }
+    }
+
+    pub fn render_module(&self) -> ModuleCode {
+        ModuleCode {
+            name: self.name.clone(),
+            implementation: self.render(),
+            interface: self.render_interface(),
+        }
+    }
+
+    /// Render the client and server signatures, hiding the fetch helpers
+    pub fn render_interface(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
+        out.push_str(&format!("module {}Client: {{\n", self.name));
+        out.push_str(&self.render_allow_deprecated());
+        out.push_str(&self.render_client_types());
+        out.push_str("  let defaultConfig: config\n");
+        out.push_str("  let serviceName: string\n");
+        for method in &self.methods {
+            out.push('\n');
+            out.push_str(&render_doc(method.doc.as_deref(), "  "));
+            out.push_str(&self.render_method_deprecated(method));
+            out.push_str(&self.render_method_signature(method));
+        }
+        out.push_str("}\n\n");
+
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
+        out.push_str(&format!("module {}Server: {{\n", self.name));
+        out.push_str(&self.render_allow_deprecated());
+        out.push_str(&self.render_server_types());
+        out.push_str("  let statusToCode: grpcStatus => int\n");
+        out.push_str("  let errorToJson: serverError => Js.Json.t\n");
+        out.push_str("  let errorHeaders: serverError => array<(string, string)>\n");
+        out.push_str("  let methodNames: array<string>\n");
+        out.push_str("  let handleRequest: (\n");
+        out.push_str("    ~service: service,\n");
+        out.push_str("    ~method: string,\n");
+        out.push_str("    ~body: Js.Json.t,\n");
+        out.push_str("    ~context: context,\n");
+        out.push_str("  ) => promise<result<Js.Json.t, serverError>>\n");
+        out.push_str("  let respond: (\n");
+        out.push_str("    ~service: service,\n");
+        out.push_str("    ~method: string,\n");
+        out.push_str("    ~body: Js.Json.t,\n");
+        out.push_str("    ~context: context,\n");
+        out.push_str("  ) => promise<response>\n");
+        if self.methods.iter().any(|m| m.server_streaming) {
+            out.push_str("  let handleStreamingRequest: (\n");
+            out.push_str("    ~service: service,\n");
+            out.push_str("    ~method: string,\n");
+            out.push_str("    ~body: Js.Json.t,\n");
+            out.push_str("    ~context: context,\n");
+            out.push_str("    ~writer: streamWriter<Js.Json.t>,\n");
+            out.push_str("  ) => unit\n");
+        }
+        out.push_str("  let isStreamingMethod: string => bool\n");
+        out.push_str("}\n");
+
+        out
+    }
+
+    /// Signature of a generated client function
+    fn render_method_signature(&self, method: &MethodInfo) -> String {
+        let request = if method.client_streaming {
+            format!("~requests: array<{}.t>", method.input_type)
+        } else {
+            format!("~request: {}.t", method.input_type)
+        };
+
+        if method.server_streaming {
+            format!(
+                "  let {}: (\n    ~config: config=?,\n    {},\n    ~handler: streamHandler<{}.t>,\n  ) => streamCancel\n",
+                method.function_name.clone(),
+                request,
+                method.output_type
+            )
+        } else if method.client_streaming {
+            format!(
+                "  let {}: (\n    ~config: config=?,\n    {},\n  ) => promise<result<{}.t, error>>\n",
+                method.function_name.clone(),
+                request,
+                method.output_type
+            )
+        } else {
+            format!(
+                "  let {}: (\n    ~config: config=?,\n    {},\n    ~timeoutMs: int=?,\n    ~signal: Fetch.AbortSignal.t=?,\n  ) => promise<result<{}.t, error>>\n",
+                method.function_name.clone(),
+                request,
+                method.output_type
+            )
+        }
+    }
+
+    /// Client types, shared by the implementation and interface
+    fn render_client_types(&self) -> String {
+        let mut out = String::new();
+
+        // Error type
+        out.push_str("  type error =\n");
+        out.push_str("    | NetworkError(string)\n");
+        out.push_str("    | GrpcError(int, string)\n");
+        out.push_str("    // google.rpc.Status from grpc-status-details-bin or an error JSON body\n");
+        out.push_str("    | StatusError(RpcStatus.t)\n");
+        out.push_str("    | DecodeError(string)\n");
+        out.push_str("    // The deadline passed before the response arrived\n");
+        out.push_str("    | DeadlineExceeded\n");
+        out.push_str("    // Aborted through the caller's signal\n");
+        out.push_str("    | Cancelled\n\n");
+
+        // Stream callback type for server streaming
+        out.push_str("  // Stream handler for server-streaming RPCs\n");
+        out.push_str("  type streamHandler<'a> = {\n");
+        out.push_str("    onMessage: 'a => unit,\n");
+        out.push_str("    onError: error => unit,\n");
+        out.push_str("    onComplete: unit => unit,\n");
+        out.push_str("  }\n\n");
+
+        // Stream cancellation type
+        out.push_str("  // Cancellation handle for streaming RPCs\n");
+        out.push_str("  type streamCancel = {\n");
+        out.push_str("    cancel: unit => unit,\n");
+        out.push_str("  }\n\n");
+
+        out.push_str("  // Wraps calls to rewrite metadata, transform results or wrap stream handlers\n");
+        out.push_str("  type interceptor = Interceptor.t<error, streamHandler<Js.Json.t>, streamCancel>\n\n");
+
+        // Client configuration type
+        out.push_str("  // gRPC-web client configuration\n");
+        out.push_str("  type config = {\n");
+        out.push_str("    baseUrl: string,\n");
+        out.push_str("    headers: option<Js.Dict.t<string>>,\n");
+        out.push_str("    // Run around every call, the first one outermost\n");
+        out.push_str("    interceptors?: array<interceptor>,\n");
+        out.push_str("    // Default deadline of unary calls, sent as grpc-timeout\n");
+        out.push_str("    timeoutMs?: int,\n");
+        out.push_str("    // Retries of failed unary calls, see Retry.fromServiceConfig\n");
+        out.push_str("    retry?: Retry.config,\n");
+        out.push_str("  }\n\n");
+
+        out
+    }
+
+    /// Server types, shared by the implementation and interface
+    fn render_server_types(&self) -> String {
+        let mut out = String::new();
+
+        // Error type for server errors
+        out.push_str("  // Server error types\n");
+        out.push_str("  type grpcStatus =\n");
+        out.push_str("    | Ok\n");
+        out.push_str("    | Cancelled\n");
+        out.push_str("    | Unknown\n");
+        out.push_str("    | InvalidArgument\n");
+        out.push_str("    | DeadlineExceeded\n");
+        out.push_str("    | NotFound\n");
+        out.push_str("    | AlreadyExists\n");
+        out.push_str("    | PermissionDenied\n");
+        out.push_str("    | ResourceExhausted\n");
+        out.push_str("    | FailedPrecondition\n");
+        out.push_str("    | Aborted\n");
+        out.push_str("    | OutOfRange\n");
+        out.push_str("    | Unimplemented\n");
+        out.push_str("    | Internal\n");
+        out.push_str("    | Unavailable\n");
+        out.push_str("    | DataLoss\n");
+        out.push_str("    | Unauthenticated\n\n");
+
+        // Request context type
+        out.push_str("  // Request context with metadata\n");
+        out.push_str("  type context = {\n");
+        out.push_str("    headers: Js.Dict.t<string>,\n");
+        out.push_str("    metadata: Js.Dict.t<string>,\n");
+        out.push_str("  }\n\n");
+
+        // Server error result type
+        out.push_str("  type serverError = {\n");
+        out.push_str("    status: grpcStatus,\n");
+        out.push_str("    message: string,\n");
+        out.push_str("    // Typed google.rpc error details, e.g. BadRequest field violations\n");
+        out.push_str("    details?: array<RpcStatus.detail>,\n");
+        out.push_str("  }\n\n");
+
+        // Stream writer for server-streaming responses
+        out.push_str("  // Stream writer for server-streaming responses\n");
+        out.push_str("  type streamWriter<'a> = {\n");
+        out.push_str("    send: 'a => promise<unit>,\n");
+        out.push_str("    complete: unit => unit,\n");
+        out.push_str("    error: serverError => unit,\n");
+        out.push_str("  }\n\n");
+
+        // Response of the unary and client-streaming path
+        out.push_str("  // Trailers (grpc-status, ...) and JSON body of a handled request\n");
+        out.push_str("  type response = {\n");
+        out.push_str("    headers: array<(string, string)>,\n");
+        out.push_str("    body: Js.Json.t,\n");
+        out.push_str("  }\n\n");
+
+        // Generate handler types for each method
+        out.push_str("  // Handler type definitions\n");
+        for method in &self.methods {
+            out.push_str(&self.render_server_handler_type(method));
+        }
+        out.push('\n');
+
+        // Generate the service definition type (all handlers together)
+        out.push_str("  // Service implementation type\n");
+        out.push_str("  type service = {\n");
+        for method in &self.methods {
+            let handler_name = method.function_name.clone();
+            out.push_str(&format!("    {}: {}Handler,\n", handler_name, handler_name));
+        }
+        out.push_str("  }\n\n");
+
+        out
+    }
+
     fn render_method(&self, method: &MethodInfo) -> String {
         if method.server_streaming && !method.client_streaming {
             self.render_server_streaming_method(method)
@@ -773,14 +1674,18 @@ impl ServiceTemplate {
 
         // Method documentation comment
         out.push_str(&format!("  // {} RPC (unary)\n", method.name));
+        out.push_str(&render_doc(method.doc.as_deref(), "  "));
+        out.push_str(&self.render_method_deprecated(method));
 
         // Method signature
         out.push_str(&format!(
             "  let {} = async (\n",
-            to_camel_case(&method.name)
+            method.function_name.clone()
         ));
         out.push_str("    ~config: config=defaultConfig,\n");
         out.push_str(&format!("    ~request: {}.t,\n", method.input_type));
+        out.push_str("    ~timeoutMs: int=?,\n");
+        out.push_str("    ~signal: Fetch.AbortSignal.t=?,\n");
         out.push_str(&format!("  ): result<{}.t, error> => {{\n", method.output_type));
 
         // Encode request
@@ -792,8 +1697,9 @@ impl ServiceTemplate {
         // Make the call
         let rpc_path = format!("{}/{}", self.name, method.name);
         out.push_str(&format!(
-            "    let response = await call(~config, ~method=\"{}\", ~request=requestJson)\n",
-            rpc_path
+            "    let response = await call(~config, ~method=\"{}\", ~request=requestJson, ~timeoutMs?, ~signal?{})\n",
+            rpc_path,
+            if method.idempotent { ", ~idempotent=true" } else { "" }
         ));
 
         // Decode response
@@ -818,11 +1724,13 @@ impl ServiceTemplate {
 
         // Method documentation comment
         out.push_str(&format!("  // {} RPC (server streaming)\n", method.name));
+        out.push_str(&render_doc(method.doc.as_deref(), "  "));
+        out.push_str(&self.render_method_deprecated(method));
 
         // Method signature - takes a handler for stream events
         out.push_str(&format!(
             "  let {} = (\n",
-            to_camel_case(&method.name)
+            method.function_name.clone()
         ));
         out.push_str("    ~config: config=defaultConfig,\n");
         out.push_str(&format!("    ~request: {}.t,\n", method.input_type));
@@ -864,9 +1772,11 @@ impl ServiceTemplate {
 
         // Client streaming is limited in gRPC-web, but we can simulate with array
         out.push_str(&format!("  // {} RPC (client streaming - batch mode)\n", method.name));
+        out.push_str(&render_doc(method.doc.as_deref(), "  "));
+        out.push_str(&self.render_method_deprecated(method));
         out.push_str(&format!(
             "  let {} = async (\n",
-            to_camel_case(&method.name)
+            method.function_name.clone()
         ));
         out.push_str("    ~config: config=defaultConfig,\n");
         out.push_str(&format!("    ~requests: array<{}.t>,\n", method.input_type));
@@ -906,9 +1816,11 @@ impl ServiceTemplate {
         // Bidirectional streaming is very limited in gRPC-web
         // We simulate with batch request + streaming response
         out.push_str(&format!("  // {} RPC (bidirectional streaming - batch/stream mode)\n", method.name));
+        out.push_str(&render_doc(method.doc.as_deref(), "  "));
+        out.push_str(&self.render_method_deprecated(method));
         out.push_str(&format!(
             "  let {} = (\n",
-            to_camel_case(&method.name)
+            method.function_name.clone()
         ));
         out.push_str("    ~config: config=defaultConfig,\n");
         out.push_str(&format!("    ~requests: array<{}.t>,\n", method.input_type));
@@ -947,28 +1859,10 @@ impl ServiceTemplate {
     pub fn render_server(&self) -> String {
         let mut out = String::new();
 
+        out.push_str(&render_doc(self.doc.as_deref(), ""));
         out.push_str(&format!("module {}Server = {{\n", self.name));
-
-        // Error type for server errors
-        out.push_str("  // Server error types\n");
-        out.push_str("  type grpcStatus =\n");
-        out.push_str("    | Ok\n");
-        out.push_str("    | Cancelled\n");
-        out.push_str("    | Unknown\n");
-        out.push_str("    | InvalidArgument\n");
-        out.push_str("    | DeadlineExceeded\n");
-        out.push_str("    | NotFound\n");
-        out.push_str("    | AlreadyExists\n");
-        out.push_str("    | PermissionDenied\n");
-        out.push_str("    | ResourceExhausted\n");
-        out.push_str("    | FailedPrecondition\n");
-        out.push_str("    | Aborted\n");
-        out.push_str("    | OutOfRange\n");
-        out.push_str("    | Unimplemented\n");
-        out.push_str("    | Internal\n");
-        out.push_str("    | Unavailable\n");
-        out.push_str("    | DataLoss\n");
-        out.push_str("    | Unauthenticated\n\n");
+        out.push_str(&self.render_allow_deprecated());
+        out.push_str(&self.render_server_types());
 
         out.push_str("  let statusToCode = (status: grpcStatus): int => {\n");
         out.push_str("    switch status {\n");
@@ -992,42 +1886,16 @@ impl ServiceTemplate {
         out.push_str("    }\n");
         out.push_str("  }\n\n");
 
-        // Request context type
-        out.push_str("  // Request context with metadata\n");
-        out.push_str("  type context = {\n");
-        out.push_str("    headers: Js.Dict.t<string>,\n");
-        out.push_str("    metadata: Js.Dict.t<string>,\n");
-        out.push_str("  }\n\n");
-
-        // Server error result type
-        out.push_str("  type serverError = {\n");
-        out.push_str("    status: grpcStatus,\n");
-        out.push_str("    message: string,\n");
-        out.push_str("  }\n\n");
-
-        // Stream writer for server-streaming responses
-        out.push_str("  // Stream writer for server-streaming responses\n");
-        out.push_str("  type streamWriter<'a> = {\n");
-        out.push_str("    send: 'a => promise<unit>,\n");
-        out.push_str("    complete: unit => unit,\n");
-        out.push_str("    error: serverError => unit,\n");
-        out.push_str("  }\n\n");
-
-        // Generate handler types for each method
-        out.push_str("  // Handler type definitions\n");
-        for method in &self.methods {
-            out.push_str(&self.render_server_handler_type(method));
-        }
-        out.push('\n');
-
-        // Generate the service definition type (all handlers together)
-        out.push_str("  // Service implementation type\n");
-        out.push_str("  type service = {\n");
-        for method in &self.methods {
-            let handler_name = to_camel_case(&method.name);
-            out.push_str(&format!("    {}: {}Handler,\n", handler_name, handler_name));
-        }
+        out.push_str("  let errorStatus = (error: serverError): RpcStatus.t => {\n");
+        out.push_str("    code: statusToCode(error.status),\n");
+        out.push_str("    message: error.message,\n");
+        out.push_str("    details: error.details->Option.getOr([]),\n");
         out.push_str("  }\n\n");
+        out.push_str("  // google.rpc.Status JSON body for an error response\n");
+        out.push_str("  let errorToJson = (error: serverError): Js.Json.t => RpcStatus.toJson(errorStatus(error))\n\n");
+        out.push_str("  // grpc-status, grpc-message and grpc-status-details-bin for an error response\n");
+        out.push_str("  let errorHeaders = (error: serverError): array<(string, string)> =>\n");
+        out.push_str("    RpcStatus.toHeaders(errorStatus(error))\n\n");
 
         // Generate route handler
         out.push_str("  // Route method name to handler\n");
@@ -1052,17 +1920,13 @@ impl ServiceTemplate {
             // Only handle unary and client-streaming here (single response)
             if !method.server_streaming {
                 let rpc_path = format!("{}/{}", self.name, method.name);
-                let handler_name = to_camel_case(&method.name);
+                let handler_name = method.function_name.clone();
                 out.push_str(&format!("    | \"{}\" =>\n", rpc_path));
 
                 if method.client_streaming {
                     // Client streaming expects array of requests
-                    out.push_str(&format!(
-                        "      switch Js.Json.decodeArray(body) {{\n"
-                    ));
-                    out.push_str(&format!(
-                        "      | Some(arr) =>\n"
-                    ));
+                    out.push_str("      switch Js.Json.decodeArray(body) {\n");
+                    out.push_str("      | Some(arr) =>\n");
                     out.push_str(&format!(
                         "        let requests = Array.filterMap(arr, {}.fromJson)\n",
                         method.input_type
@@ -1086,9 +1950,7 @@ impl ServiceTemplate {
                         "      switch {}.fromJson(body) {{\n",
                         method.input_type
                     ));
-                    out.push_str(&format!(
-                        "      | Some(request) =>\n"
-                    ));
+                    out.push_str("      | Some(request) =>\n");
                     out.push_str(&format!(
                         "        let response = await service.{}(~request, ~context)\n",
                         handler_name
@@ -1110,6 +1972,19 @@ impl ServiceTemplate {
         out.push_str("    }\n");
         out.push_str("  }\n\n");
 
+        out.push_str("  // handleRequest with the trailers and body to send back\n");
+        out.push_str("  let respond = async (\n");
+        out.push_str("    ~service: service,\n");
+        out.push_str("    ~method: string,\n");
+        out.push_str("    ~body: Js.Json.t,\n");
+        out.push_str("    ~context: context,\n");
+        out.push_str("  ): response => {\n");
+        out.push_str("    switch await handleRequest(~service, ~method, ~body, ~context) {\n");
+        out.push_str("    | Ok(body) => {headers: [(\"grpc-status\", \"0\")], body}\n");
+        out.push_str("    | Error(error) => {headers: errorHeaders(error), body: errorToJson(error)}\n");
+        out.push_str("    }\n");
+        out.push_str("  }\n\n");
+
         // Generate streaming request handler
         let has_streaming = self.methods.iter().any(|m| m.server_streaming);
         if has_streaming {
@@ -1126,7 +2001,7 @@ impl ServiceTemplate {
             for method in &self.methods {
                 if method.server_streaming {
                     let rpc_path = format!("{}/{}", self.name, method.name);
-                    let handler_name = to_camel_case(&method.name);
+                    let handler_name = method.function_name.clone();
                     out.push_str(&format!("    | \"{}\" =>\n", rpc_path));
 
                     // Create typed stream writer
@@ -1199,7 +2074,7 @@ impl ServiceTemplate {
 
     fn render_server_handler_type(&self, method: &MethodInfo) -> String {
         let mut out = String::new();
-        let handler_name = to_camel_case(&method.name);
+        let handler_name = method.function_name.clone();
 
         if method.server_streaming && method.client_streaming {
             // Bidirectional streaming
@@ -1231,36 +2106,135 @@ impl ServiceTemplate {
     }
 }
 
-/// Capitalize the first letter of a string
-fn capitalize_first(name: &str) -> String {
-    let mut chars = name.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(c) => c.to_uppercase().collect::<String>() + chars.as_str(),
+/// Js.* bindings used by templates, or common in `(rescript.type)` codecs,
+/// and their @rescript/core equivalents
+const CORE_BINDINGS: &[(&str, &str)] = &[
+    ("Js.Typed_array.Uint8Array.make", "Uint8Array.fromArray"),
+    ("Js.Typed_array.Uint8Array.t", "Uint8Array.t"),
+    ("Js.TypedArray2.Uint8Array.make", "Uint8Array.fromArray"),
+    ("Js.TypedArray2.Uint8Array.t", "Uint8Array.t"),
+    ("Js.Dict.t", "Dict.t"),
+    ("Js.Dict.empty", "Dict.make"),
+    ("Js.Dict.fromArray", "Dict.fromArray"),
+    ("Js.Dict.entries", "Dict.toArray"),
+    ("Js.Dict.get", "Dict.get"),
+    ("Js.Dict.set", "Dict.set"),
+    ("Js.Dict.keys", "Dict.keysToArray"),
+    ("Js.Dict.values", "Dict.valuesToArray"),
+    ("Js.Json.t", "JSON.t"),
+    ("Js.Json.stringify", "JSON.stringify"),
+    ("Js.Json.parseExn", "JSON.parseExn"),
+    ("Js.Json.classify", "JSON.Classify.classify"),
+    ("Js.Json.string", "JSON.Encode.string"),
+    ("Js.Json.number", "JSON.Encode.float"),
+    ("Js.Json.boolean", "JSON.Encode.bool"),
+    ("Js.Json.array", "JSON.Encode.array"),
+    ("Js.Json.object_", "JSON.Encode.object"),
+    ("Js.Json.null", "JSON.Encode.null"),
+    ("Js.Json.decodeString", "JSON.Decode.string"),
+    ("Js.Json.decodeNumber", "JSON.Decode.float"),
+    ("Js.Json.decodeBoolean", "JSON.Decode.bool"),
+    ("Js.Json.decodeArray", "JSON.Decode.array"),
+    ("Js.Json.decodeObject", "JSON.Decode.object"),
+    ("Js.Json.decodeNull", "JSON.Decode.null"),
+    ("Js.Date.t", "Date.t"),
+    ("Js.Date.fromFloat", "Date.fromTime"),
+    ("Js.Date.fromString", "Date.fromString"),
+    ("Js.Date.getTime", "Date.getTime"),
+    ("Js.Date.toISOString", "Date.toISOString"),
+    ("Js.Null.t", "Null.t"),
+    ("Js.Null.empty", "Null.null"),
+    ("Js.Null.return", "Null.make"),
+    ("Js.Nullable.t", "Nullable.t"),
+    ("Js.Promise.t", "promise"),
+    ("Js.Exn.raiseError", "Exn.raiseError"),
+    ("Js.log", "Console.log"),
+    ("Js.log2", "Console.log2"),
+];
+
+/// Rewrite generated code to use @rescript/core instead of Js.* bindings
+///
+/// Only whole paths are replaced, so `Js.Json.t` never matches inside a longer identifier.
+pub fn to_core_bindings(code: &str) -> String {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(code.len());
+    let mut rest = code;
+
+    while let Some(pos) = rest.find("Js.") {
+        let (before, candidate) = rest.split_at(pos);
+        out.push_str(before);
+
+        let at_boundary = !out.ends_with(|c: char| is_ident(c) || c == '.');
+        let binding = CORE_BINDINGS.iter().find(|(js, _)| {
+            candidate.starts_with(js) && !candidate[js.len()..].starts_with(is_ident)
+        });
+
+        match binding {
+            Some((js, core)) if at_boundary => {
+                out.push_str(core);
+                rest = &candidate[js.len()..];
+            }
+            _ => {
+                out.push_str("Js.");
+                rest = &candidate[3..];
+            }
+        }
     }
+    out.push_str(rest);
+
+    out
 }
 
-/// Convert PascalCase or snake_case to camelCase
-fn to_camel_case(name: &str) -> String {
-    let mut result = String::new();
-    let mut capitalize_next = false;
-    let mut first = true;
-
-    for c in name.chars() {
-        if c == '_' {
-            capitalize_next = true;
-        } else if first {
-            result.push(c.to_ascii_lowercase());
-            first = false;
-        } else if capitalize_next {
-            result.push(c.to_ascii_uppercase());
-            capitalize_next = false;
+/// Render a proto comment as a `/** ... */` doc comment at the given indent
+fn render_doc(doc: Option<&str>, indent: &str) -> String {
+    let Some(doc) = doc else {
+        return String::new();
+    };
+
+    // protoc keeps the space after `//`, so drop one leading space per line.
+    // ReScript comments nest, so both delimiters are escaped.
+    let lines: Vec<String> = doc
+        .lines()
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(line)
+                .trim_end()
+                .replace("/*", "/\\*")
+                .replace("*/", "*\\/")
+        })
+        .collect();
+    let first = lines.iter().position(|l| !l.is_empty());
+    let last = lines.iter().rposition(|l| !l.is_empty());
+    let lines = match (first, last) {
+        (Some(first), Some(last)) => &lines[first..=last],
+        _ => return String::new(),
+    };
+
+    if let [line] = lines {
+        return format!("{}/** {} */\n", indent, line);
+    }
+
+    let mut out = format!("{}/**\n", indent);
+    for line in lines {
+        if line.is_empty() {
+            out.push_str(&format!("{} *\n", indent));
         } else {
-            result.push(c);
+            out.push_str(&format!("{} * {}\n", indent, line));
         }
     }
+    out.push_str(&format!("{} */\n", indent));
+    out
+}
+
+/// Silence deprecation warnings inside a generated module whose code uses
+/// deprecated definitions; code using the module still gets the warnings
+fn render_allow_deprecated() -> String {
+    "  @@warning(\"-3\")\n\n".to_string()
+}
 
-    result
+/// Render a `@deprecated` attribute for a proto element marked `deprecated = true`
+fn render_deprecated(name: &str, indent: &str) -> String {
+    format!("{}@deprecated(\"{} is deprecated in the proto schema\")\n", indent, name)
 }
 
 #[cfg(test)]
@@ -1280,6 +2254,10 @@ mod tests {
             is_enum: false,
             oneof_index: None,
             well_known_type: None,
+            custom_codec: None,
+            doc: None,
+            deprecated: false,
+            deprecated_type: false,
         };
         assert_eq!(field.full_type(), "array<string>");
 
@@ -1304,6 +2282,10 @@ mod tests {
             is_enum: false,
             oneof_index: None,
             well_known_type: None,
+            custom_codec: None,
+            doc: None,
+            deprecated: false,
+            deprecated_type: false,
         };
         assert_eq!(string_field.json_encoder(), "Json.Encode.string");
 
@@ -1318,6 +2300,10 @@ mod tests {
             is_enum: true,
             oneof_index: None,
             well_known_type: None,
+            custom_codec: None,
+            doc: None,
+            deprecated: false,
+            deprecated_type: false,
         };
         assert_eq!(enum_field.json_encoder(), "v => Json.Encode.int(Status.toInt(v))");
 
@@ -1332,6 +2318,10 @@ mod tests {
             is_enum: false,
             oneof_index: None,
             well_known_type: None,
+            custom_codec: None,
+            doc: None,
+            deprecated: false,
+            deprecated_type: false,
         };
         assert_eq!(msg_field.json_encoder(), "Address.toJson");
 
@@ -1347,6 +2337,10 @@ mod tests {
             is_enum: false,
             oneof_index: None,
             well_known_type: Some(".google.protobuf.Timestamp".to_string()),
+            custom_codec: None,
+            doc: None,
+            deprecated: false,
+            deprecated_type: false,
         };
         assert_eq!(timestamp_field.json_encoder(), "WellKnown.Timestamp.toJson");
     }
@@ -1356,14 +2350,66 @@ mod tests {
         let template = EnumTemplate {
             name: "Status".to_string(),
             variants: vec![
-                ("Unknown".to_string(), 0),
-                ("Active".to_string(), 1),
-                ("Inactive".to_string(), 2),
+                EnumVariant {
+                    name: "Unknown".to_string(),
+                    proto_name: "UNKNOWN".to_string(),
+                    number: 0,
+                    doc: None,
+                    deprecated: false,
+                },
+                EnumVariant {
+                    name: "Active".to_string(),
+                    proto_name: "ACTIVE".to_string(),
+                    number: 1,
+                    doc: Some(" Currently active\n".to_string()),
+                    deprecated: false,
+                },
+                EnumVariant {
+                    name: "Inactive".to_string(),
+                    proto_name: "INACTIVE".to_string(),
+                    number: 2,
+                    doc: None,
+                    deprecated: true,
+                },
             ],
+            doc: None,
+            deprecated: false,
         };
         let output = template.render();
         assert!(output.contains("module Status"));
-        assert!(output.contains("#Active"));
+        assert!(output.contains("    /** Currently active */\n    | #Active\n"));
         assert!(output.contains("| 1 => Some(#Active)"));
+        assert!(output.contains(
+            "  @deprecated(\"Status.INACTIVE is deprecated in the proto schema\")\n  let inactive: t = #Inactive\n"
+        ));
+    }
+
+    #[test]
+    fn test_to_core_bindings() {
+        assert_eq!(
+            to_core_bindings("let toJson = (msg: t): Js.Json.t => Js.Json.object_(Js.Dict.empty())"),
+            "let toJson = (msg: t): JSON.t => JSON.Encode.object(Dict.make())"
+        );
+        assert_eq!(
+            to_core_bindings("bytes: Js.Typed_array.Uint8Array.t, at: option<Js.Date.t>"),
+            "bytes: Uint8Array.t, at: option<Date.t>"
+        );
+        assert_eq!(
+            to_core_bindings("Js.log(Js.Json.decodeString(Js.Json.string(\"x\")))"),
+            "Console.log(JSON.Decode.string(JSON.Encode.string(\"x\")))"
+        );
+        // Bindings without a core equivalent and longer identifiers are left alone
+        assert_eq!(to_core_bindings("Js.Vector.t, Js.logMany(x), MyJs.Dict.t"), "Js.Vector.t, Js.logMany(x), MyJs.Dict.t");
+    }
+
+    #[test]
+    fn test_render_doc() {
+        assert_eq!(render_doc(None, "  "), "");
+        assert_eq!(render_doc(Some(" \n"), "  "), "");
+        assert_eq!(render_doc(Some(" A user.\n"), "  "), "  /** A user. */\n");
+        assert_eq!(
+            render_doc(Some(" First line.\n\n Uses a/*b*/c\n"), ""),
+            "/**\n * First line.\n *\n * Uses a/\\*b*\\/c\n */\n"
+        );
     }
 }
@@ -0,0 +1,5 @@
+#!/bin/sh
+# SPDX-License-Identifier: MPL-2.0
+# protoc plugin that saves the raw CodeGeneratorRequest to $REQUEST_OUT.
+# Writing nothing to stdout is a valid, empty CodeGeneratorResponse.
+cat > "$REQUEST_OUT"
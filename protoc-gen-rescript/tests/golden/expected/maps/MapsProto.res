@@ -0,0 +1,144 @@
+// Generated from maps.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: fixtures.maps
+
+module Level = {
+  type t = [
+    | #LevelUnspecified
+    | #LevelHigh
+  ]
+
+  let toInt = (v: t): int => {
+    switch v {
+    | #LevelUnspecified => 0
+    | #LevelHigh => 1
+    }
+  }
+
+  let fromInt = (i: int): option<t> => {
+    switch i {
+    | 0 => Some(#LevelUnspecified)
+    | 1 => Some(#LevelHigh)
+    | _ => None
+    }
+  }
+}
+
+
+module Item = {
+  type t = {
+    name: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~name,
+    ~unknownFields=?
+  ): t => {
+    name,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (name) {
+        | (Ok(name)) =>
+          Some({
+            name,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Map fields of every value kind */
+module Inventory = {
+  type t = {
+    counts: array<CountsEntry.t>,
+    itemsById: array<ItemsByIdEntry.t>,
+    levels: array<LevelsEntry.t>,
+    blobs: array<BlobsEntry.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~counts=[],
+    ~itemsById=[],
+    ~levels=[],
+    ~blobs=[],
+    ~unknownFields=?
+  ): t => {
+    counts,
+    itemsById,
+    levels,
+    blobs,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+      ],
+      [
+        Json.Encode.repeated("counts", msg.counts, v => CountsEntry.toJsonWith(v, ~unknownFields)),
+        Json.Encode.repeated("items_by_id", msg.itemsById, v => ItemsByIdEntry.toJsonWith(v, ~unknownFields)),
+        Json.Encode.repeated("levels", msg.levels, v => LevelsEntry.toJsonWith(v, ~unknownFields)),
+        Json.Encode.repeated("blobs", msg.blobs, v => BlobsEntry.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let counts = Json.Decode.repeated(obj, "counts", CountsEntry.fromJson)->Result.getOr([])
+        let itemsById = Json.Decode.repeated(obj, "items_by_id", ItemsByIdEntry.fromJson)->Result.getOr([])
+        let levels = Json.Decode.repeated(obj, "levels", LevelsEntry.fromJson)->Result.getOr([])
+        let blobs = Json.Decode.repeated(obj, "blobs", BlobsEntry.fromJson)->Result.getOr([])
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        Some({
+          counts,
+          itemsById,
+          levels,
+          blobs,
+          unknownFields: ?unknownFields,
+        })
+    | None => None
+    }
+  }
+}
+
+
@@ -0,0 +1,241 @@
+// Generated from nested.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: fixtures.nested
+
+module Outer = {
+  /** Nested enum */
+  module Kind = {
+    type t = [
+      | #KindUnspecified
+      | #KindInner
+    ]
+  
+    let toInt = (v: t): int => {
+      switch v {
+      | #KindUnspecified => 0
+      | #KindInner => 1
+      }
+    }
+  
+    let fromInt = (i: int): option<t> => {
+      switch i {
+      | 0 => Some(#KindUnspecified)
+      | 1 => Some(#KindInner)
+      | _ => None
+      }
+    }
+  }
+
+  module Middle = {
+    module Inner = {
+      type t = {
+        depth: int,
+        unknownFields?: UnknownFields.t,
+      }
+    
+      let make = (
+        ~depth,
+        ~unknownFields=?
+      ): t => {
+        depth,
+        unknownFields: ?unknownFields,
+      }
+    
+      // JSON serialization
+      // Unknown fields are kept under "$unknown" only for the WASM codec,
+      // proto3 JSON has no representation for them
+      let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+        Json.Encode.object(Json.Encode.fields(
+          [
+            Json.Encode.required("depth", msg.depth, Json.Encode.int),
+          ],
+          [
+            unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+          ],
+        ))
+      }
+    
+      let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+    
+      // JSON deserialization
+      let fromJson = (json: Js.Json.t): option<t> => {
+        switch Json.Decode.object(json) {
+        | Some(obj) =>
+            let depth = Json.Decode.required(obj, "depth", Json.Decode.int)
+            let unknownFields = UnknownFields.fromJsonObject(obj)
+            switch (depth) {
+            | (Ok(depth)) =>
+              Some({
+                depth,
+                unknownFields: ?unknownFields,
+              })
+            | _ => None
+            }
+        | None => None
+        }
+      }
+    }
+  
+    type t = {
+      inner: option<Inner.t>,
+      inners: array<Inner.t>,
+      unknownFields?: UnknownFields.t,
+    }
+  
+    let make = (
+      ~inner=?,
+      ~inners=[],
+      ~unknownFields=?
+    ): t => {
+      inner,
+      inners,
+      unknownFields: ?unknownFields,
+    }
+  
+    // JSON serialization
+    // Unknown fields are kept under "$unknown" only for the WASM codec,
+    // proto3 JSON has no representation for them
+    let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+      Json.Encode.object(Json.Encode.fields(
+        [
+        ],
+        [
+          Json.Encode.optional("inner", msg.inner, v => Inner.toJsonWith(v, ~unknownFields)),
+          Json.Encode.repeated("inners", msg.inners, v => Inner.toJsonWith(v, ~unknownFields)),
+          unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+        ],
+      ))
+    }
+  
+    let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+  
+    // JSON deserialization
+    let fromJson = (json: Js.Json.t): option<t> => {
+      switch Json.Decode.object(json) {
+      | Some(obj) =>
+          let inner = Json.Decode.optional(obj, "inner", Inner.fromJson)->Result.getOr(None)
+          let inners = Json.Decode.repeated(obj, "inners", Inner.fromJson)->Result.getOr([])
+          let unknownFields = UnknownFields.fromJsonObject(obj)
+          Some({
+            inner,
+            inners,
+            unknownFields: ?unknownFields,
+          })
+      | None => None
+      }
+    }
+  }
+
+  type t = {
+    kind: Kind.t,
+    middle: option<Middle.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~kind,
+    ~middle=?,
+    ~unknownFields=?
+  ): t => {
+    kind,
+    middle,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("kind", msg.kind, v => Json.Encode.int(Kind.toInt(v))),
+      ],
+      [
+        Json.Encode.optional("middle", msg.middle, v => Middle.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let kind = Json.Decode.required(obj, "kind", json => Json.Decode.int(json)->Option.flatMap(Kind.fromInt))
+        let middle = Json.Decode.optional(obj, "middle", Middle.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (kind) {
+        | (Ok(kind)) =>
+          Some({
+            kind,
+            middle,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+module Sibling = {
+  type t = {
+    inner: option<Outer.Middle.Inner.t>,
+    kind: Outer.Kind.t,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~inner=?,
+    ~kind,
+    ~unknownFields=?
+  ): t => {
+    inner,
+    kind,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("kind", msg.kind, v => Json.Encode.int(Outer.Kind.toInt(v))),
+      ],
+      [
+        Json.Encode.optional("inner", msg.inner, v => Outer.Middle.Inner.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let inner = Json.Decode.optional(obj, "inner", Outer.Middle.Inner.fromJson)->Result.getOr(None)
+        let kind = Json.Decode.required(obj, "kind", json => Json.Decode.int(json)->Option.flatMap(Outer.Kind.fromInt))
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (kind) {
+        | (Ok(kind)) =>
+          Some({
+            inner,
+            kind,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
@@ -0,0 +1,205 @@
+// Generated from proto2.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: fixtures.legacy
+
+module Priority = {
+  type t = [
+    | #Low
+    | #High
+  ]
+
+  let toInt = (v: t): int => {
+    switch v {
+    | #Low => 0
+    | #High => 1
+    }
+  }
+
+  let fromInt = (i: int): option<t> => {
+    switch i {
+    | 0 => Some(#Low)
+    | 1 => Some(#High)
+    | _ => None
+    }
+  }
+}
+
+
+module Task = {
+  @@warning("-3")
+
+  module Step = {
+    type t = {
+      name: string,
+      done: bool,
+      unknownFields?: UnknownFields.t,
+    }
+  
+    let make = (
+      ~name,
+      ~done,
+      ~unknownFields=?
+    ): t => {
+      name,
+      done,
+      unknownFields: ?unknownFields,
+    }
+  
+    // JSON serialization
+    // Unknown fields are kept under "$unknown" only for the WASM codec,
+    // proto3 JSON has no representation for them
+    let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+      Json.Encode.object(Json.Encode.fields(
+        [
+          Json.Encode.required("name", msg.name, Json.Encode.string),
+          Json.Encode.required("done", msg.done, Json.Encode.bool),
+        ],
+        [
+          unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+        ],
+      ))
+    }
+  
+    let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+  
+    // JSON deserialization
+    let fromJson = (json: Js.Json.t): option<t> => {
+      switch Json.Decode.object(json) {
+      | Some(obj) =>
+          let name = Json.Decode.required(obj, "name", Json.Decode.string)
+          let done = Json.Decode.required(obj, "done", Json.Decode.bool)
+          let unknownFields = UnknownFields.fromJsonObject(obj)
+          switch (name, done) {
+          | (Ok(name), Ok(done)) =>
+            Some({
+              name,
+              done,
+              unknownFields: ?unknownFields,
+            })
+          | _ => None
+          }
+      | None => None
+      }
+    }
+  }
+
+  type t = {
+    title: string,
+    retries: int,
+    priority: Priority.t,
+    tags: array<string>,
+    @deprecated("owner is deprecated in the proto schema")
+    owner: string,
+    step: array<Step.t>,
+    extensions: Extension.values,
+    unknownFields?: UnknownFields.t,
+  }
+
+  /** Deprecated labels: `~owner` */
+  let make = (
+    ~title,
+    ~retries,
+    ~priority,
+    ~tags=[],
+    ~owner,
+    ~step=[],
+    ~extensions=Extension.empty(),
+    ~unknownFields=?
+  ): t => {
+    title,
+    retries,
+    priority,
+    tags,
+    owner,
+    step,
+    extensions,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("title", msg.title, Json.Encode.string),
+        Json.Encode.required("retries", msg.retries, Json.Encode.int),
+        Json.Encode.required("priority", msg.priority, v => Json.Encode.int(Priority.toInt(v))),
+        Json.Encode.required("owner", msg.owner, Json.Encode.string),
+      ],
+      [
+        Json.Encode.repeated("tags", msg.tags, Json.Encode.string),
+        Json.Encode.repeated("step", msg.step, v => Step.toJsonWith(v, ~unknownFields)),
+        Extension.toJsonFields(msg.extensions),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let title = Json.Decode.required(obj, "title", Json.Decode.string)
+        let retries = Json.Decode.required(obj, "retries", Json.Decode.int)
+        let priority = Json.Decode.required(obj, "priority", json => Json.Decode.int(json)->Option.flatMap(Priority.fromInt))
+        let tags = Json.Decode.repeated(obj, "tags", Json.Decode.string)->Result.getOr([])
+        let owner = Json.Decode.required(obj, "owner", Json.Decode.string)
+        let step = Json.Decode.repeated(obj, "step", Step.fromJson)->Result.getOr([])
+        let extensions = Extension.fromJsonObject(obj, ~extendee="fixtures.legacy.Task")
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (title, retries, priority, owner) {
+        | (Ok(title), Ok(retries), Ok(priority), Ok(owner)) =>
+          Some({
+            title,
+            retries,
+            priority,
+            tags,
+            owner,
+            step,
+            extensions,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+module Extensions = {
+  // Extends fixtures.legacy.Task
+  /** Who reviewed the task */
+  let reviewer: Extension.t<string> = Extension.make(
+    ~extendee="fixtures.legacy.Task",
+    ~fieldNumber=100,
+    ~name="fixtures.legacy.reviewer",
+    ~toJson=Json.Encode.string,
+    ~fromJson=Json.Decode.string,
+    ~wasmType=WasmCodec.String,
+  )
+
+  // Extends fixtures.legacy.Task
+  let scores: Extension.t<array<int>> = Extension.make(
+    ~extendee="fixtures.legacy.Task",
+    ~fieldNumber=101,
+    ~name="fixtures.legacy.scores",
+    ~repeated=true,
+    ~toJson=Json.Encode.array(Json.Encode.int),
+    ~fromJson=Json.Decode.array(Json.Decode.int),
+    ~wasmType=WasmCodec.Int32,
+  )
+
+  // Register extensions for fromJson and the WASM codec
+  let register = () => {
+    Extension.register(reviewer)
+    Extension.register(scores)
+  }
+}
+
+
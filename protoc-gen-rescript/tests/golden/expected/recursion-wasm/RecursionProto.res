@@ -0,0 +1,188 @@
+// Generated from recursion.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: fixtures.recursion
+
+/** Self-referencing tree */
+module TreeNode = {
+  type t = {
+    value: string,
+    children: array<TreeNode.t>,
+    parent: option<TreeNode.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~value,
+    ~children=[],
+    ~parent=?,
+    ~unknownFields=?
+  ): t => {
+    value,
+    children,
+    parent,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("value", msg.value, Json.Encode.string),
+      ],
+      [
+        Json.Encode.repeated("children", msg.children, v => TreeNode.toJsonWith(v, ~unknownFields)),
+        Json.Encode.optional("parent", msg.parent, v => TreeNode.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let value = Json.Decode.required(obj, "value", Json.Decode.string)
+        let children = Json.Decode.repeated(obj, "children", TreeNode.fromJson)->Result.getOr([])
+        let parent = Json.Decode.optional(obj, "parent", TreeNode.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (value) {
+        | (Ok(value)) =>
+          Some({
+            value,
+            children,
+            parent,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // No binary codec: the WASM codec schema cannot describe recursive types
+}
+
+
+/** Mutually recursive pair */
+module Folder = {
+  type t = {
+    name: string,
+    documents: array<Document.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~name,
+    ~documents=[],
+    ~unknownFields=?
+  ): t => {
+    name,
+    documents,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+      ],
+      [
+        Json.Encode.repeated("documents", msg.documents, v => Document.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let documents = Json.Decode.repeated(obj, "documents", Document.fromJson)->Result.getOr([])
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (name) {
+        | (Ok(name)) =>
+          Some({
+            name,
+            documents,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // No binary codec: the WASM codec schema cannot describe recursive types
+}
+
+
+module Document = {
+  type t = {
+    title: string,
+    folder: option<Folder.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~title,
+    ~folder=?,
+    ~unknownFields=?
+  ): t => {
+    title,
+    folder,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("title", msg.title, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("folder", msg.folder, v => Folder.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let title = Json.Decode.required(obj, "title", Json.Decode.string)
+        let folder = Json.Decode.optional(obj, "folder", Folder.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (title) {
+        | (Ok(title)) =>
+          Some({
+            title,
+            folder,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // No binary codec: the WASM codec schema cannot describe recursive types
+}
+
+
@@ -0,0 +1,628 @@
+// Generated from services.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: fixtures.services
+
+module Ping = {
+  type t = {
+    text: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~text,
+    ~unknownFields=?
+  ): t => {
+    text,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("text", msg.text, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let text = Json.Decode.required(obj, "text", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (text) {
+        | (Ok(text)) =>
+          Some({
+            text,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+module Pong = {
+  type t = {
+    text: string,
+    receivedAt: bigint,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~text,
+    ~receivedAt,
+    ~unknownFields=?
+  ): t => {
+    text,
+    receivedAt,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("text", msg.text, Json.Encode.string),
+        Json.Encode.required("received_at", msg.receivedAt, Json.Encode.int64),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let text = Json.Decode.required(obj, "text", Json.Decode.string)
+        let receivedAt = Json.Decode.required(obj, "received_at", Json.Decode.int64)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (text, receivedAt) {
+        | (Ok(text), Ok(receivedAt)) =>
+          Some({
+            text,
+            receivedAt,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+}
+
+
+/** Every RPC shape */
+module EchoClient = {
+  type error =
+    | NetworkError(string)
+    | GrpcError(int, string)
+    // google.rpc.Status from grpc-status-details-bin or an error JSON body
+    | StatusError(RpcStatus.t)
+    | DecodeError(string)
+    // The deadline passed before the response arrived
+    | DeadlineExceeded
+    // Aborted through the caller's signal
+    | Cancelled
+
+  // Stream handler for server-streaming RPCs
+  type streamHandler<'a> = {
+    onMessage: 'a => unit,
+    onError: error => unit,
+    onComplete: unit => unit,
+  }
+
+  // Cancellation handle for streaming RPCs
+  type streamCancel = {
+    cancel: unit => unit,
+  }
+
+  // Wraps calls to rewrite metadata, transform results or wrap stream handlers
+  type interceptor = Interceptor.t<error, streamHandler<Js.Json.t>, streamCancel>
+
+  // gRPC-web client configuration
+  type config = {
+    baseUrl: string,
+    headers: option<Js.Dict.t<string>>,
+    // Run around every call, the first one outermost
+    interceptors?: array<interceptor>,
+    // Default deadline of unary calls, sent as grpc-timeout
+    timeoutMs?: int,
+    // Retries of failed unary calls, see Retry.fromServiceConfig
+    retry?: Retry.config,
+  }
+
+  let defaultConfig = {
+    baseUrl: "http://localhost:8080",
+    headers: None,
+    retry: {Retry.policy: Retry.defaultPolicy},
+  }
+
+  // Service name in gRPC service configs
+  let serviceName = "fixtures.services.Echo"
+
+  // gRPC status of a failed call, for retry policies
+  let retryStatus = (error: error): option<int> => {
+    switch error {
+    | NetworkError(_) => Some(14)
+    | GrpcError(status, _) => Some(Retry.codeOfHttpStatus(status))
+    | StatusError(status) => Some(status.code)
+    | DecodeError(_) | DeadlineExceeded | Cancelled => None
+    }
+  }
+
+  // Default headers merged with config.headers
+  let requestHeaders = (~config: config, ~accept: string): Js.Dict.t<string> => {
+    let headers = Js.Dict.fromArray([
+      ("Content-Type", "application/json"),
+      ("Accept", accept),
+    ])
+    // Merge custom headers
+    switch config.headers {
+    | Some(h) => Js.Dict.entries(h)->Array.forEach(((k, v)) => Js.Dict.set(headers, k, v))
+    | None => ()
+    }
+    headers
+  }
+
+  // Internal fetch helper, called by the innermost interceptor
+  let fetchUnary = async (
+    ~config: config,
+    ~deadline: Deadline.t,
+    request: Interceptor.request,
+  ): result<Js.Json.t, error> => {
+    let url = `${config.baseUrl}/${request.method}`
+    // grpc-timeout counts from this attempt, so retries send the time left
+    let headers = Js.Dict.fromArray(Js.Dict.entries(request.headers))
+    Deadline.remainingMs(deadline)->Option.forEach(ms => Js.Dict.set(headers, "grpc-timeout", Deadline.header(ms)))
+    try {
+      let response = await Fetch.fetch(
+        url,
+        {
+          method: #POST,
+          headers: Fetch.Headers.fromDict(headers),
+          body: Fetch.Body.string(Js.Json.stringify(request.body)),
+          signal: Deadline.signal(deadline),
+        },
+      )
+      let header = name => Fetch.Headers.get(Fetch.Headers.ofResponse(response), name)->Nullable.toOption
+      if Fetch.Response.ok(response) && header("grpc-status")->Option.getOr("0") == "0" {
+        let json = await Fetch.Response.json(response)
+        Ok(json)
+      } else {
+        let status = Fetch.Response.status(response)
+        let text = await Fetch.Response.text(response)
+        switch RpcStatus.fromResponse(~header, ~body=text) {
+        | Some(rpcStatus) => Error(StatusError(rpcStatus))
+        | None => Error(GrpcError(status, text))
+        }
+      }
+    } catch {
+    | _ if Deadline.timedOut(deadline) => Error(DeadlineExceeded)
+    | _ if Deadline.aborted(deadline) => Error(Cancelled)
+    | Exn.Error(exn) => Error(NetworkError(Exn.message(exn)->Option.getOr("Unknown error")))
+    | _ => Error(NetworkError("Unknown error"))
+    }
+  }
+
+  // Unary call through config.interceptors, aborted at the deadline or by `signal`
+  // and retried per config.retry
+  let call = async (
+    ~config: config,
+    ~method: string,
+    ~request: Js.Json.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+    ~idempotent: bool=false,
+  ): result<Js.Json.t, error> => {
+    let timeoutMs = timeoutMs->Option.orElse(config.timeoutMs)
+    let deadline = Deadline.make(~timeoutMs?, ~signal?, ())
+    let headers = requestHeaders(~config, ~accept="application/json")
+    timeoutMs->Option.forEach(ms => Js.Dict.set(headers, "grpc-timeout", Deadline.header(ms)))
+    let interceptors = config.interceptors->Option.getOr([])
+    let send = switch Retry.policyFor(config.retry, ~method, ~idempotent) {
+    | Some(policy) =>
+      request =>
+        Retry.run(
+          policy,
+          ~status=retryStatus,
+          ~signal=Deadline.signal(deadline),
+          ~aborted=() => Deadline.timedOut(deadline) ? DeadlineExceeded : Cancelled,
+          () => fetchUnary(~config, ~deadline, request),
+        )
+    | None => request => fetchUnary(~config, ~deadline, request)
+    }
+    let run = Interceptor.chainUnary(interceptors, send)
+    // Cleared on success, failure and exceptions from interceptors alike
+    await run({method, headers, body: request})->Promise.finally(() => Deadline.clear(deadline))
+  }
+
+  // Internal streaming helper, called by the innermost interceptor
+  let fetchStream = (
+    ~config: config,
+    request: Interceptor.request,
+    handler: streamHandler<Js.Json.t>,
+  ): streamCancel => {
+    let cancelled = ref(false)
+    let url = `${config.baseUrl}/${request.method}`
+
+    // Start the streaming request
+    let _ = Streaming.fetchNdjson(
+      ~url,
+      ~method=#POST,
+      ~headers=request.headers,
+      ~body=Js.Json.stringify(request.body),
+      ~onMessage=json => {
+        if !cancelled.contents {
+          handler.onMessage(json)
+        }
+      },
+      ~onError=msg => {
+        if !cancelled.contents {
+          handler.onError(NetworkError(msg))
+        }
+      },
+      ~onComplete=() => {
+        if !cancelled.contents {
+          handler.onComplete()
+        }
+      },
+    )
+
+    {cancel: () => cancelled := true}
+  }
+
+  // Server-streaming call through config.interceptors
+  let callStream = (
+    ~config: config,
+    ~method: string,
+    ~request: Js.Json.t,
+    ~handler: streamHandler<Js.Json.t>,
+  ): streamCancel => {
+    let interceptors = config.interceptors->Option.getOr([])
+    let run = Interceptor.chainStream(interceptors, (request, handler) =>
+      fetchStream(~config, request, handler)
+    )
+    let headers = requestHeaders(~config, ~accept="application/x-ndjson")
+    run({method, headers, body: request}, handler)
+  }
+
+  // Send RPC (unary)
+  /** Unary */
+  let send = async (
+    ~config: config=defaultConfig,
+    ~request: Ping.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+  ): result<Pong.t, error> => {
+    let requestJson = Ping.toJson(request)
+    let response = await call(~config, ~method="Echo/Send", ~request=requestJson, ~timeoutMs?, ~signal?)
+    switch response {
+    | Ok(json) =>
+      switch Pong.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+  // Subscribe RPC (server streaming)
+  /** Server streaming */
+  let subscribe = (
+    ~config: config=defaultConfig,
+    ~request: Ping.t,
+    ~handler: streamHandler<Pong.t>,
+  ): streamCancel => {
+    let requestJson = Ping.toJson(request)
+    callStream(
+      ~config,
+      ~method="Echo/Subscribe",
+      ~request=requestJson,
+      ~handler={
+        onMessage: json => {
+          switch Pong.fromJson(json) {
+          | Some(msg) => handler.onMessage(msg)
+          | None => handler.onError(DecodeError("Failed to decode stream message"))
+          }
+        },
+        onError: handler.onError,
+        onComplete: handler.onComplete,
+      },
+    )
+  }
+
+  // Upload RPC (client streaming - batch mode)
+  /** Client streaming */
+  let upload = async (
+    ~config: config=defaultConfig,
+    ~requests: array<Ping.t>,
+  ): result<Pong.t, error> => {
+    let requestsJson = Js.Json.array(Array.map(requests, Ping.toJson))
+    let response = await call(~config, ~method="Echo/Upload", ~request=requestsJson)
+    switch response {
+    | Ok(json) =>
+      switch Pong.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+  // Chat RPC (bidirectional streaming - batch/stream mode)
+  /** Bidirectional streaming */
+  let chat = (
+    ~config: config=defaultConfig,
+    ~requests: array<Ping.t>,
+    ~handler: streamHandler<Pong.t>,
+  ): streamCancel => {
+    let requestsJson = Js.Json.array(Array.map(requests, Ping.toJson))
+    callStream(
+      ~config,
+      ~method="Echo/Chat",
+      ~request=requestsJson,
+      ~handler={
+        onMessage: json => {
+          switch Pong.fromJson(json) {
+          | Some(msg) => handler.onMessage(msg)
+          | None => handler.onError(DecodeError("Failed to decode stream message"))
+          }
+        },
+        onError: handler.onError,
+        onComplete: handler.onComplete,
+      },
+    )
+  }
+
+  // Legacy RPC (unary)
+  @deprecated("Echo.Legacy is deprecated in the proto schema")
+  let legacy = async (
+    ~config: config=defaultConfig,
+    ~request: Ping.t,
+    ~timeoutMs: int=?,
+    ~signal: Fetch.AbortSignal.t=?,
+  ): result<Pong.t, error> => {
+    let requestJson = Ping.toJson(request)
+    let response = await call(~config, ~method="Echo/Legacy", ~request=requestJson, ~timeoutMs?, ~signal?)
+    switch response {
+    | Ok(json) =>
+      switch Pong.fromJson(json) {
+      | Some(msg) => Ok(msg)
+      | None => Error(DecodeError("Failed to decode response"))
+      }
+    | Error(e) => Error(e)
+    }
+  }
+
+}
+
+/** Every RPC shape */
+module EchoServer = {
+  // Server error types
+  type grpcStatus =
+    | Ok
+    | Cancelled
+    | Unknown
+    | InvalidArgument
+    | DeadlineExceeded
+    | NotFound
+    | AlreadyExists
+    | PermissionDenied
+    | ResourceExhausted
+    | FailedPrecondition
+    | Aborted
+    | OutOfRange
+    | Unimplemented
+    | Internal
+    | Unavailable
+    | DataLoss
+    | Unauthenticated
+
+  // Request context with metadata
+  type context = {
+    headers: Js.Dict.t<string>,
+    metadata: Js.Dict.t<string>,
+  }
+
+  type serverError = {
+    status: grpcStatus,
+    message: string,
+    // Typed google.rpc error details, e.g. BadRequest field violations
+    details?: array<RpcStatus.detail>,
+  }
+
+  // Stream writer for server-streaming responses
+  type streamWriter<'a> = {
+    send: 'a => promise<unit>,
+    complete: unit => unit,
+    error: serverError => unit,
+  }
+
+  // Trailers (grpc-status, ...) and JSON body of a handled request
+  type response = {
+    headers: array<(string, string)>,
+    body: Js.Json.t,
+  }
+
+  // Handler type definitions
+  type sendHandler = (~request: Ping.t, ~context: context) => promise<result<Pong.t, serverError>>
+  type subscribeHandler = (~request: Ping.t, ~context: context, ~writer: streamWriter<Pong.t>) => unit
+  type uploadHandler = (~requests: array<Ping.t>, ~context: context) => promise<result<Pong.t, serverError>>
+  type chatHandler = (~requests: array<Ping.t>, ~context: context, ~writer: streamWriter<Pong.t>) => unit
+  type legacyHandler = (~request: Ping.t, ~context: context) => promise<result<Pong.t, serverError>>
+
+  // Service implementation type
+  type service = {
+    send: sendHandler,
+    subscribe: subscribeHandler,
+    upload: uploadHandler,
+    chat: chatHandler,
+    legacy: legacyHandler,
+  }
+
+  let statusToCode = (status: grpcStatus): int => {
+    switch status {
+    | Ok => 0
+    | Cancelled => 1
+    | Unknown => 2
+    | InvalidArgument => 3
+    | DeadlineExceeded => 4
+    | NotFound => 5
+    | AlreadyExists => 6
+    | PermissionDenied => 7
+    | ResourceExhausted => 8
+    | FailedPrecondition => 9
+    | Aborted => 10
+    | OutOfRange => 11
+    | Unimplemented => 12
+    | Internal => 13
+    | Unavailable => 14
+    | DataLoss => 15
+    | Unauthenticated => 16
+    }
+  }
+
+  let errorStatus = (error: serverError): RpcStatus.t => {
+    code: statusToCode(error.status),
+    message: error.message,
+    details: error.details->Option.getOr([]),
+  }
+
+  // google.rpc.Status JSON body for an error response
+  let errorToJson = (error: serverError): Js.Json.t => RpcStatus.toJson(errorStatus(error))
+
+  // grpc-status, grpc-message and grpc-status-details-bin for an error response
+  let errorHeaders = (error: serverError): array<(string, string)> =>
+    RpcStatus.toHeaders(errorStatus(error))
+
+  // Route method name to handler
+  let methodNames = [
+    "Echo/Send",
+    "Echo/Subscribe",
+    "Echo/Upload",
+    "Echo/Chat",
+    "Echo/Legacy",
+  ]
+
+  // Handle incoming JSON request
+  let handleRequest = async (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+  ): result<Js.Json.t, serverError> => {
+    switch method {
+    | "Echo/Send" =>
+      switch Ping.fromJson(body) {
+      | Some(request) =>
+        let response = await service.send(~request, ~context)
+        switch response {
+        | Ok(msg) => Ok(Pong.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | "Echo/Upload" =>
+      switch Js.Json.decodeArray(body) {
+      | Some(arr) =>
+        let requests = Array.filterMap(arr, Ping.fromJson)
+        let response = await service.upload(~requests, ~context)
+        switch response {
+        | Ok(msg) => Ok(Pong.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Expected array of requests"})
+      }
+    | "Echo/Legacy" =>
+      switch Ping.fromJson(body) {
+      | Some(request) =>
+        let response = await service.legacy(~request, ~context)
+        switch response {
+        | Ok(msg) => Ok(Pong.toJson(msg))
+        | Error(e) => Error(e)
+        }
+      | None => Error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | _ => Error({status: Unimplemented, message: "Method not found"})
+    }
+  }
+
+  // handleRequest with the trailers and body to send back
+  let respond = async (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+  ): response => {
+    switch await handleRequest(~service, ~method, ~body, ~context) {
+    | Ok(body) => {headers: [("grpc-status", "0")], body}
+    | Error(error) => {headers: errorHeaders(error), body: errorToJson(error)}
+    }
+  }
+
+  // Handle streaming request
+  let handleStreamingRequest = (
+    ~service: service,
+    ~method: string,
+    ~body: Js.Json.t,
+    ~context: context,
+    ~writer: streamWriter<Js.Json.t>,
+  ): unit => {
+    switch method {
+    | "Echo/Subscribe" =>
+      let typedWriter: streamWriter<Pong.t> = {
+        send: async msg => await writer.send(Pong.toJson(msg)),
+        complete: writer.complete,
+        error: writer.error,
+      }
+      switch Ping.fromJson(body) {
+      | Some(request) =>
+        service.subscribe(~request, ~context, ~writer=typedWriter)
+      | None => writer.error({status: InvalidArgument, message: "Failed to decode request"})
+      }
+    | "Echo/Chat" =>
+      let typedWriter: streamWriter<Pong.t> = {
+        send: async msg => await writer.send(Pong.toJson(msg)),
+        complete: writer.complete,
+        error: writer.error,
+      }
+      switch Js.Json.decodeArray(body) {
+      | Some(arr) =>
+        let requests = Array.filterMap(arr, Ping.fromJson)
+        service.chat(~requests, ~context, ~writer=typedWriter)
+      | None => writer.error({status: InvalidArgument, message: "Expected array of requests"})
+      }
+    | _ => writer.error({status: Unimplemented, message: "Method not found"})
+    }
+  }
+
+  // Check if method requires streaming response
+  let isStreamingMethod = (method: string): bool => {
+    switch method {
+    | "Echo/Subscribe" => true
+    | "Echo/Chat" => true
+    | _ => false
+    }
+  }
+}
+
+
@@ -0,0 +1,20 @@
+#!/bin/sh
+# SPDX-License-Identifier: MPL-2.0
+# Regenerate the checked-in CodeGeneratorRequests (requires protoc).
+set -eu
+cd "$(dirname "$0")"
+
+dump() {
+    name=$1
+    shift
+    REQUEST_OUT="$PWD/requests/$name.pb" protoc \
+        --plugin=protoc-gen-dump="$PWD/dump-request.sh" \
+        --dump_out="${TMPDIR:-/tmp}" \
+        "$@"
+}
+
+dump basic -I ../../../examples/basic/protos user.proto event.proto streaming.proto wkt.proto
+
+for proto in maps oneofs nested recursion proto2 services; do
+    dump "$proto" -I protos "$proto.proto"
+done
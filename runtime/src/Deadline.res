@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MPL-2.0
+// Deadlines and cancellation for unary gRPC-web calls
+
+// Aborts one call when its deadline passes or the caller's signal fires
+type t = {
+  controller: Fetch.AbortController.t,
+  // Date.now() at which the deadline passes
+  expiresAt: option<float>,
+  mutable timedOut: bool,
+  mutable timer: option<timeoutId>,
+  // Caller's signal and the listener added to it, removed by `clear`
+  mutable listener: option<(Fetch.AbortSignal.t, unit => unit)>,
+}
+
+let make = (~timeoutMs: int=?, ~signal: Fetch.AbortSignal.t=?, ()): t => {
+  let deadline = {
+    controller: Fetch.AbortController.make(),
+    expiresAt: timeoutMs->Option.map(ms => Date.now() +. Int.toFloat(ms)),
+    timedOut: false,
+    timer: None,
+    listener: None,
+  }
+  let abort = () => Fetch.AbortController.abort(deadline.controller)
+  switch signal {
+  | Some(signal) if Fetch.AbortSignal.aborted(signal) => abort()
+  | Some(signal) =>
+    Fetch.AbortSignal.onAbort(signal, abort)
+    deadline.listener = Some((signal, abort))
+  | None => ()
+  }
+  deadline.timer = timeoutMs->Option.map(ms =>
+    setTimeout(() => {
+      deadline.timedOut = true
+      abort()
+    }, ms)
+  )
+  deadline
+}
+
+let signal = (deadline: t): Fetch.AbortSignal.t => Fetch.AbortController.signal(deadline.controller)
+
+let timedOut = (deadline: t): bool => deadline.timedOut
+
+let aborted = (deadline: t): bool => Fetch.AbortSignal.aborted(signal(deadline))
+
+// Milliseconds left before the deadline, None without one
+let remainingMs = (deadline: t): option<int> =>
+  deadline.expiresAt->Option.map(expiresAt => Math.Int.max(0, Float.toInt(Math.ceil(expiresAt -. Date.now()))))
+
+// Stop the timer and detach from the caller's signal once the call has finished,
+// however it ended
+let clear = (deadline: t): unit => {
+  deadline.timer->Option.forEach(clearTimeout)
+  deadline.timer = None
+  deadline.listener->Option.forEach(((signal, abort)) => Fetch.AbortSignal.removeOnAbort(signal, abort))
+  deadline.listener = None
+}
+
+// grpc-timeout header value: at most 8 digits, in milliseconds or whole seconds
+let header = (timeoutMs: int): string => {
+  if timeoutMs < 100000000 {
+    `${Int.toString(timeoutMs)}m`
+  } else {
+    `${Int.toString((timeoutMs + 999) / 1000)}S`
+  }
+}
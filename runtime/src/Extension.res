@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MPL-2.0
+// Proto2 extensions with a registry for JSON and binary decoding
+// https://protobuf.dev/programming-guides/proto2/#extensions
+
+// Extension values carried by an extendable message
+// Keyed by the proto3 JSON key of the extension, e.g. "[example.priority]"
+type values = Js.Dict.t<Js.Json.t>
+
+// Typed extension descriptor (generated for each `extend` field)
+type t<'a> = {
+  // Fully-qualified name of the extended message, e.g. "example.Task"
+  extendee: string,
+  fieldNumber: int,
+  // Fully-qualified extension name, e.g. "example.priority"
+  name: string,
+  toJson: 'a => Js.Json.t,
+  fromJson: Js.Json.t => option<'a>,
+  // Binary codec descriptor (None for recursive types and types missing from the request)
+  wasmField: option<WasmCodec.fieldDescriptor>,
+}
+
+// JSON key used for an extension value
+let jsonKey = (name: string): string => `[${name}]`
+
+let make = (
+  ~extendee: string,
+  ~fieldNumber: int,
+  ~name: string,
+  ~repeated: bool=false,
+  ~toJson: 'a => Js.Json.t,
+  ~fromJson: Js.Json.t => option<'a>,
+  ~wasmType: WasmCodec.fieldType=?,
+): t<'a> => {
+  extendee,
+  fieldNumber,
+  name,
+  toJson,
+  fromJson,
+  wasmField: wasmType->Option.map((fieldType): WasmCodec.fieldDescriptor => {
+    n: fieldNumber,
+    name: jsonKey(name),
+    fieldType,
+    repeated,
+    optional: !repeated,
+  }),
+}
+
+let empty = (): values => Js.Dict.empty()
+
+// Read a typed extension value
+let get = (values: values, ext: t<'a>): option<'a> => {
+  Js.Dict.get(values, jsonKey(ext.name))->Option.flatMap(ext.fromJson)
+}
+
+let has = (values: values, ext: t<'a>): bool => {
+  Option.isSome(Js.Dict.get(values, jsonKey(ext.name)))
+}
+
+// Set a typed extension value (returns a new values dict)
+let set = (values: values, ext: t<'a>, value: 'a): values => {
+  let copy = Js.Dict.fromArray(Js.Dict.entries(values))
+  Js.Dict.set(copy, jsonKey(ext.name), ext.toJson(value))
+  copy
+}
+
+// Remove an extension value (returns a new values dict)
+let clear = (values: values, ext: t<'a>): values => {
+  let key = jsonKey(ext.name)
+  Js.Dict.fromArray(Js.Dict.entries(values)->Array.filter(((k, _)) => k != key))
+}
+
+// Alias so the registry can refer to descriptors alongside its own `t`
+type extension<'a> = t<'a>
+
+// Registry of known extensions, keyed by extendee
+module Registry = {
+  type entry = {
+    extendee: string,
+    fieldNumber: int,
+    name: string,
+    wasmField: option<WasmCodec.fieldDescriptor>,
+  }
+
+  type t = Js.Dict.t<array<entry>>
+
+  let make = (): t => Js.Dict.empty()
+
+  // Registry consulted by generated fromJson functions
+  let global: t = make()
+
+  let register = (registry: t, ext: extension<'a>): unit => {
+    let entry = {
+      extendee: ext.extendee,
+      fieldNumber: ext.fieldNumber,
+      name: ext.name,
+      wasmField: ext.wasmField,
+    }
+    let existing = Js.Dict.get(registry, ext.extendee)->Option.getOr([])
+    let others = existing->Array.filter(e => e.fieldNumber != ext.fieldNumber)
+    Js.Dict.set(registry, ext.extendee, Array.concat(others, [entry]))
+  }
+
+  let forExtendee = (registry: t, extendee: string): array<entry> => {
+    Js.Dict.get(registry, extendee)->Option.getOr([])
+  }
+
+  let findByKey = (registry: t, ~extendee: string, ~key: string): option<entry> => {
+    forExtendee(registry, extendee)->Array.find(e => jsonKey(e.name) == key)
+  }
+
+  let entries = (registry: t): array<entry> => {
+    Js.Dict.values(registry)->Array.flat
+  }
+}
+
+// Register an extension globally for JSON decoding and the WASM codec
+let register = (ext: t<'a>): unit => {
+  Registry.register(Registry.global, ext)
+  switch ext.wasmField {
+  | Some(field) => WasmCodec.registerExtension(~extendee=ext.extendee, field)->ignore
+  | None => ()
+  }
+}
+
+// Push all registered extensions to the WASM codec
+// Call after WasmCodec.initialize if extensions were registered before it finished
+let syncCodec = (): result<unit, string> => {
+  Registry.entries(Registry.global)->Array.reduce(Ok(), (acc, entry) => {
+    switch (acc, entry.wasmField) {
+    | (Ok(), Some(field)) => WasmCodec.registerExtension(~extendee=entry.extendee, field)
+    | _ => acc
+    }
+  })
+}
+
+// Collect registered extension values from a JSON object (used by generated fromJson)
+let fromJsonObject = (obj: Js.Dict.t<Js.Json.t>, ~extendee: string): values => {
+  let values = empty()
+  Js.Dict.entries(obj)->Array.forEach(((key, value)) => {
+    if String.startsWith(key, "[") {
+      switch Registry.findByKey(Registry.global, ~extendee, ~key) {
+      | Some(_) => Js.Dict.set(values, key, value)
+      | None => ()
+      }
+    }
+  })
+  values
+}
+
+// Extension values as JSON object fields (used by generated toJson)
+let toJsonFields = (values: values): array<(string, Js.Json.t)> => {
+  Js.Dict.entries(values)
+}
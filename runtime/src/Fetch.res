@@ -18,6 +18,7 @@ module Headers = {
   @new external make: unit => t = "Headers"
   @send external set: (t, string, string) => unit = "set"
   @send external get: (t, string) => Nullable.t<string> = "get"
+  @get external ofResponse: Response.t => t = "headers"
 
   let fromDict = (dict: Js.Dict.t<string>): t => {
     let headers = make()
@@ -34,11 +35,29 @@ module Body = {
   external json: Js.Json.t => t = "%identity"
 }
 
+// Abort signal, passed to fetch to cancel the request
+module AbortSignal = {
+  type t
+
+  @get external aborted: t => bool = "aborted"
+  @send external onAbort: (t, @as("abort") _, unit => unit) => unit = "addEventListener"
+  @send external removeOnAbort: (t, @as("abort") _, unit => unit) => unit = "removeEventListener"
+}
+
+module AbortController = {
+  type t
+
+  @new external make: unit => t = "AbortController"
+  @get external signal: t => AbortSignal.t = "signal"
+  @send external abort: t => unit = "abort"
+}
+
 // Request init options
 type requestInit = {
   method: [#GET | #POST | #PUT | #DELETE | #PATCH],
   headers: Headers.t,
   body: Body.t,
+  signal?: AbortSignal.t,
 }
 
 // Fetch function
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MPL-2.0
+// Client interceptors for generated gRPC-web clients
+//
+// Each hook receives the outgoing call and `next`, the rest of the chain, so it
+// can rewrite the request, transform the result or retry. The first entry of
+// `config.interceptors` runs outermost.
+
+// Outgoing call: RPC path ("UserService/GetUser"), request metadata and JSON body
+type request = {
+  method: string,
+  headers: Js.Dict.t<string>,
+  body: Js.Json.t,
+}
+
+type unary<'error> = (
+  request,
+  request => promise<result<Js.Json.t, 'error>>,
+) => promise<result<Js.Json.t, 'error>>
+
+// Streaming hooks can also wrap the handler to observe messages and errors
+type stream<'handler, 'cancel> = (request, 'handler, (request, 'handler) => 'cancel) => 'cancel
+
+// Generated clients use t<error, streamHandler<Js.Json.t>, streamCancel>
+type t<'error, 'handler, 'cancel> = {
+  unary?: unary<'error>,
+  stream?: stream<'handler, 'cancel>,
+}
+
+// Copy of the request with one header set
+let withHeader = (request: request, name: string, value: string): request => {
+  let headers = Js.Dict.fromArray(Js.Dict.entries(request.headers))
+  Js.Dict.set(headers, name, value)
+  {...request, headers}
+}
+
+// Interceptor rewriting every unary and streaming request, usable with any client
+let mapRequest = (rewrite: request => request): t<'error, 'handler, 'cancel> => {
+  unary: (request, next) => next(rewrite(request)),
+  stream: (request, handler, next) => next(rewrite(request), handler),
+}
+
+// Wrap `send` in the unary hooks
+let chainUnary = (
+  interceptors: array<t<'error, 'handler, 'cancel>>,
+  send: request => promise<result<Js.Json.t, 'error>>,
+): (request => promise<result<Js.Json.t, 'error>>) => {
+  interceptors->Array.reduceRight(send, (next, interceptor) =>
+    switch interceptor.unary {
+    | Some(unary) => request => unary(request, next)
+    | None => next
+    }
+  )
+}
+
+// Wrap `start` in the streaming hooks
+let chainStream = (
+  interceptors: array<t<'error, 'handler, 'cancel>>,
+  start: (request, 'handler) => 'cancel,
+): ((request, 'handler) => 'cancel) => {
+  interceptors->Array.reduceRight(start, (next, interceptor) =>
+    switch interceptor.stream {
+    | Some(stream) => (request, handler) => stream(request, handler, next)
+    | None => next
+    }
+  )
+}
@@ -35,6 +35,11 @@ module Encode = {
   // Int64/BigInt as string (proto3 JSON mapping)
   let int64 = (i: bigint): Js.Json.t => Js.Json.string(BigInt.toString(i))
 
+  // Encode array of values
+  let array = (encode: 'a => Js.Json.t): (array<'a> => Js.Json.t) => {
+    values => Js.Json.array(Array.map(values, encode))
+  }
+
   // Bytes as base64
   let bytes = (b: Js.Typed_array.Uint8Array.t): Js.Json.t => {
     // Simple base64 encoding
@@ -165,29 +170,59 @@ module Decode = {
     }
   }
 
-  // Bytes from base64
+  // Decode array of values - fails if any element fails
+  let array = (decode: Js.Json.t => option<'a>): (Js.Json.t => option<array<'a>>) => {
+    json =>
+      switch Js.Json.decodeArray(json) {
+      | Some(arr) =>
+        let results = Array.filterMap(arr, decode)
+        if Array.length(results) == Array.length(arr) {
+          Some(results)
+        } else {
+          None
+        }
+      | None => None
+      }
+  }
+
+  // Bytes from base64, standard or URL-safe, with or without padding
   let bytes = (json: Js.Json.t): option<Js.Typed_array.Uint8Array.t> => {
     switch Js.Json.decodeString(json) {
     | Some(s) =>
       let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
-      let len = String.length(s)
-      let outputLen = len * 3 / 4 - (if String.endsWith(s, "==") { 2 } else if String.endsWith(s, "=") { 1 } else { 0 })
+      let len = ref(String.length(s))
+      while len.contents > 0 && String.charAt(s, len.contents - 1) == "=" {
+        len := len.contents - 1
+      }
+      let len = len.contents
+      // A trailing group of 2 or 3 characters holds 1 or 2 bytes
+      let outputLen = len * 3 / 4
       let result = Js.Typed_array.Uint8Array.make(Array.make(~length=outputLen, 0))
 
+      // A single character left over, or one outside both alphabets, is not base64
+      let valid = ref(mod(len, 4) != 1)
       let indexOf = (c: string): int => {
-        switch String.indexOf(chars, c) {
-        | i if i >= 0 => i
-        | _ => 0
+        switch c {
+        | "-" => 62
+        | "_" => 63
+        | c =>
+          switch String.indexOf(chars, c) {
+          | i if i >= 0 => i
+          | _ =>
+            valid := false
+            0
+          }
         }
       }
+      let at = i => i < len ? indexOf(String.charAt(s, i)) : 0
 
       let j = ref(0)
       let i = ref(0)
-      while i.contents < len - 3 {
-        let c0 = indexOf(String.charAt(s, i.contents))
-        let c1 = indexOf(String.charAt(s, i.contents + 1))
-        let c2 = indexOf(String.charAt(s, i.contents + 2))
-        let c3 = indexOf(String.charAt(s, i.contents + 3))
+      while i.contents < len {
+        let c0 = at(i.contents)
+        let c1 = at(i.contents + 1)
+        let c2 = at(i.contents + 2)
+        let c3 = at(i.contents + 3)
 
         if j.contents < outputLen {
           Js.Typed_array.Uint8Array.unsafe_set(result, j.contents, lor(lsl(c0, 2), lsr(c1, 4)))
@@ -203,7 +238,7 @@ module Decode = {
         j := j.contents + 3
       }
 
-      Some(result)
+      valid.contents ? Some(result) : None
     | None => None
     }
   }
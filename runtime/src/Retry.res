@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: MPL-2.0
+// Retry policies for unary gRPC-web calls
+// https://github.com/grpc/proposal/blob/master/A6-client-retries.md
+
+// gRPC status codes by name, as used in service configs
+let statusCodes = [
+  ("OK", 0),
+  ("CANCELLED", 1),
+  ("UNKNOWN", 2),
+  ("INVALID_ARGUMENT", 3),
+  ("DEADLINE_EXCEEDED", 4),
+  ("NOT_FOUND", 5),
+  ("ALREADY_EXISTS", 6),
+  ("PERMISSION_DENIED", 7),
+  ("RESOURCE_EXHAUSTED", 8),
+  ("FAILED_PRECONDITION", 9),
+  ("ABORTED", 10),
+  ("OUT_OF_RANGE", 11),
+  ("UNIMPLEMENTED", 12),
+  ("INTERNAL", 13),
+  ("UNAVAILABLE", 14),
+  ("DATA_LOSS", 15),
+  ("UNAUTHENTICATED", 16),
+]
+
+let codeFromName = (name: string): option<int> => {
+  statusCodes->Array.find(((n, _)) => n == name)->Option.map(((_, code)) => code)
+}
+
+// gRPC status of an HTTP error response without grpc-status, per the gRPC HTTP mapping
+let codeOfHttpStatus = (status: int): int => {
+  switch status {
+  | 400 => 13
+  | 401 => 16
+  | 403 => 7
+  | 404 => 12
+  | 429 | 502 | 503 | 504 => 14
+  | _ => 2
+  }
+}
+
+type policy = {
+  // Attempts including the first call
+  maxAttempts: int,
+  initialBackoffMs: int,
+  maxBackoffMs: int,
+  backoffMultiplier: float,
+  // gRPC status codes worth another attempt
+  retryableStatusCodes: array<int>,
+}
+
+// Three attempts on UNAVAILABLE, waiting up to 100ms then 200ms
+let defaultPolicy = {
+  maxAttempts: 3,
+  initialBackoffMs: 100,
+  maxBackoffMs: 5000,
+  backoffMultiplier: 2.0,
+  retryableStatusCodes: [14],
+}
+
+// Retry settings of a client
+type config = {
+  // Policy of idempotent methods (idempotency_level in the proto), defaultPolicy if absent
+  policy?: policy,
+  // Policy of every method without an entry in `methods`
+  allMethods?: policy,
+  // Per-method policies keyed by method name ("GetUser"), used for any method
+  methods?: Js.Dict.t<policy>,
+}
+
+let policyFor = (config: option<config>, ~method: string, ~idempotent: bool): option<policy> => {
+  // `method` is the RPC path, "UserService/GetUser"
+  let name = String.split(method, "/")->Array.at(-1)->Option.getOr(method)
+  let idempotentPolicy = policy => idempotent ? Some(policy->Option.getOr(defaultPolicy)) : None
+  switch config {
+  | Some(config) =>
+    switch config.methods->Option.flatMap(methods => Js.Dict.get(methods, name)) {
+    | Some(policy) => Some(policy)
+    | None => config.allMethods->Option.orElse(idempotentPolicy(config.policy))
+    }
+  | None => idempotentPolicy(None)
+  }
+}
+
+// Random delay before attempt `attempt + 1`, full jitter as in gRPC
+let backoffMs = (policy: policy, attempt: int): int => {
+  let base = Int.toFloat(policy.initialBackoffMs) *. Math.pow(policy.backoffMultiplier, ~exp=Int.toFloat(attempt - 1))
+  Float.toInt(Math.random() *. Math.min(base, Int.toFloat(policy.maxBackoffMs)))
+}
+
+// Resolve after `ms`, or as soon as `signal` aborts
+let sleep = (~signal: Fetch.AbortSignal.t=?, ms: int): promise<unit> =>
+  Promise.make((resolve, _) => {
+    switch signal {
+    | Some(signal) if Fetch.AbortSignal.aborted(signal) => resolve()
+    | Some(signal) =>
+      let timer = ref(None)
+      let rec wake = () => {
+        timer.contents->Option.forEach(clearTimeout)
+        Fetch.AbortSignal.removeOnAbort(signal, wake)
+        resolve()
+      }
+      timer := Some(setTimeout(wake, ms))
+      Fetch.AbortSignal.onAbort(signal, wake)
+    | None => setTimeout(() => resolve(), ms)->ignore
+    }
+  })
+
+// Run `attempt` until it succeeds, fails with a non-retryable status or runs out of attempts
+//
+// Once `signal` aborts (deadline or caller) the backoff stops and `aborted()` is returned.
+let run = async (
+  policy: policy,
+  ~status: 'error => option<int>,
+  ~signal: Fetch.AbortSignal.t,
+  ~aborted: unit => 'error,
+  attempt: unit => promise<result<'a, 'error>>,
+): result<'a, 'error> => {
+  let retryable = error =>
+    status(error)->Option.mapOr(false, code => policy.retryableStatusCodes->Array.includes(code))
+  let rec loop = async n => {
+    switch await attempt() {
+    | Error(error) if n < policy.maxAttempts && retryable(error) =>
+      await sleep(~signal, backoffMs(policy, n))
+      if Fetch.AbortSignal.aborted(signal) {
+        Error(aborted())
+      } else {
+        await loop(n + 1)
+      }
+    | result => result
+    }
+  }
+  await loop(1)
+}
+
+// "0.1s" in a service config
+let parseDurationMs = (json: Js.Json.t): option<int> => {
+  switch Js.Json.decodeString(json) {
+  | Some(s) if String.endsWith(s, "s") =>
+    String.slice(s, ~start=0, ~end=-1)->Float.fromString->Option.map(seconds => Float.toInt(seconds *. 1000.0))
+  | _ => None
+  }
+}
+
+let parsePolicy = (json: Js.Json.t): option<policy> => {
+  let field = name => Js.Json.decodeObject(json)->Option.flatMap(obj => Js.Dict.get(obj, name))
+  let code = json =>
+    switch (Js.Json.decodeString(json), Js.Json.decodeNumber(json)) {
+    | (Some(name), _) => codeFromName(name)
+    | (_, Some(code)) => Some(Float.toInt(code))
+    | _ => None
+    }
+  switch (
+    field("maxAttempts")->Option.flatMap(Js.Json.decodeNumber),
+    field("initialBackoff")->Option.flatMap(parseDurationMs),
+    field("maxBackoff")->Option.flatMap(parseDurationMs),
+    field("backoffMultiplier")->Option.flatMap(Js.Json.decodeNumber),
+    field("retryableStatusCodes")->Option.flatMap(Js.Json.decodeArray),
+  ) {
+  | (Some(maxAttempts), Some(initialBackoffMs), Some(maxBackoffMs), Some(backoffMultiplier), Some(codes)) =>
+    Some({
+      maxAttempts: Float.toInt(maxAttempts),
+      initialBackoffMs,
+      maxBackoffMs,
+      backoffMultiplier,
+      retryableStatusCodes: codes->Array.filterMap(code),
+    })
+  | _ => None
+  }
+}
+
+// Retry settings of `service` ("example.UserService") from a gRPC service config
+// (https://github.com/grpc/grpc/blob/master/doc/service_config.md)
+//
+// Entries naming a method go to `methods`; entries naming only the service, or
+// nothing, apply to all other methods.
+let fromServiceConfig = (json: Js.Json.t, ~service: string): config => {
+  let methods = Js.Dict.empty()
+  let servicePolicy = ref(None)
+  let globalPolicy = ref(None)
+  let entries =
+    Js.Json.decodeObject(json)
+    ->Option.flatMap(obj => Js.Dict.get(obj, "methodConfig"))
+    ->Option.flatMap(Js.Json.decodeArray)
+    ->Option.getOr([])
+
+  entries->Array.forEach(entry => {
+    let obj = Js.Json.decodeObject(entry)
+    let policy = obj->Option.flatMap(obj => Js.Dict.get(obj, "retryPolicy"))->Option.flatMap(parsePolicy)
+    let names =
+      obj
+      ->Option.flatMap(obj => Js.Dict.get(obj, "name"))
+      ->Option.flatMap(Js.Json.decodeArray)
+      ->Option.getOr([])
+    switch policy {
+    | Some(policy) =>
+      names->Array.forEach(name => {
+        let name = Js.Json.decodeObject(name)->Option.getOr(Js.Dict.empty())
+        let get = key => Js.Dict.get(name, key)->Option.flatMap(Js.Json.decodeString)
+        switch (get("service"), get("method")) {
+        | (Some(s), Some(method)) if s == service => Js.Dict.set(methods, method, policy)
+        | (Some(s), None) if s == service => servicePolicy := Some(policy)
+        | (None, None) => globalPolicy := Some(policy)
+        | _ => ()
+        }
+      })
+    | None => ()
+    }
+  })
+
+  switch servicePolicy.contents->Option.orElse(globalPolicy.contents) {
+  | Some(policy) => {allMethods: policy, methods}
+  | None => {methods: methods}
+  }
+}
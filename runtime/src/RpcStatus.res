@@ -0,0 +1,654 @@
+// SPDX-License-Identifier: MPL-2.0
+// google.rpc.Status with the details of google/rpc/error_details.proto
+// https://google.aip.dev/193
+
+type errorInfo = {
+  reason: string,
+  domain: string,
+  metadata: Js.Dict.t<string>,
+}
+
+type retryInfo = {
+  // Seconds to wait before retrying, as in WellKnown.Duration
+  retryDelay: option<float>,
+}
+
+type debugInfo = {
+  stackEntries: array<string>,
+  detail: string,
+}
+
+type quotaViolation = {
+  subject: string,
+  description: string,
+}
+
+type quotaFailure = {violations: array<quotaViolation>}
+
+type preconditionViolation = {
+  type_: string,
+  subject: string,
+  description: string,
+}
+
+type preconditionFailure = {violations: array<preconditionViolation>}
+
+type localizedMessage = {
+  locale: string,
+  message: string,
+}
+
+type fieldViolation = {
+  // Path of the invalid field, "address.postal_code"
+  field: string,
+  description: string,
+  reason: string,
+  localizedMessage: option<localizedMessage>,
+}
+
+type badRequest = {fieldViolations: array<fieldViolation>}
+
+type requestInfo = {
+  requestId: string,
+  servingData: string,
+}
+
+type resourceInfo = {
+  resourceType: string,
+  resourceName: string,
+  owner: string,
+  description: string,
+}
+
+type link = {
+  description: string,
+  url: string,
+}
+
+type help = {links: array<link>}
+
+// Details of a status; other message types stay packed in Any
+type detail =
+  | ErrorInfo(errorInfo)
+  | RetryInfo(retryInfo)
+  | DebugInfo(debugInfo)
+  | QuotaFailure(quotaFailure)
+  | PreconditionFailure(preconditionFailure)
+  | BadRequest(badRequest)
+  | RequestInfo(requestInfo)
+  | ResourceInfo(resourceInfo)
+  | Help(help)
+  | LocalizedMessage(localizedMessage)
+  | Other(WellKnown.Any.t)
+
+type t = {
+  // gRPC status code, see Retry.statusCodes
+  code: int,
+  message: string,
+  details: array<detail>,
+}
+
+// Field violations of BadRequest details, for showing errors next to form fields
+let fieldViolations = (status: t): array<fieldViolation> => {
+  status.details->Array.flatMap(detail =>
+    switch detail {
+    | BadRequest({fieldViolations}) => fieldViolations
+    | _ => []
+    }
+  )
+}
+
+// JSON (proto3 mapping) of the detail types
+
+let str = (obj: Js.Dict.t<Js.Json.t>, name: string): string => {
+  Js.Dict.get(obj, name)->Option.flatMap(Js.Json.decodeString)->Option.getOr("")
+}
+
+let objects = (obj: Js.Dict.t<Js.Json.t>, name: string): array<Js.Dict.t<Js.Json.t>> => {
+  Js.Dict.get(obj, name)
+  ->Option.flatMap(Js.Json.decodeArray)
+  ->Option.getOr([])
+  ->Array.filterMap(Js.Json.decodeObject)
+}
+
+let localizedMessageFromJson = (obj: Js.Dict.t<Js.Json.t>): localizedMessage => {
+  locale: str(obj, "locale"),
+  message: str(obj, "message"),
+}
+
+let detailOfAny = (any: WellKnown.Any.t): detail => {
+  let obj = Js.Json.decodeObject(any.value)->Option.getOr(Js.Dict.empty())
+  switch WellKnown.Any.typeName(any) {
+  | "google.rpc.ErrorInfo" =>
+    let metadata =
+      Js.Dict.get(obj, "metadata")
+      ->Option.flatMap(Js.Json.decodeObject)
+      ->Option.getOr(Js.Dict.empty())
+      ->Js.Dict.entries
+      ->Array.filterMap(((key, value)) => Js.Json.decodeString(value)->Option.map(value => (key, value)))
+    ErrorInfo({reason: str(obj, "reason"), domain: str(obj, "domain"), metadata: Js.Dict.fromArray(metadata)})
+  | "google.rpc.RetryInfo" =>
+    RetryInfo({retryDelay: Js.Dict.get(obj, "retryDelay")->Option.flatMap(WellKnown.Duration.fromJson)})
+  | "google.rpc.DebugInfo" =>
+    let stackEntries =
+      Js.Dict.get(obj, "stackEntries")
+      ->Option.flatMap(Js.Json.decodeArray)
+      ->Option.getOr([])
+      ->Array.filterMap(Js.Json.decodeString)
+    DebugInfo({stackEntries, detail: str(obj, "detail")})
+  | "google.rpc.QuotaFailure" =>
+    let violations = objects(obj, "violations")->Array.map((v): quotaViolation => {
+      subject: str(v, "subject"),
+      description: str(v, "description"),
+    })
+    QuotaFailure({violations: violations})
+  | "google.rpc.PreconditionFailure" =>
+    let violations = objects(obj, "violations")->Array.map((v): preconditionViolation => {
+      type_: str(v, "type"),
+      subject: str(v, "subject"),
+      description: str(v, "description"),
+    })
+    PreconditionFailure({violations: violations})
+  | "google.rpc.BadRequest" =>
+    let fieldViolations = objects(obj, "fieldViolations")->Array.map((v): fieldViolation => {
+      field: str(v, "field"),
+      description: str(v, "description"),
+      reason: str(v, "reason"),
+      localizedMessage: Js.Dict.get(v, "localizedMessage")
+      ->Option.flatMap(Js.Json.decodeObject)
+      ->Option.map(localizedMessageFromJson),
+    })
+    BadRequest({fieldViolations: fieldViolations})
+  | "google.rpc.RequestInfo" =>
+    RequestInfo({requestId: str(obj, "requestId"), servingData: str(obj, "servingData")})
+  | "google.rpc.ResourceInfo" =>
+    ResourceInfo({
+      resourceType: str(obj, "resourceType"),
+      resourceName: str(obj, "resourceName"),
+      owner: str(obj, "owner"),
+      description: str(obj, "description"),
+    })
+  | "google.rpc.Help" =>
+    let links = objects(obj, "links")->Array.map((l): link => {
+      description: str(l, "description"),
+      url: str(l, "url"),
+    })
+    Help({links: links})
+  | "google.rpc.LocalizedMessage" => LocalizedMessage(localizedMessageFromJson(obj))
+  | _ => Other(any)
+  }
+}
+
+let pack = (typeName: string, fields: array<(string, Js.Json.t)>): WellKnown.Any.t => {
+  typeUrl: WellKnown.Any.typeUrlPrefix ++ typeName,
+  value: Js.Json.object_(Js.Dict.fromArray(fields)),
+}
+
+let localizedMessageToJson = (m: localizedMessage): Js.Json.t => {
+  Js.Json.object_(
+    Js.Dict.fromArray([("locale", Js.Json.string(m.locale)), ("message", Js.Json.string(m.message))]),
+  )
+}
+
+let detailToAny = (detail: detail): WellKnown.Any.t => {
+  let s = Js.Json.string
+  let messages = (items, toFields) =>
+    Js.Json.array(items->Array.map(item => Js.Json.object_(Js.Dict.fromArray(toFields(item)))))
+  switch detail {
+  | ErrorInfo(info) =>
+    let metadata = Js.Dict.entries(info.metadata)->Array.map(((k, v)) => (k, s(v)))
+    pack(
+      "google.rpc.ErrorInfo",
+      [
+        ("reason", s(info.reason)),
+        ("domain", s(info.domain)),
+        ("metadata", Js.Json.object_(Js.Dict.fromArray(metadata))),
+      ],
+    )
+  | RetryInfo({retryDelay}) =>
+    pack("google.rpc.RetryInfo", retryDelay->Option.mapOr([], d => [("retryDelay", WellKnown.Duration.toJson(d))]))
+  | DebugInfo(info) =>
+    pack(
+      "google.rpc.DebugInfo",
+      [("stackEntries", Js.Json.array(info.stackEntries->Array.map(s))), ("detail", s(info.detail))],
+    )
+  | QuotaFailure({violations}) =>
+    pack(
+      "google.rpc.QuotaFailure",
+      [("violations", messages(violations, (v: quotaViolation) => [("subject", s(v.subject)), ("description", s(v.description))]))],
+    )
+  | PreconditionFailure({violations}) =>
+    let toFields = (v: preconditionViolation) => [
+      ("type", s(v.type_)),
+      ("subject", s(v.subject)),
+      ("description", s(v.description)),
+    ]
+    pack("google.rpc.PreconditionFailure", [("violations", messages(violations, toFields))])
+  | BadRequest({fieldViolations}) =>
+    let toFields = (v: fieldViolation) =>
+      [("field", s(v.field)), ("description", s(v.description)), ("reason", s(v.reason))]->Array.concat(
+        v.localizedMessage->Option.mapOr([], m => [("localizedMessage", localizedMessageToJson(m))]),
+      )
+    pack("google.rpc.BadRequest", [("fieldViolations", messages(fieldViolations, toFields))])
+  | RequestInfo(info) =>
+    pack("google.rpc.RequestInfo", [("requestId", s(info.requestId)), ("servingData", s(info.servingData))])
+  | ResourceInfo(info) =>
+    pack(
+      "google.rpc.ResourceInfo",
+      [
+        ("resourceType", s(info.resourceType)),
+        ("resourceName", s(info.resourceName)),
+        ("owner", s(info.owner)),
+        ("description", s(info.description)),
+      ],
+    )
+  | Help({links}) =>
+    pack("google.rpc.Help", [("links", messages(links, (l: link) => [("description", s(l.description)), ("url", s(l.url))]))])
+  | LocalizedMessage(m) =>
+    {typeUrl: WellKnown.Any.typeUrlPrefix ++ "google.rpc.LocalizedMessage", value: localizedMessageToJson(m)}
+  | Other(any) => any
+  }
+}
+
+// JSON body of an error response: {"code": 3, "message": ..., "details": [Any, ...]}
+let toJson = (status: t): Js.Json.t => {
+  Js.Json.object_(
+    Js.Dict.fromArray([
+      ("code", Js.Json.number(Int.toFloat(status.code))),
+      ("message", Js.Json.string(status.message)),
+      ("details", Js.Json.array(status.details->Array.map(d => WellKnown.Any.toJson(detailToAny(d))))),
+    ]),
+  )
+}
+
+// Binary details, converted to their JSON form with the field numbers of error_details.proto
+
+type rec kind =
+  | Str
+  | Int
+  | Duration
+  | Strings
+  | StringMap
+  | Bytes
+  | Message(array<wireField>)
+  | Messages(array<wireField>)
+and wireField = {number: int, name: string, kind: kind}
+
+let f = (number, name, kind): wireField => {number, name, kind}
+
+let localizedMessageFields = [f(1, "locale", Str), f(2, "message", Str)]
+
+let detailFields = Js.Dict.fromArray([
+  ("google.rpc.ErrorInfo", [f(1, "reason", Str), f(2, "domain", Str), f(3, "metadata", StringMap)]),
+  ("google.rpc.RetryInfo", [f(1, "retryDelay", Duration)]),
+  ("google.rpc.DebugInfo", [f(1, "stackEntries", Strings), f(2, "detail", Str)]),
+  ("google.rpc.QuotaFailure", [f(1, "violations", Messages([f(1, "subject", Str), f(2, "description", Str)]))]),
+  (
+    "google.rpc.PreconditionFailure",
+    [f(1, "violations", Messages([f(1, "type", Str), f(2, "subject", Str), f(3, "description", Str)]))],
+  ),
+  (
+    "google.rpc.BadRequest",
+    [
+      f(
+        1,
+        "fieldViolations",
+        Messages([
+          f(1, "field", Str),
+          f(2, "description", Str),
+          f(3, "reason", Str),
+          f(4, "localizedMessage", Message(localizedMessageFields)),
+        ]),
+      ),
+    ],
+  ),
+  ("google.rpc.RequestInfo", [f(1, "requestId", Str), f(2, "servingData", Str)]),
+  (
+    "google.rpc.ResourceInfo",
+    [f(1, "resourceType", Str), f(2, "resourceName", Str), f(3, "owner", Str), f(4, "description", Str)],
+  ),
+  ("google.rpc.Help", [f(1, "links", Messages([f(1, "description", Str), f(2, "url", Str)]))]),
+  ("google.rpc.LocalizedMessage", localizedMessageFields),
+])
+
+let statusFields = [
+  f(1, "code", Int),
+  f(2, "message", Str),
+  f(3, "details", Messages([f(1, "typeUrl", Str), f(2, "value", Bytes)])),
+]
+
+type wireValue =
+  | Varint(float)
+  | LengthDelim(Js.Typed_array.Uint8Array.t)
+
+// Varint and length-delimited fields of a message; None if the bytes are malformed
+let readFields = (bytes: Js.Typed_array.Uint8Array.t): option<array<(int, wireValue)>> => {
+  let length = Js.Typed_array.Uint8Array.length(bytes)
+  let pos = ref(0)
+  let valid = ref(true)
+  let fields = []
+  let readVarint = () => {
+    let value = ref(0.0)
+    let scale = ref(1.0)
+    let more = ref(true)
+    while more.contents && valid.contents {
+      if pos.contents >= length {
+        valid := false
+      } else {
+        let byte = Js.Typed_array.Uint8Array.unsafe_get(bytes, pos.contents)
+        pos := pos.contents + 1
+        value := value.contents +. Int.toFloat(land(byte, 0x7f)) *. scale.contents
+        scale := scale.contents *. 128.0
+        more := byte >= 0x80
+      }
+    }
+    value.contents
+  }
+
+  while valid.contents && pos.contents < length {
+    let tag = Float.toInt(readVarint())
+    switch Message.Tag.wireType(tag) {
+    | Some(Message.WireType.Varint) => fields->Array.push((Message.Tag.fieldNumber(tag), Varint(readVarint())))
+    | Some(Message.WireType.LengthDelim) =>
+      let size = Float.toInt(readVarint())
+      if pos.contents + size > length {
+        valid := false
+      } else {
+        let value = Js.Typed_array.Uint8Array.subarray(~start=pos.contents, ~end_=pos.contents + size, bytes)
+        fields->Array.push((Message.Tag.fieldNumber(tag), LengthDelim(value)))
+        pos := pos.contents + size
+      }
+    | Some(Message.WireType.Fixed64) => pos := pos.contents + 8
+    | Some(Message.WireType.Fixed32) => pos := pos.contents + 4
+    | _ => valid := false
+    }
+  }
+  valid.contents && pos.contents == length ? Some(fields) : None
+}
+
+let utf8 = (bytes: Js.Typed_array.Uint8Array.t): string => {
+  Streaming.decode(Streaming.makeTextDecoder(), bytes)
+}
+
+let rec wireToJson = (bytes: Js.Typed_array.Uint8Array.t, schema: array<wireField>): option<Js.Json.t> => {
+  readFields(bytes)->Option.map(fields => {
+    let obj = Js.Dict.empty()
+    let append = (name, value) => {
+      let items = Js.Dict.get(obj, name)->Option.flatMap(Js.Json.decodeArray)->Option.getOr([])
+      Js.Dict.set(obj, name, Js.Json.array(Array.concat(items, [value])))
+    }
+    fields->Array.forEach(((number, value)) =>
+      switch (schema->Array.find(field => field.number == number), value) {
+      | (Some({name, kind: Int}), Varint(n)) => Js.Dict.set(obj, name, Js.Json.number(n))
+      | (Some({name, kind: Str}), LengthDelim(b)) => Js.Dict.set(obj, name, Js.Json.string(utf8(b)))
+      | (Some({name, kind: Bytes}), LengthDelim(b)) => Js.Dict.set(obj, name, Json.Encode.bytes(b))
+      | (Some({name, kind: Strings}), LengthDelim(b)) => append(name, Js.Json.string(utf8(b)))
+      | (Some({name, kind: Message(nested)}), LengthDelim(b)) =>
+        wireToJson(b, nested)->Option.forEach(json => Js.Dict.set(obj, name, json))
+      | (Some({name, kind: Messages(nested)}), LengthDelim(b)) => wireToJson(b, nested)->Option.forEach(append(name, _))
+      | (Some({name, kind: StringMap}), LengthDelim(b)) =>
+        switch wireToJson(b, [f(1, "key", Str), f(2, "value", Str)])->Option.flatMap(Js.Json.decodeObject) {
+        | Some(entry) =>
+          let map = Js.Dict.get(obj, name)->Option.flatMap(Js.Json.decodeObject)->Option.getOr(Js.Dict.empty())
+          Js.Dict.set(map, str(entry, "key"), Js.Json.string(str(entry, "value")))
+          Js.Dict.set(obj, name, Js.Json.object_(map))
+        | None => ()
+        }
+      | (Some({name, kind: Duration}), LengthDelim(b)) =>
+        switch wireToJson(b, [f(1, "seconds", Int), f(2, "nanos", Int)])->Option.flatMap(Js.Json.decodeObject) {
+        | Some(d) =>
+          let part = key => Js.Dict.get(d, key)->Option.flatMap(Js.Json.decodeNumber)->Option.getOr(0.0)
+          let duration: WellKnown.PreciseDuration.t = {
+            seconds: BigInt.fromFloat(part("seconds")),
+            nanos: Float.toInt(part("nanos")),
+          }
+          Js.Dict.set(obj, name, WellKnown.PreciseDuration.toJson(duration))
+        | None => ()
+        }
+      | _ => ()
+      }
+    )
+    Js.Json.object_(obj)
+  })
+}
+
+// Detail from its type name and binary value; unknown types keep the bytes under "$unknown"
+let detailOfBinary = (typeUrl: string, value: Js.Typed_array.Uint8Array.t): detail => {
+  let raw = () => Js.Json.object_(Js.Dict.fromArray([("$unknown", Json.Encode.bytes(value))]))
+  let any: WellKnown.Any.t = {typeUrl, value: raw()}
+  switch Js.Dict.get(detailFields, WellKnown.Any.typeName(any))->Option.flatMap(wireToJson(value, _)) {
+  | Some(json) => detailOfAny({typeUrl, value: json})
+  | None => Other(any)
+  }
+}
+
+// Decoding error responses
+
+// `message` and `details` of an error body; None unless `message` is a string and
+// `details` an array of objects with a string `typeKey`
+let bodyFields = (obj: Js.Dict.t<Js.Json.t>, ~typeKey: string): option<(string, array<Js.Dict.t<Js.Json.t>>)> => {
+  let message = switch Js.Dict.get(obj, "message") {
+  | Some(message) => Js.Json.decodeString(message)
+  | None => Some("")
+  }
+  let isDetail = detail => Js.Dict.get(detail, typeKey)->Option.flatMap(Js.Json.decodeString)->Option.isSome
+  let details = switch Js.Dict.get(obj, "details") {
+  | Some(details) =>
+    Js.Json.decodeArray(details)->Option.flatMap(items => {
+      let details = items->Array.filterMap(Js.Json.decodeObject)->Array.filter(isDetail)
+      Array.length(details) == Array.length(items) ? Some(details) : None
+    })
+  | None => Some([])
+  }
+  switch (message, details) {
+  | (Some(message), Some(details)) => Some((message, details))
+  | _ => None
+  }
+}
+
+// google.rpc.Status JSON; a body such as a proxy's {"code": 404} is not one
+let fromJson = (json: Js.Json.t): option<t> => {
+  let obj = Js.Json.decodeObject(json)
+  let code =
+    obj
+    ->Option.flatMap(obj => Js.Dict.get(obj, "code"))
+    ->Option.flatMap(Js.Json.decodeNumber)
+    ->Option.filter(code => Math.floor(code) == code && code >= 0.0 && code <= 16.0)
+  switch (obj, code) {
+  | (Some(obj), Some(code)) =>
+    bodyFields(obj, ~typeKey="@type")->Option.map(((message, details)) => {
+      code: Float.toInt(code),
+      message,
+      details: details
+      ->Array.filterMap(detail => WellKnown.Any.fromJson(Js.Json.object_(detail)))
+      ->Array.map(detailOfAny),
+    })
+  | _ => None
+  }
+}
+
+// Binary google.rpc.Status, as sent in grpc-status-details-bin
+let fromBinary = (bytes: Js.Typed_array.Uint8Array.t): option<t> => {
+  wireToJson(bytes, statusFields)
+  ->Option.flatMap(Js.Json.decodeObject)
+  ->Option.map(obj => {
+    code: Js.Dict.get(obj, "code")->Option.flatMap(Js.Json.decodeNumber)->Option.mapOr(0, Float.toInt),
+    message: str(obj, "message"),
+    details: objects(obj, "details")->Array.map(any =>
+      detailOfBinary(
+        str(any, "typeUrl"),
+        Js.Dict.get(any, "value")
+        ->Option.flatMap(Json.Decode.bytes)
+        ->Option.getOr(Js.Typed_array.Uint8Array.make([])),
+      )
+    ),
+  })
+}
+
+// Connect error JSON: {"code": "invalid_argument", "message": ..., "details": [{"type", "value", "debug"}]}
+let fromConnectJson = (json: Js.Json.t): option<t> => {
+  let obj = Js.Json.decodeObject(json)
+  let code =
+    obj
+    ->Option.flatMap(obj => Js.Dict.get(obj, "code"))
+    ->Option.flatMap(Js.Json.decodeString)
+    ->Option.flatMap(name => Retry.codeFromName(String.toUpperCase(name)))
+  switch (obj, code) {
+  | (Some(obj), Some(code)) =>
+    bodyFields(obj, ~typeKey="type")->Option.map(((message, details)) => {
+      let details = details->Array.map(detail => {
+        let typeUrl = WellKnown.Any.typeUrlPrefix ++ str(detail, "type")
+        switch (Js.Dict.get(detail, "value")->Option.flatMap(Json.Decode.bytes), Js.Dict.get(detail, "debug")) {
+        | (Some(value), _) => detailOfBinary(typeUrl, value)
+        | (None, Some(debug)) => detailOfAny({typeUrl, value: debug})
+        | (None, None) => Other({typeUrl, value: Js.Json.object_(Js.Dict.empty())})
+        }
+      })
+      {code, message, details}
+    })
+  | _ => None
+  }
+}
+
+// Status of an error response: the grpc-status-details-bin header, a Status or
+// Connect error JSON body, or the plain grpc-status and grpc-message headers
+let fromResponse = (~header: string => option<string>, ~body: string): option<t> => {
+  let fromBody = () =>
+    try {
+      let json = Js.Json.parseExn(body)
+      fromJson(json)->Option.orElse(fromConnectJson(json))
+    } catch {
+    | _ => None
+    }
+  let fromHeaders = () =>
+    header("grpc-status")
+    ->Option.flatMap(code => Int.fromString(code))
+    ->Option.map(code => {
+      code,
+      message: header("grpc-message")->Option.mapOr("", message =>
+        try {
+          decodeURIComponent(message)
+        } catch {
+        | _ => message
+        }
+      ),
+      details: [],
+    })
+
+  header("grpc-status-details-bin")
+  ->Option.flatMap(value => Json.Decode.bytes(Js.Json.string(value)))
+  ->Option.flatMap(fromBinary)
+  ->Option.orElse(fromBody())
+  ->Option.orElse(fromHeaders())
+}
+
+// Encoding error responses
+
+type textEncoder
+@new external makeTextEncoder: unit => textEncoder = "TextEncoder"
+@send external encodeUtf8: (textEncoder, string) => Js.Typed_array.Uint8Array.t = "encode"
+
+let toArray = (bytes: Js.Typed_array.Uint8Array.t): array<int> =>
+  Array.fromInitializer(~length=Js.Typed_array.Uint8Array.length(bytes), i =>
+    Js.Typed_array.Uint8Array.unsafe_get(bytes, i)
+  )
+
+// Binary form of the JSON produced by wireToJson; default values are left out
+let rec jsonToWire = (obj: Js.Dict.t<Js.Json.t>, schema: array<wireField>): array<int> => {
+  let out = []
+  let push = bytes => bytes->Array.forEach(byte => out->Array.push(byte))
+  let delimited = (number, bytes) => {
+    push(Message.Varint.encode(Message.Tag.make(number, Message.WireType.LengthDelim)))
+    push(Message.Varint.encode(Array.length(bytes)))
+    push(bytes)
+  }
+  let text = s => toArray(encodeUtf8(makeTextEncoder(), s))
+  let nested = (number, fields, json) =>
+    Js.Json.decodeObject(json)->Option.forEach(obj => delimited(number, jsonToWire(obj, fields)))
+  let items = json => Js.Json.decodeArray(json)->Option.getOr([])
+  schema->Array.forEach(({number, name, kind}) =>
+    switch (kind, Js.Dict.get(obj, name)) {
+    | (Int, Some(value)) =>
+      switch Js.Json.decodeNumber(value) {
+      | Some(n) if n != 0.0 =>
+        push(Message.Varint.encode(Message.Tag.make(number, Message.WireType.Varint)))
+        push(Message.Varint.encode(Float.toInt(n)))
+      | _ => ()
+      }
+    | (Str, Some(value)) =>
+      switch Js.Json.decodeString(value) {
+      | Some(s) if s != "" => delimited(number, text(s))
+      | _ => ()
+      }
+    | (Bytes, Some(value)) => Json.Decode.bytes(value)->Option.forEach(bytes => delimited(number, toArray(bytes)))
+    | (Strings, Some(value)) =>
+      items(value)->Array.filterMap(Js.Json.decodeString)->Array.forEach(s => delimited(number, text(s)))
+    | (Message(fields), Some(value)) => nested(number, fields, value)
+    | (Messages(fields), Some(value)) => items(value)->Array.forEach(nested(number, fields, _))
+    | (StringMap, Some(value)) =>
+      Js.Json.decodeObject(value)
+      ->Option.getOr(Js.Dict.empty())
+      ->Js.Dict.entries
+      ->Array.forEach(((key, value)) =>
+        nested(
+          number,
+          [f(1, "key", Str), f(2, "value", Str)],
+          Js.Json.object_(Js.Dict.fromArray([("key", Js.Json.string(key)), ("value", value)])),
+        )
+      )
+    | (Duration, Some(value)) =>
+      WellKnown.PreciseDuration.fromJson(value)->Option.forEach(duration =>
+        nested(
+          number,
+          [f(1, "seconds", Int), f(2, "nanos", Int)],
+          Js.Json.object_(
+            Js.Dict.fromArray([
+              ("seconds", Js.Json.number(BigInt.toFloat(duration.seconds))),
+              ("nanos", Js.Json.number(Int.toFloat(duration.nanos))),
+            ]),
+          ),
+        )
+      )
+    | (_, None) => ()
+    }
+  )
+  out
+}
+
+// Binary google.rpc.Status for grpc-status-details-bin; Other details are kept
+// only when they still hold their bytes (see detailOfBinary)
+let toBinary = (status: t): Js.Typed_array.Uint8Array.t => {
+  let details = status.details->Array.filterMap(detail => {
+    let any = detailToAny(detail)
+    let obj = Js.Json.decodeObject(any.value)->Option.getOr(Js.Dict.empty())
+    let value = switch (Js.Dict.get(detailFields, WellKnown.Any.typeName(any)), Js.Dict.get(obj, "$unknown")) {
+    | (Some(fields), _) => Some(Json.Encode.bytes(Js.Typed_array.Uint8Array.make(jsonToWire(obj, fields))))
+    | (None, unknown) => unknown
+    }
+    value->Option.map(value =>
+      Js.Json.object_(Js.Dict.fromArray([("typeUrl", Js.Json.string(any.typeUrl)), ("value", value)]))
+    )
+  })
+  let json = Js.Dict.fromArray([
+    ("code", Js.Json.number(Int.toFloat(status.code))),
+    ("message", Js.Json.string(status.message)),
+    ("details", Js.Json.array(details)),
+  ])
+  Js.Typed_array.Uint8Array.make(jsonToWire(json, statusFields))
+}
+
+// grpc-status, grpc-message and, with details, grpc-status-details-bin (unpadded
+// base64, as the gRPC spec asks of senders)
+let toHeaders = (status: t): array<(string, string)> => {
+  let headers = [("grpc-status", Int.toString(status.code))]
+  if status.message != "" {
+    headers->Array.push(("grpc-message", encodeURIComponent(status.message)))
+  }
+  if Array.length(status.details) > 0 {
+    switch Js.Json.decodeString(Json.Encode.bytes(toBinary(status))) {
+    | Some(base64) => headers->Array.push(("grpc-status-details-bin", String.replaceRegExp(base64, %re("/=+$/"), "")))
+    | None => ()
+    }
+  }
+  headers
+}
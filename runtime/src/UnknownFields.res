@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MPL-2.0
+// Unknown field preservation for lossless binary round-trips
+// https://protobuf.dev/programming-guides/proto3/#unknowns
+
+// Raw wire bytes of fields the schema does not know about
+type t = Js.Typed_array.Uint8Array.t
+
+// Key used by the WASM codec to carry unknown fields in its JSON format
+let key = "$unknown"
+
+// Unknown fields as JSON object fields (used by generated toJsonWith)
+let toJsonFields = (unknown: option<t>): array<(string, Js.Json.t)> => {
+  switch unknown {
+  | Some(bytes) => [(key, Json.Encode.bytes(bytes))]
+  | None => []
+  }
+}
+
+// Read unknown fields from a decoded JSON object (used by generated fromJson)
+let fromJsonObject = (obj: Js.Dict.t<Js.Json.t>): option<t> => {
+  Js.Dict.get(obj, key)->Option.flatMap(Json.Decode.bytes)
+}
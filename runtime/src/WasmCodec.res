@@ -20,18 +20,40 @@ type rec fieldType =
   | Bytes
   | Enum
   | Message(array<fieldDescriptor>)
+  // Proto2 group, encoded with START_GROUP/END_GROUP wire types
+  | Group(array<fieldDescriptor>)
+  // Well-known type by full name, e.g. WellKnown("google.protobuf.Timestamp");
+  // values use the same JSON as the WellKnown.* codecs
+  | WellKnown(string)
 and fieldDescriptor = {
   n: int,
   name: string,
   fieldType: fieldType,
   repeated: bool,
   optional: bool,
+  // Repeated numeric fields are packed unless set to false (proto2 defaults)
+  packed?: bool,
+  // Oneof group of a member field, the record field of the generated variant.
+  // Members keep their presence and at most one may be set; decode yields the
+  // flat proto3 JSON member key, encode also accepts the variant shape
+  // {"TAG": variant, "_0": value}, with the constructor defaulting to the
+  // capitalized field name
+  oneof?: string,
+  variant?: string,
+  // Fully-qualified proto name of a message field's type, needed for extensions
+  typeName?: string,
 }
 
 // WASM module interface
 type wasmCodec = {
   encode: (string, string) => string,
   decode: (string, string) => string,
+  // Same as encode/decode, but with JS values and bytes instead of JSON and base64 strings
+  encode_object: (string, Js.Json.t) => Js.TypedArray2.Uint8Array.t,
+  decode_object: (string, Js.TypedArray2.Uint8Array.t) => Js.Json.t,
+  register_extension: (string, string) => unit,
+  register_type: string => unit,
+  clear_extensions: unit => unit,
 }
 
 // Global WASM instance (set after loading)
@@ -61,6 +83,8 @@ let fieldTypeToString = (ft: fieldType): string => {
   | Bytes => "bytes"
   | Enum => "enum"
   | Message(_) => "message"
+  | Group(_) => "group"
+  | WellKnown(_) => "message"
   }
 }
 
@@ -74,25 +98,97 @@ let rec fieldToJson = (field: fieldDescriptor): Js.Json.t => {
   Js.Dict.set(d, "optional", Js.Json.boolean(field.optional))
 
   switch field.fieldType {
-  | Message(nested) =>
+  | Message(nested) | Group(nested) =>
     Js.Dict.set(d, "fields", Js.Json.array(Array.map(nested, fieldToJson)))
   | _ => ()
   }
 
+  switch (field.fieldType, field.typeName) {
+  | (WellKnown(typeName), _) | (_, Some(typeName)) =>
+    Js.Dict.set(d, "typeName", Js.Json.string(typeName))
+  | _ => ()
+  }
+
+  switch field.packed {
+  | Some(packed) => Js.Dict.set(d, "packed", Js.Json.boolean(packed))
+  | None => ()
+  }
+
+  switch field.oneof {
+  | Some(oneof) => Js.Dict.set(d, "oneof", Js.Json.string(oneof))
+  | None => ()
+  }
+
+  switch field.variant {
+  | Some(variant) => Js.Dict.set(d, "variant", Js.Json.string(variant))
+  | None => ()
+  }
+
   Js.Json.object_(d)
 }
 
+// Default JSON of a field without presence, which binary decode leaves out
+let defaultJson = (fieldType: fieldType): option<Js.Json.t> => {
+  switch fieldType {
+  | Int64 | Uint64 | Sint64 | Fixed64 | Sfixed64 => Some(Js.Json.string("0"))
+  | Int32 | Uint32 | Sint32 | Fixed32 | Sfixed32 | Float | Double | Enum => Some(Js.Json.number(0.0))
+  | Bool => Some(Js.Json.boolean(false))
+  | String | Bytes => Some(Js.Json.string(""))
+  | Message(_) | Group(_) | WellKnown(_) => None
+  }
+}
+
+// Add the defaults binary decode leaves out, in nested messages too, so the
+// result has every key the generated fromJson requires
+let rec withDefaults = (json: Js.Json.t, schema: array<fieldDescriptor>): Js.Json.t => {
+  switch Js.Json.decodeObject(json) {
+  | Some(obj) =>
+    let out = Js.Dict.fromArray(Js.Dict.entries(obj))
+    Array.forEach(schema, field => {
+      switch (field.fieldType, Js.Dict.get(out, field.name)) {
+      | (Message(nested) | Group(nested), Some(value)) =>
+        let filled = switch Js.Json.decodeArray(value) {
+        | Some(values) if field.repeated => Js.Json.array(Array.map(values, v => withDefaults(v, nested)))
+        | _ => withDefaults(value, nested)
+        }
+        Js.Dict.set(out, field.name, filled)
+      | (fieldType, None) if !field.repeated && !field.optional && Option.isNone(field.oneof) =>
+        switch defaultJson(fieldType) {
+        | Some(value) => Js.Dict.set(out, field.name, value)
+        | None => ()
+        }
+      | _ => ()
+      }
+    })
+    Js.Json.object_(out)
+  | None => json
+  }
+}
+
 // Convert schema to JSON string
-let schemaToString = (fields: array<fieldDescriptor>): string => {
-  Js.Json.stringify(Js.Json.array(Array.map(fields, fieldToJson)))
+// With a typeName the object form is used so registered extensions are encoded/decoded
+let schemaToString = (~typeName: option<string>=?, fields: array<fieldDescriptor>): string => {
+  let fieldsJson = Js.Json.array(Array.map(fields, fieldToJson))
+  switch typeName {
+  | Some(name) =>
+    let d = Js.Dict.empty()
+    Js.Dict.set(d, "typeName", Js.Json.string(name))
+    Js.Dict.set(d, "fields", fieldsJson)
+    Js.Json.stringify(Js.Json.object_(d))
+  | None => Js.Json.stringify(fieldsJson)
+  }
 }
 
 // Encode a message to protobuf binary (base64)
-let encode = (~schema: array<fieldDescriptor>, ~data: Js.Json.t): result<string, string> => {
+let encode = (
+  ~schema: array<fieldDescriptor>,
+  ~typeName: option<string>=?,
+  ~data: Js.Json.t,
+): result<string, string> => {
   switch wasmInstance.contents {
   | Some(wasm) =>
     try {
-      let schemaStr = schemaToString(schema)
+      let schemaStr = schemaToString(~typeName?, schema)
       let dataStr = Js.Json.stringify(data)
       Ok(wasm.encode(schemaStr, dataStr))
     } catch {
@@ -103,11 +199,15 @@ let encode = (~schema: array<fieldDescriptor>, ~data: Js.Json.t): result<string,
 }
 
 // Decode protobuf binary (base64) to JSON
-let decode = (~schema: array<fieldDescriptor>, ~data: string): result<Js.Json.t, string> => {
+let decode = (
+  ~schema: array<fieldDescriptor>,
+  ~typeName: option<string>=?,
+  ~data: string,
+): result<Js.Json.t, string> => {
   switch wasmInstance.contents {
   | Some(wasm) =>
     try {
-      let schemaStr = schemaToString(schema)
+      let schemaStr = schemaToString(~typeName?, schema)
       let jsonStr = wasm.decode(schemaStr, data)
       switch Js.Json.parseExn(jsonStr) {
       | json => Ok(json)
@@ -120,6 +220,71 @@ let decode = (~schema: array<fieldDescriptor>, ~data: string): result<Js.Json.t,
   }
 }
 
+// Encode a message to protobuf binary bytes
+// Skips JSON.stringify and base64, which dominate the cost for large messages
+let encodeBytes = (
+  ~schema: array<fieldDescriptor>,
+  ~typeName: option<string>=?,
+  ~data: Js.Json.t,
+): result<Js.TypedArray2.Uint8Array.t, string> => {
+  switch wasmInstance.contents {
+  | Some(wasm) =>
+    try {
+      Ok(wasm.encode_object(schemaToString(~typeName?, schema), data))
+    } catch {
+    | Exn.Error(e) => Error(Exn.message(e)->Option.getOr("Encode error"))
+    }
+  | None => Error("WASM codec not initialized")
+  }
+}
+
+// Decode protobuf binary bytes to JSON, built directly as JS objects
+let decodeBytes = (
+  ~schema: array<fieldDescriptor>,
+  ~typeName: option<string>=?,
+  ~data: Js.TypedArray2.Uint8Array.t,
+): result<Js.Json.t, string> => {
+  switch wasmInstance.contents {
+  | Some(wasm) =>
+    try {
+      Ok(wasm.decode_object(schemaToString(~typeName?, schema), data))
+    } catch {
+    | Exn.Error(e) => Error(Exn.message(e)->Option.getOr("Decode error"))
+    }
+  | None => Error("WASM codec not initialized")
+  }
+}
+
+// Register an extension field so binary decode keeps it instead of skipping it
+// The field name must be the proto3 JSON key, e.g. "[example.priority]"
+let registerExtension = (~extendee: string, field: fieldDescriptor): result<unit, string> => {
+  switch wasmInstance.contents {
+  | Some(wasm) =>
+    try {
+      wasm.register_extension(extendee, Js.Json.stringify(fieldToJson(field)))
+      Ok()
+    } catch {
+    | Exn.Error(e) => Error(Exn.message(e)->Option.getOr("Register extension error"))
+    }
+  | None => Error("WASM codec not initialized")
+  }
+}
+
+// Register a message type that may be packed into google.protobuf.Any, so
+// binary Any values of that type decode to inline fields next to "@type"
+let registerType = (~typeName: string, schema: array<fieldDescriptor>): result<unit, string> => {
+  switch wasmInstance.contents {
+  | Some(wasm) =>
+    try {
+      wasm.register_type(schemaToString(~typeName, schema))
+      Ok()
+    } catch {
+    | Exn.Error(e) => Error(Exn.message(e)->Option.getOr("Register type error"))
+    }
+  | None => Error("WASM codec not initialized")
+  }
+}
+
 // Check if WASM codec is initialized
 let isInitialized = (): bool => {
   Option.isSome(wasmInstance.contents)
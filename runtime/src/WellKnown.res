@@ -88,6 +88,131 @@ module Duration = {
   }
 }
 
+// Fraction of a second for JSON, with 0, 3, 6 or 9 digits like the reference implementations
+let nanosFraction = (nanos: int): string => {
+  let digits = Int.toString(nanos)->String.padStart(9, "0")
+  if nanos == 0 {
+    ""
+  } else if mod(nanos, 1000000) == 0 {
+    "." ++ String.slice(digits, ~start=0, ~end=3)
+  } else if mod(nanos, 1000) == 0 {
+    "." ++ String.slice(digits, ~start=0, ~end=6)
+  } else {
+    "." ++ digits
+  }
+}
+
+// Nanos of up to 9 fraction digits ("01" -> 10000000)
+let parseNanos = (digits: Js.Nullable.t<string>): option<int> => {
+  switch Js.Nullable.toOption(digits) {
+  | Some(digits) => Int.fromString(String.padEnd(digits, 9, "0"))
+  | None => Some(0)
+  }
+}
+
+// Timestamp with exact seconds/nanos (`precise_time` option)
+module PreciseTimestamp = {
+  // Same fields as timestamp.proto: seconds since the Unix epoch, nanos in 0..999999999
+  // JSON format: "2017-01-15T01:30:15.010000001Z", years 0001 to 9999
+  type t = {seconds: bigint, nanos: int}
+
+  let make = (~seconds: bigint, ~nanos: int=0): t => {seconds, nanos}
+
+  let toJson = (timestamp: t): Js.Json.t => {
+    // Whole seconds are exact as a float, only the fraction needs nanos
+    let date = Js.Date.fromFloat(BigInt.toFloat(timestamp.seconds) *. 1000.0)
+    let base = String.slice(Js.Date.toISOString(date), ~start=0, ~end=19)
+    Js.Json.string(base ++ nanosFraction(timestamp.nanos) ++ "Z")
+  }
+
+  let fromJson = (json: Js.Json.t): option<t> => {
+    let pattern = %re("/^(\d{4}-\d\d-\d\d[Tt]\d\d:\d\d:\d\d)(?:\.(\d{1,9}))?([Zz]|[+-]\d\d:\d\d)$/")
+    switch Js.Json.decodeString(json)->Option.flatMap(s => Js.Re.exec_(pattern, s)) {
+    | Some(result) =>
+      let captures = Js.Re.captures(result)
+      let base = captures[1]->Option.flatMap(Js.Nullable.toOption)->Option.getOr("")
+      let offset = captures[3]->Option.flatMap(Js.Nullable.toOption)->Option.getOr("Z")
+      let millis = Js.Date.getTime(Js.Date.fromString(String.toUpperCase(base ++ offset)))
+      switch (Float.isNaN(millis), captures[2]->Option.flatMap(parseNanos)) {
+      | (false, Some(nanos)) => Some({seconds: BigInt.fromFloat(millis /. 1000.0), nanos})
+      | _ => None
+      }
+    | None => None
+    }
+  }
+
+  // Millisecond precision, like Timestamp
+  let fromDate = (date: Js.Date.t): t => {
+    let millis = Js.Date.getTime(date)
+    let seconds = Math.floor(millis /. 1000.0)
+    {seconds: BigInt.fromFloat(seconds), nanos: Float.toInt((millis -. seconds *. 1000.0) *. 1000000.0)}
+  }
+
+  let toDate = (timestamp: t): Js.Date.t => {
+    Timestamp.fromSecondsNanos(timestamp.seconds, timestamp.nanos)
+  }
+
+  // Orders by seconds, then nanos
+  let compare = (a: t, b: t): int => {
+    switch Pervasives.compare(a.seconds, b.seconds) {
+    | 0 => Pervasives.compare(a.nanos, b.nanos)
+    | order => order
+    }
+  }
+}
+
+// Duration with exact seconds/nanos (`precise_time` option)
+module PreciseDuration = {
+  // Same fields as duration.proto: seconds and nanos carry the same sign
+  // JSON format: "1.000000001s", "-0.5s"
+  type t = {seconds: bigint, nanos: int}
+
+  let make = (~seconds: bigint, ~nanos: int=0): t => {seconds, nanos}
+
+  let toJson = (duration: t): Js.Json.t => {
+    let seconds = BigInt.toString(duration.seconds)
+    let negative = String.startsWith(seconds, "-") || duration.nanos < 0
+    let whole = String.startsWith(seconds, "-") ? String.sliceToEnd(seconds, ~start=1) : seconds
+    let sign = negative ? "-" : ""
+    Js.Json.string(sign ++ whole ++ nanosFraction(Math.Int.abs(duration.nanos)) ++ "s")
+  }
+
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Js.Json.decodeString(json)->Option.flatMap(s => Js.Re.exec_(%re("/^(-?)(\d+)(?:\.(\d{1,9}))?s$/"), s)) {
+    | Some(result) =>
+      let captures = Js.Re.captures(result)
+      let sign = captures[1]->Option.flatMap(Js.Nullable.toOption)->Option.getOr("")
+      let whole = captures[2]->Option.flatMap(Js.Nullable.toOption)->Option.getOr("0")
+      switch captures[3]->Option.flatMap(parseNanos) {
+      | Some(nanos) =>
+        Some({
+          seconds: BigInt.fromStringExn(sign ++ whole),
+          nanos: sign == "-" ? -nanos : nanos,
+        })
+      | None => None
+      }
+    | None => None
+    }
+  }
+
+  // Float seconds, like Duration (loses precision beyond microseconds for large values)
+  let fromSeconds = (seconds: float): t => {
+    let whole = Math.trunc(seconds)
+    {seconds: BigInt.fromFloat(whole), nanos: Float.toInt(Math.round((seconds -. whole) *. 1000000000.0))}
+  }
+
+  let toSeconds = (duration: t): float => {
+    Duration.fromSecondsNanos(duration.seconds, duration.nanos)
+  }
+
+  let compare = (a: t, b: t): int => {
+    switch Pervasives.compare(a.seconds, b.seconds) {
+    | 0 => Pervasives.compare(a.nanos, b.nanos)
+    | order => order
+    }
+  }
+}
+
 // Struct - JSON object
 module Struct = {
   // Struct is represented as Js.Dict.t<Js.Json.t> in ReScript
@@ -154,6 +279,89 @@ module Any = {
   let make = (~typeUrl: string, ~value: Js.Json.t): t => {
     {typeUrl, value}
   }
+
+  // A message type that can be packed into Any
+  // Generated as `anyType` in each message module with the any_types option
+  type messageType<'a> = {
+    // Fully-qualified proto name, e.g. "example.User"
+    typeName: string,
+    toJson: 'a => Js.Json.t,
+    fromJson: Js.Json.t => option<'a>,
+  }
+
+  let typeUrlPrefix = "type.googleapis.com/"
+
+  // Type name part of the type URL: "type.googleapis.com/example.User" -> "example.User"
+  let typeName = (any: t): string => {
+    switch String.lastIndexOf(any.typeUrl, "/") {
+    | -1 => any.typeUrl
+    | i => String.sliceToEnd(any.typeUrl, ~start=i + 1)
+    }
+  }
+
+  // Well-known types keep their JSON under "value" instead of inline fields
+  let isWellKnown = (typeName: string): bool => String.startsWith(typeName, "google.protobuf.")
+
+  let pack = (messageType: messageType<'a>, message: 'a): t => {
+    let json = messageType.toJson(message)
+    {
+      typeUrl: typeUrlPrefix ++ messageType.typeName,
+      value: isWellKnown(messageType.typeName)
+        ? Js.Json.object_(Js.Dict.fromArray([("value", json)]))
+        : json,
+    }
+  }
+
+  // JSON of the packed message, the inline fields or the well-known "value"
+  let packedJson = (any: t): option<Js.Json.t> => {
+    if isWellKnown(typeName(any)) {
+      Js.Json.decodeObject(any.value)->Option.flatMap(obj => Js.Dict.get(obj, "value"))
+    } else {
+      Some(any.value)
+    }
+  }
+
+  // Holds a message of this type?
+  let is = (any: t, messageType: messageType<'a>): bool => typeName(any) == messageType.typeName
+
+  // The message, if the Any holds this type and its fields decode
+  let unpack = (any: t, messageType: messageType<'a>): option<'a> => {
+    is(any, messageType) ? packedJson(any)->Option.flatMap(messageType.fromJson) : None
+  }
+
+  // Registry of message types, keyed by type name
+  module Registry = {
+    type entry = {
+      typeName: string,
+      // Whether a packed value decodes as this type
+      check: Js.Json.t => bool,
+    }
+
+    type t = Js.Dict.t<entry>
+
+    let make = (): t => Js.Dict.empty()
+
+    // Registry filled by generated AnyTypes.register functions
+    let global: t = make()
+
+    let register = (registry: t, messageType: messageType<'a>): unit => {
+      let check = json => Option.isSome(messageType.fromJson(json))
+      Js.Dict.set(registry, messageType.typeName, {typeName: messageType.typeName, check})
+    }
+
+    let find = (registry: t, typeName: string): option<entry> => Js.Dict.get(registry, typeName)
+  }
+
+  // Register a message type globally
+  let register = (messageType: messageType<'a>): unit => Registry.register(Registry.global, messageType)
+
+  // Holds a registered type whose fields decode?
+  let isKnown = (any: t): bool => {
+    switch (Registry.find(Registry.global, typeName(any)), packedJson(any)) {
+    | (Some(entry), Some(json)) => entry.check(json)
+    | _ => false
+    }
+  }
 }
 
 // Empty - empty object
@@ -169,3 +377,47 @@ module Empty = {
     Some()
   }
 }
+
+// FieldMask - comma-separated camelCase paths in JSON
+module FieldMask = {
+  // FieldMask is represented as its paths with proto field names ("address.postal_code")
+  // JSON format: "address.postalCode,name"
+  type t = array<string>
+
+  let toCamelCase = (path: string): string => {
+    Js.String2.unsafeReplaceBy0(path, %re("/_([a-z0-9])/g"), (matched, _, _) =>
+      Js.String2.toUpperCase(Js.String2.sliceToEnd(matched, ~from=1))
+    )
+  }
+
+  let toSnakeCase = (path: string): string => {
+    Js.String2.unsafeReplaceBy0(path, %re("/[A-Z]/g"), (matched, _, _) =>
+      "_" ++ Js.String2.toLowerCase(matched)
+    )
+  }
+
+  let toJson = (mask: t): Js.Json.t => {
+    Js.Json.string(mask->Array.map(toCamelCase)->Js.Array2.joinWith(","))
+  }
+
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Js.Json.decodeString(json) {
+    | Some("") => Some([])
+    | Some(s) => Some(String.split(s, ",")->Array.map(toSnakeCase))
+    | None => None
+    }
+  }
+
+  // Whether the mask names the field itself (not just paths below it)
+  let covers = (mask: t, field: string): bool => Array.includes(mask, field)
+
+  // Paths below a message field, relative to it ("address.city" -> "city")
+  let subMask = (mask: t, field: string): t => {
+    let prefix = field ++ "."
+    mask->Array.filterMap(path =>
+      String.startsWith(path, prefix)
+        ? Some(String.sliceToEnd(path, ~start=String.length(prefix)))
+        : None
+    )
+  }
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MPL-2.0
+open NodeTest
+
+test("header uses milliseconds up to 8 digits, then whole seconds", async () => {
+  equal(Deadline.header(1500), "1500m")
+  equal(Deadline.header(99999999), "99999999m")
+  equal(Deadline.header(100000000), "100000S")
+})
+
+test("remainingMs counts down from the timeout", async () => {
+  let deadline = Deadline.make(~timeoutMs=1000, ())
+  let remaining = Deadline.remainingMs(deadline)->Option.getOr(-1)
+  ok(remaining > 0 && remaining <= 1000)
+  equal(Deadline.remainingMs(Deadline.make()), None)
+  Deadline.clear(deadline)
+})
+
+test("the caller's signal aborts the call until it is cleared", async () => {
+  let caller = Fetch.AbortController.make()
+  let deadline = Deadline.make(~signal=Fetch.AbortController.signal(caller), ())
+  let other = Deadline.make(~signal=Fetch.AbortController.signal(caller), ())
+  Deadline.clear(deadline)
+  Fetch.AbortController.abort(caller)
+  equal(Deadline.aborted(deadline), false)
+  equal(Deadline.aborted(other), true)
+  equal(Deadline.timedOut(other), false)
+})
+
+test("the timer marks the call as timed out", async () => {
+  let deadline = Deadline.make(~timeoutMs=1, ())
+  await Retry.sleep(20)
+  equal(Deadline.timedOut(deadline), true)
+  equal(Deadline.aborted(deadline), true)
+  equal(Deadline.remainingMs(deadline), Some(0))
+})
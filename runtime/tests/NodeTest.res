@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: MPL-2.0
+// node:test bindings for the runtime tests (`npm test` runs tests/*_test.mjs)
+
+@module("node:test") external test: (string, unit => promise<unit>) => unit = "test"
+
+@module("node:assert/strict") external equal: ('a, 'a) => unit = "deepStrictEqual"
+@module("node:assert/strict") external ok: bool => unit = "ok"
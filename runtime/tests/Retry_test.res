@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MPL-2.0
+open NodeTest
+
+let slow = {...Retry.defaultPolicy, initialBackoffMs: 60000, maxBackoffMs: 60000}
+
+test("the default policy only applies to idempotent methods", async () => {
+  let config = Some({Retry.policy: Retry.defaultPolicy})
+  equal(Retry.policyFor(config, ~method="UserService/GetUser", ~idempotent=true), Some(Retry.defaultPolicy))
+  equal(Retry.policyFor(config, ~method="UserService/CreateUser", ~idempotent=false), None)
+})
+
+test("idempotent methods retry without a configured policy", async () => {
+  let policy = config => Retry.policyFor(config, ~method="UserService/GetUser", ~idempotent=true)
+  equal(policy(None), Some(Retry.defaultPolicy))
+  equal(policy(Some({Retry.methods: Js.Dict.empty()})), Some(Retry.defaultPolicy))
+  equal(policy(Some({Retry.policy: slow})), Some(slow))
+  equal(Retry.policyFor(None, ~method="UserService/CreateUser", ~idempotent=false), None)
+})
+
+test("service config policies apply to every method they name", async () => {
+  let json = Js.Json.parseExn(`{"methodConfig": [
+    {"name": [{"service": "UserService", "method": "CreateUser"}], "retryPolicy": {
+      "maxAttempts": 2, "initialBackoff": "0.1s", "maxBackoff": "1s", "backoffMultiplier": 2,
+      "retryableStatusCodes": ["UNAVAILABLE"]}},
+    {"name": [{"service": "UserService"}], "retryPolicy": {
+      "maxAttempts": 4, "initialBackoff": "0.1s", "maxBackoff": "1s", "backoffMultiplier": 2,
+      "retryableStatusCodes": ["UNAVAILABLE"]}}
+  ]}`)
+  let config = Some(Retry.fromServiceConfig(json, ~service="UserService"))
+  let attempts = method =>
+    Retry.policyFor(config, ~method, ~idempotent=false)->Option.map(policy => policy.maxAttempts)
+  equal(attempts("UserService/CreateUser"), Some(2))
+  equal(attempts("UserService/DeleteUser"), Some(4))
+})
+
+test("an abort during the backoff ends the call", async () => {
+  let controller = Fetch.AbortController.make()
+  let calls = ref(0)
+  let started = Date.now()
+  let result = await Retry.run(
+    slow,
+    ~status=code => Some(code),
+    ~signal=Fetch.AbortController.signal(controller),
+    ~aborted=() => 1,
+    async () => {
+      calls := calls.contents + 1
+      setTimeout(() => Fetch.AbortController.abort(controller), 10)->ignore
+      Error(14)
+    },
+  )
+  equal(result, Error(1))
+  equal(calls.contents, 1)
+  ok(Date.now() -. started < 1000.0)
+})
+
+test("an already aborted signal skips the backoff", async () => {
+  let controller = Fetch.AbortController.make()
+  Fetch.AbortController.abort(controller)
+  await Retry.sleep(~signal=Fetch.AbortController.signal(controller), 60000)
+  equal(Fetch.AbortSignal.aborted(Fetch.AbortController.signal(controller)), true)
+})
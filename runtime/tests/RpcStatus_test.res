@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MPL-2.0
+open NodeTest
+
+// google.rpc.Status{code: 3, message: "invalid user", details: [BadRequest]}
+let statusBin = "CAMSDGludmFsaWQgdXNlchpPCil0eXBlLmdvb2dsZWFwaXMuY29tL2dvb2dsZS5ycGMuQmFkUmVxdWVzdBIiCiAKBWVtYWlsEhdtdXN0IGJlIGEgdmFsaWQgYWRkcmVzcw=="
+// BadRequest{field_violations: [{field: "email", description: "must be a valid address"}]}
+let badRequestBin = "CiAKBWVtYWlsEhdtdXN0IGJlIGEgdmFsaWQgYWRkcmVzcw=="
+
+let unpadded = s => String.replaceRegExp(s, %re("/=+$/"), "")
+
+let emailViolation: RpcStatus.fieldViolation = {
+  field: "email",
+  description: "must be a valid address",
+  reason: "",
+  localizedMessage: None,
+}
+
+let fromHeaders = (headers, ~body="") =>
+  RpcStatus.fromResponse(~header=name => Js.Dict.get(Js.Dict.fromArray(headers), name), ~body)
+
+let violations = status => status->Option.mapOr([], RpcStatus.fieldViolations)
+
+test("details header decodes with and without padding", async () => {
+  let padded = fromHeaders([("grpc-status-details-bin", statusBin)])
+  equal(padded->Option.map(status => (status.code, status.message)), Some((3, "invalid user")))
+  equal(violations(padded), [emailViolation])
+  equal(violations(fromHeaders([("grpc-status-details-bin", unpadded(statusBin))])), [emailViolation])
+})
+
+test("Connect details decode with and without padding or the URL-safe alphabet", async () => {
+  let body = value =>
+    `{"code": "invalid_argument", "message": "invalid user", "details": [{"type": "google.rpc.BadRequest", "value": "${value}"}]}`
+  [badRequestBin, unpadded(badRequestBin), unpadded(badRequestBin)->String.replaceAll("+", "-")->String.replaceAll("/", "_")]
+  ->Array.forEach(value => equal(violations(fromHeaders([], ~body=body(value))), [emailViolation]))
+})
+
+test("corrupt base64 details are rejected", async () => {
+  let bytes = s => Json.Decode.bytes(Js.Json.string(s))->Option.map(Js.Typed_array.Uint8Array.length)
+  equal(bytes("CAM"), Some(2))
+  equal(bytes("CA*M"), None)
+  equal(bytes("CA=M"), None)
+  equal(bytes("CAMSD"), None)
+
+  let corrupt = String.slice(statusBin, ~start=0, ~end=20) ++ "!" ++ String.sliceToEnd(statusBin, ~start=21)
+  let status = fromHeaders([("grpc-status", "3"), ("grpc-message", "invalid user"), ("grpc-status-details-bin", corrupt)])
+  equal(status, Some({RpcStatus.code: 3, message: "invalid user", details: []}))
+})
+
+test("error bodies that are not a status fall back to the headers", async () => {
+  let status = fromHeaders([("grpc-status", "14"), ("grpc-message", "down")], ~body=`{"code": 404}`)
+  equal(status->Option.map(status => status.code), Some(14))
+  equal(RpcStatus.fromJson(Js.Json.parseExn(`{"code": 3, "message": 7}`)), None)
+  equal(RpcStatus.fromJson(Js.Json.parseExn(`{"code": 3, "details": [1]}`)), None)
+  equal(RpcStatus.fromConnectJson(Js.Json.parseExn(`{"code": "invalid_argument", "details": {}}`)), None)
+})
+
+test("toHeaders round-trips through fromResponse", async () => {
+  let status: RpcStatus.t = {
+    code: 3,
+    message: "invalid user: ä",
+    details: [BadRequest({fieldViolations: [emailViolation]}), RetryInfo({retryDelay: Some(1.5)})],
+  }
+  let headers = RpcStatus.toHeaders(status)
+  equal(headers->Array.map(((name, _)) => name), ["grpc-status", "grpc-message", "grpc-status-details-bin"])
+  ok(headers->Array.every(((_, value)) => !String.endsWith(value, "=")))
+  equal(fromHeaders(headers), Some(status))
+  // Without the details header, code and message still come through
+  equal(fromHeaders(headers->Array.slice(~start=0, ~end=2)), Some({...status, details: []}))
+})
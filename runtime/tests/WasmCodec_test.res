@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MPL-2.0
+open NodeTest
+
+// The WASM codec is not built for the tests, so a stub returns what the Rust
+// codec decodes from the same bytes (pinned by the *_generated_schema tests
+// in codec/src/lib.rs)
+let decodesTo = (json: string) =>
+  WasmCodec.wasmInstance :=
+    Some({
+      encode: (_, _) => "",
+      decode: (_, _) => "",
+      encode_object: (_, _) => Js.TypedArray2.Uint8Array.make([]),
+      decode_object: (_, _) => Js.Json.parseExn(json),
+      register_extension: (_, _) => (),
+      register_type: _ => (),
+      clear_extensions: () => (),
+    })
+
+test("decoded oneofs map onto the generated variant", async () => {
+  decodesTo(`{"id": "s1", "circle": {}}`)
+  switch OneofsProto.Shape.decode(Js.TypedArray2.Uint8Array.make([0x0a, 2, 0x73, 0x31, 0x12, 0])) {
+  | Ok({id, zIndex, kind: Some(Circle(circle))}) => equal((id, zIndex, circle.radius), ("s1", None, 0.0))
+  | _ => ok(false)
+  }
+
+  decodesTo(`{"label": "a", "z_index": 0}`)
+  switch OneofsProto.Shape.decode(Js.TypedArray2.Uint8Array.make([0x2a, 1, 0x61, 0x30, 0])) {
+  | Ok({id, zIndex, kind: Some(Label(label))}) => equal((id, zIndex, label), ("", Some(0), "a"))
+  | _ => ok(false)
+  }
+})
+
+test("a oneof with several members or an invalid member does not decode", async () => {
+  let decode = json => OneofsProto.Shape.fromJson(Js.Json.parseExn(json))
+  ok(Option.isSome(decode(`{"id": "s1", "side": 1}`)))
+  ok(Option.isNone(decode(`{"id": "s1", "side": 1, "label": "a"}`)))
+  ok(Option.isNone(decode(`{"id": "s1", "circle": 3}`)))
+})
+
+test("binary and JSON well-known types decode to the same value", async () => {
+  decodesTo(`{"id": "a1", "action": "x", "created_at": "2023-11-14T22:13:20.500Z",
+    "duration": "1.500s", "description": "d", "response_code": "200"}`)
+  let binary = WktProto.AuditLog.decode(
+    Js.TypedArray2.Uint8Array.make([
+      0x0a, 0x02, 0x61, 0x31, 0x12, 0x01, 0x78, 0x1a, 0x0c, 0x08, 0x80, 0xe2, 0xcf, 0xaa, 0x06, 0x10, 0x80, 0xca,
+      0xb5, 0xee, 0x01, 0x22, 0x08, 0x08, 0x01, 0x10, 0x80, 0xca, 0xb5, 0xee, 0x01, 0x2a, 0x03, 0x0a, 0x01, 0x64,
+      0x32, 0x03, 0x08, 0xc8, 0x01,
+    ]),
+  )
+  let json = WktProto.AuditLog.fromJson(Js.Json.parseExn(`{"id": "a1", "action": "x",
+    "created_at": "2023-11-14T22:13:20.5Z", "duration": "1.5s", "description": "d", "response_code": "200"}`))
+  switch (binary, json) {
+  | (Ok(binary), Some(json)) =>
+    equal(binary, json)
+    equal(binary.duration, Some(1.5))
+  | _ => ok(false)
+  }
+})
@@ -0,0 +1,349 @@
+// Generated from event.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: example
+
+module UserCreated = {
+  type t = {
+    userId: string,
+    name: string,
+    email: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~userId,
+    ~name,
+    ~email,
+    ~unknownFields=?
+  ): t => {
+    userId,
+    name,
+    email,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("user_id", msg.userId, Json.Encode.string),
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+        Json.Encode.required("email", msg.email, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let userId = Json.Decode.required(obj, "user_id", Json.Decode.string)
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let email = Json.Decode.required(obj, "email", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (userId, name, email) {
+        | (Ok(userId), Ok(name), Ok(email)) =>
+          Some({
+            userId,
+            name,
+            email,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "user_id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "name", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid UserCreated")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+module UserUpdated = {
+  type t = {
+    userId: string,
+    name: option<string>,
+    email: option<string>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~userId,
+    ~name=?,
+    ~email=?,
+    ~unknownFields=?
+  ): t => {
+    userId,
+    name,
+    email,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("user_id", msg.userId, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("name", msg.name, Json.Encode.string),
+        Json.Encode.optional("email", msg.email, Json.Encode.string),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let userId = Json.Decode.required(obj, "user_id", Json.Decode.string)
+        let name = Json.Decode.optional(obj, "name", Json.Decode.string)->Result.getOr(None)
+        let email = Json.Decode.optional(obj, "email", Json.Decode.string)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (userId) {
+        | (Ok(userId)) =>
+          Some({
+            userId,
+            name,
+            email,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "user_id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "name", fieldType: WasmCodec.String, repeated: false, optional: true},
+    {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: true},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid UserUpdated")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+module UserDeleted = {
+  type t = {
+    userId: string,
+    reason: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~userId,
+    ~reason,
+    ~unknownFields=?
+  ): t => {
+    userId,
+    reason,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("user_id", msg.userId, Json.Encode.string),
+        Json.Encode.required("reason", msg.reason, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let userId = Json.Decode.required(obj, "user_id", Json.Decode.string)
+        let reason = Json.Decode.required(obj, "reason", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (userId, reason) {
+        | (Ok(userId), Ok(reason)) =>
+          Some({
+            userId,
+            reason,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "user_id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "reason", fieldType: WasmCodec.String, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid UserDeleted")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Event with oneof payload */
+module Event = {
+  type payload =
+    | UserCreated(UserCreated.t)
+    | UserUpdated(UserUpdated.t)
+    | UserDeleted(UserDeleted.t)
+
+  type t = {
+    id: string,
+    timestamp: bigint,
+    payload: option<payload>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~timestamp,
+    ~payload=?,
+    ~unknownFields=?
+  ): t => {
+    id,
+    timestamp,
+    payload,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    let payloadFields: array<(string, Js.Json.t)> = switch msg.payload {
+    | None => []
+    | Some(UserCreated(v)) => [("user_created", UserCreated.toJsonWith(v, ~unknownFields))]
+    | Some(UserUpdated(v)) => [("user_updated", UserUpdated.toJsonWith(v, ~unknownFields))]
+    | Some(UserDeleted(v)) => [("user_deleted", UserDeleted.toJsonWith(v, ~unknownFields))]
+    }
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.string),
+        Json.Encode.required("timestamp", msg.timestamp, Json.Encode.int64),
+      ],
+      [
+        payloadFields,
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.string)
+        let timestamp = Json.Decode.required(obj, "timestamp", Json.Decode.int64)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        let payload = switch [
+          Json.Decode.optional(obj, "user_created", UserCreated.fromJson)->Result.map(v => Option.map(v, v => UserCreated(v))),
+          Json.Decode.optional(obj, "user_updated", UserUpdated.fromJson)->Result.map(v => Option.map(v, v => UserUpdated(v))),
+          Json.Decode.optional(obj, "user_deleted", UserDeleted.fromJson)->Result.map(v => Option.map(v, v => UserDeleted(v))),
+        ]->Array.filter(member => Result.mapOr(member, true, Option.isSome)) {
+        | [] => Ok(None)
+        | [member] => member
+        | _ => Error(Json.Decode.InvalidValue("more than one member of payload is set"))
+        }
+        switch (id, timestamp, payload) {
+        | (Ok(id), Ok(timestamp), Ok(payload)) =>
+          Some({
+            id,
+            timestamp,
+            payload,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "timestamp", fieldType: WasmCodec.Int64, repeated: false, optional: false},
+    {n: 10, name: "user_created", fieldType: WasmCodec.Message([{n: 1, name: "user_id", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 2, name: "name", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: false}]), repeated: false, optional: false, oneof: "payload", variant: "UserCreated"},
+    {n: 11, name: "user_updated", fieldType: WasmCodec.Message([{n: 1, name: "user_id", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 2, name: "name", fieldType: WasmCodec.String, repeated: false, optional: true}, {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: true}]), repeated: false, optional: false, oneof: "payload", variant: "UserUpdated"},
+    {n: 12, name: "user_deleted", fieldType: WasmCodec.Message([{n: 1, name: "user_id", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 2, name: "reason", fieldType: WasmCodec.String, repeated: false, optional: false}]), repeated: false, optional: false, oneof: "payload", variant: "UserDeleted"},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid Event")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
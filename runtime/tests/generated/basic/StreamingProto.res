@@ -0,0 +1,465 @@
+// Generated from streaming.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: example
+
+/** User type for streaming (self-contained for testing) */
+module StreamUser = {
+  type t = {
+    id: int,
+    name: string,
+    email: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~name,
+    ~email,
+    ~unknownFields=?
+  ): t => {
+    id,
+    name,
+    email,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+        Json.Encode.required("email", msg.email, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let email = Json.Decode.required(obj, "email", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id, name, email) {
+        | (Ok(id), Ok(name), Ok(email)) =>
+          Some({
+            id,
+            name,
+            email,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+    {n: 2, name: "name", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid StreamUser")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Request for getting a user */
+module GetStreamUserRequest = {
+  type t = {
+    id: int,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~unknownFields=?
+  ): t => {
+    id,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id) {
+        | (Ok(id)) =>
+          Some({
+            id,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid GetStreamUserRequest")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Request for listing users with streaming */
+module ListUsersStreamRequest = {
+  type t = {
+    pageSize: int,
+    pageToken: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~pageSize,
+    ~pageToken,
+    ~unknownFields=?
+  ): t => {
+    pageSize,
+    pageToken,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("page_size", msg.pageSize, Json.Encode.int),
+        Json.Encode.required("page_token", msg.pageToken, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let pageSize = Json.Decode.required(obj, "page_size", Json.Decode.int)
+        let pageToken = Json.Decode.required(obj, "page_token", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (pageSize, pageToken) {
+        | (Ok(pageSize), Ok(pageToken)) =>
+          Some({
+            pageSize,
+            pageToken,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "page_size", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+    {n: 2, name: "page_token", fieldType: WasmCodec.String, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid ListUsersStreamRequest")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Request for uploading multiple users */
+module UploadUsersRequest = {
+  type t = {
+    user: option<StreamUser.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~user=?,
+    ~unknownFields=?
+  ): t => {
+    user,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+      ],
+      [
+        Json.Encode.optional("user", msg.user, v => StreamUser.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let user = Json.Decode.optional(obj, "user", StreamUser.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        Some({
+          user,
+          unknownFields: ?unknownFields,
+        })
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "user", fieldType: WasmCodec.Message([{n: 1, name: "id", fieldType: WasmCodec.Int32, repeated: false, optional: false}, {n: 2, name: "name", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: false}]), repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid UploadUsersRequest")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Response for upload */
+module UploadUsersResponse = {
+  type t = {
+    uploadedCount: int,
+    failedIds: array<string>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~uploadedCount,
+    ~failedIds=[],
+    ~unknownFields=?
+  ): t => {
+    uploadedCount,
+    failedIds,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("uploaded_count", msg.uploadedCount, Json.Encode.int),
+      ],
+      [
+        Json.Encode.repeated("failed_ids", msg.failedIds, Json.Encode.string),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let uploadedCount = Json.Decode.required(obj, "uploaded_count", Json.Decode.int)
+        let failedIds = Json.Decode.repeated(obj, "failed_ids", Json.Decode.string)->Result.getOr([])
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (uploadedCount) {
+        | (Ok(uploadedCount)) =>
+          Some({
+            uploadedCount,
+            failedIds,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "uploaded_count", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+    {n: 2, name: "failed_ids", fieldType: WasmCodec.String, repeated: true, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid UploadUsersResponse")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Chat message for bidirectional streaming */
+module ChatMessage = {
+  type t = {
+    id: string,
+    sender: string,
+    content: string,
+    timestamp: bigint,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~sender,
+    ~content,
+    ~timestamp,
+    ~unknownFields=?
+  ): t => {
+    id,
+    sender,
+    content,
+    timestamp,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.string),
+        Json.Encode.required("sender", msg.sender, Json.Encode.string),
+        Json.Encode.required("content", msg.content, Json.Encode.string),
+        Json.Encode.required("timestamp", msg.timestamp, Json.Encode.int64),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.string)
+        let sender = Json.Decode.required(obj, "sender", Json.Decode.string)
+        let content = Json.Decode.required(obj, "content", Json.Decode.string)
+        let timestamp = Json.Decode.required(obj, "timestamp", Json.Decode.int64)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id, sender, content, timestamp) {
+        | (Ok(id), Ok(sender), Ok(content), Ok(timestamp)) =>
+          Some({
+            id,
+            sender,
+            content,
+            timestamp,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "sender", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 3, name: "content", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 4, name: "timestamp", fieldType: WasmCodec.Int64, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid ChatMessage")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
@@ -0,0 +1,448 @@
+// Generated from user.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: example
+
+/** Status enum for user account */
+module Status = {
+  type t = [
+    | #StatusUnknown
+    | #StatusActive
+    | #StatusInactive
+    | #StatusSuspended
+  ]
+
+  let toInt = (v: t): int => {
+    switch v {
+    | #StatusUnknown => 0
+    | #StatusActive => 1
+    | #StatusInactive => 2
+    | #StatusSuspended => 3
+    }
+  }
+
+  let fromInt = (i: int): option<t> => {
+    switch i {
+    | 0 => Some(#StatusUnknown)
+    | 1 => Some(#StatusActive)
+    | 2 => Some(#StatusInactive)
+    | 3 => Some(#StatusSuspended)
+    | _ => None
+    }
+  }
+}
+
+
+/** Nested address message */
+module Address = {
+  type t = {
+    street: string,
+    city: string,
+    country: string,
+    postalCode: option<string>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~street,
+    ~city,
+    ~country,
+    ~postalCode=?,
+    ~unknownFields=?
+  ): t => {
+    street,
+    city,
+    country,
+    postalCode,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("street", msg.street, Json.Encode.string),
+        Json.Encode.required("city", msg.city, Json.Encode.string),
+        Json.Encode.required("country", msg.country, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("postal_code", msg.postalCode, Json.Encode.string),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let street = Json.Decode.required(obj, "street", Json.Decode.string)
+        let city = Json.Decode.required(obj, "city", Json.Decode.string)
+        let country = Json.Decode.required(obj, "country", Json.Decode.string)
+        let postalCode = Json.Decode.optional(obj, "postal_code", Json.Decode.string)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (street, city, country) {
+        | (Ok(street), Ok(city), Ok(country)) =>
+          Some({
+            street,
+            city,
+            country,
+            postalCode,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "street", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "city", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 3, name: "country", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 4, name: "postal_code", fieldType: WasmCodec.String, repeated: false, optional: true},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid Address")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** User message */
+module User = {
+  type t = {
+    name: string,
+    id: int,
+    email: option<string>,
+    status: Status.t,
+    tags: array<string>,
+    address: option<Address.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~name,
+    ~id,
+    ~email=?,
+    ~status,
+    ~tags=[],
+    ~address=?,
+    ~unknownFields=?
+  ): t => {
+    name,
+    id,
+    email,
+    status,
+    tags,
+    address,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("name", msg.name, Json.Encode.string),
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+        Json.Encode.required("status", msg.status, v => Json.Encode.int(Status.toInt(v))),
+      ],
+      [
+        Json.Encode.optional("email", msg.email, Json.Encode.string),
+        Json.Encode.repeated("tags", msg.tags, Json.Encode.string),
+        Json.Encode.optional("address", msg.address, v => Address.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let name = Json.Decode.required(obj, "name", Json.Decode.string)
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let email = Json.Decode.optional(obj, "email", Json.Decode.string)->Result.getOr(None)
+        let status = Json.Decode.required(obj, "status", json => Json.Decode.int(json)->Option.flatMap(Status.fromInt))
+        let tags = Json.Decode.repeated(obj, "tags", Json.Decode.string)->Result.getOr([])
+        let address = Json.Decode.optional(obj, "address", Address.fromJson)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (name, id, status) {
+        | (Ok(name), Ok(id), Ok(status)) =>
+          Some({
+            name,
+            id,
+            email,
+            status,
+            tags,
+            address,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "name", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "id", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+    {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: true},
+    {n: 4, name: "status", fieldType: WasmCodec.Enum, repeated: false, optional: false},
+    {n: 5, name: "tags", fieldType: WasmCodec.String, repeated: true, optional: false},
+    {n: 6, name: "address", fieldType: WasmCodec.Message([{n: 1, name: "street", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 2, name: "city", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 3, name: "country", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 4, name: "postal_code", fieldType: WasmCodec.String, repeated: false, optional: true}]), repeated: false, optional: true},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid User")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+module GetUserRequest = {
+  type t = {
+    id: int,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~unknownFields=?
+  ): t => {
+    id,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.int),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.int)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id) {
+        | (Ok(id)) =>
+          Some({
+            id,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid GetUserRequest")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+module ListUsersRequest = {
+  type t = {
+    pageSize: int,
+    pageToken: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~pageSize,
+    ~pageToken,
+    ~unknownFields=?
+  ): t => {
+    pageSize,
+    pageToken,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("page_size", msg.pageSize, Json.Encode.int),
+        Json.Encode.required("page_token", msg.pageToken, Json.Encode.string),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let pageSize = Json.Decode.required(obj, "page_size", Json.Decode.int)
+        let pageToken = Json.Decode.required(obj, "page_token", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (pageSize, pageToken) {
+        | (Ok(pageSize), Ok(pageToken)) =>
+          Some({
+            pageSize,
+            pageToken,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "page_size", fieldType: WasmCodec.Int32, repeated: false, optional: false},
+    {n: 2, name: "page_token", fieldType: WasmCodec.String, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid ListUsersRequest")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+module ListUsersResponse = {
+  type t = {
+    users: array<User.t>,
+    nextPageToken: string,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~users=[],
+    ~nextPageToken,
+    ~unknownFields=?
+  ): t => {
+    users,
+    nextPageToken,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("next_page_token", msg.nextPageToken, Json.Encode.string),
+      ],
+      [
+        Json.Encode.repeated("users", msg.users, v => User.toJsonWith(v, ~unknownFields)),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let users = Json.Decode.repeated(obj, "users", User.fromJson)->Result.getOr([])
+        let nextPageToken = Json.Decode.required(obj, "next_page_token", Json.Decode.string)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (nextPageToken) {
+        | (Ok(nextPageToken)) =>
+          Some({
+            users,
+            nextPageToken,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "users", fieldType: WasmCodec.Message([{n: 1, name: "name", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 2, name: "id", fieldType: WasmCodec.Int32, repeated: false, optional: false}, {n: 3, name: "email", fieldType: WasmCodec.String, repeated: false, optional: true}, {n: 4, name: "status", fieldType: WasmCodec.Enum, repeated: false, optional: false}, {n: 5, name: "tags", fieldType: WasmCodec.String, repeated: true, optional: false}, {n: 6, name: "address", fieldType: WasmCodec.Message([{n: 1, name: "street", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 2, name: "city", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 3, name: "country", fieldType: WasmCodec.String, repeated: false, optional: false}, {n: 4, name: "postal_code", fieldType: WasmCodec.String, repeated: false, optional: true}]), repeated: false, optional: true}]), repeated: true, optional: false},
+    {n: 2, name: "next_page_token", fieldType: WasmCodec.String, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid ListUsersResponse")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
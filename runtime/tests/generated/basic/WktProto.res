@@ -0,0 +1,290 @@
+// Generated from wkt.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: example
+
+/** Message using well-known types */
+module AuditLog = {
+  type t = {
+    id: string,
+    action: string,
+    createdAt: option<Js.Date.t>,
+    duration: option<float>,
+    description: option<string>,
+    responseCode: option<bigint>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~action,
+    ~createdAt=?,
+    ~duration=?,
+    ~description=?,
+    ~responseCode=?,
+    ~unknownFields=?
+  ): t => {
+    id,
+    action,
+    createdAt,
+    duration,
+    description,
+    responseCode,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.string),
+        Json.Encode.required("action", msg.action, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("created_at", msg.createdAt, WellKnown.Timestamp.toJson),
+        Json.Encode.optional("duration", msg.duration, WellKnown.Duration.toJson),
+        Json.Encode.optional("description", msg.description, Json.Encode.string),
+        Json.Encode.optional("response_code", msg.responseCode, Json.Encode.int64),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.string)
+        let action = Json.Decode.required(obj, "action", Json.Decode.string)
+        let createdAt = Json.Decode.optional(obj, "created_at", WellKnown.Timestamp.fromJson)->Result.getOr(None)
+        let duration = Json.Decode.optional(obj, "duration", WellKnown.Duration.fromJson)->Result.getOr(None)
+        let description = Json.Decode.optional(obj, "description", Json.Decode.string)->Result.getOr(None)
+        let responseCode = Json.Decode.optional(obj, "response_code", Json.Decode.int64)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id, action) {
+        | (Ok(id), Ok(action)) =>
+          Some({
+            id,
+            action,
+            createdAt,
+            duration,
+            description,
+            responseCode,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "action", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 3, name: "created_at", fieldType: WasmCodec.WellKnown("google.protobuf.Timestamp"), repeated: false, optional: false},
+    {n: 4, name: "duration", fieldType: WasmCodec.WellKnown("google.protobuf.Duration"), repeated: false, optional: false},
+    {n: 5, name: "description", fieldType: WasmCodec.WellKnown("google.protobuf.StringValue"), repeated: false, optional: false},
+    {n: 6, name: "response_code", fieldType: WasmCodec.WellKnown("google.protobuf.Int64Value"), repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid AuditLog")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Task with optional deadline */
+module Task = {
+  type t = {
+    id: string,
+    title: string,
+    deadline: option<Js.Date.t>,
+    estimatedTime: option<float>,
+    completed: option<bool>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~title,
+    ~deadline=?,
+    ~estimatedTime=?,
+    ~completed=?,
+    ~unknownFields=?
+  ): t => {
+    id,
+    title,
+    deadline,
+    estimatedTime,
+    completed,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.string),
+        Json.Encode.required("title", msg.title, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("deadline", msg.deadline, WellKnown.Timestamp.toJson),
+        Json.Encode.optional("estimated_time", msg.estimatedTime, WellKnown.Duration.toJson),
+        Json.Encode.optional("completed", msg.completed, Json.Encode.bool),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.string)
+        let title = Json.Decode.required(obj, "title", Json.Decode.string)
+        let deadline = Json.Decode.optional(obj, "deadline", WellKnown.Timestamp.fromJson)->Result.getOr(None)
+        let estimatedTime = Json.Decode.optional(obj, "estimated_time", WellKnown.Duration.fromJson)->Result.getOr(None)
+        let completed = Json.Decode.optional(obj, "completed", Json.Decode.bool)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (id, title) {
+        | (Ok(id), Ok(title)) =>
+          Some({
+            id,
+            title,
+            deadline,
+            estimatedTime,
+            completed,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "title", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 3, name: "deadline", fieldType: WasmCodec.WellKnown("google.protobuf.Timestamp"), repeated: false, optional: false},
+    {n: 4, name: "estimated_time", fieldType: WasmCodec.WellKnown("google.protobuf.Duration"), repeated: false, optional: false},
+    {n: 5, name: "completed", fieldType: WasmCodec.WellKnown("google.protobuf.BoolValue"), repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid Task")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+/** Dynamic metadata */
+module Metadata = {
+  type t = {
+    key: string,
+    data: option<Js.Dict.t<Js.Json.t>>,
+    value: option<Js.Json.t>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~key,
+    ~data=?,
+    ~value=?,
+    ~unknownFields=?
+  ): t => {
+    key,
+    data,
+    value,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("key", msg.key, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("data", msg.data, WellKnown.Struct.toJson),
+        Json.Encode.optional("value", msg.value, v => v),
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let key = Json.Decode.required(obj, "key", Json.Decode.string)
+        let data = Json.Decode.optional(obj, "data", WellKnown.Struct.fromJson)->Result.getOr(None)
+        let value = Json.Decode.optional(obj, "value", json => Some(json))->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (key) {
+        | (Ok(key)) =>
+          Some({
+            key,
+            data,
+            value,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "key", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "data", fieldType: WasmCodec.WellKnown("google.protobuf.Struct"), repeated: false, optional: false},
+    {n: 3, name: "value", fieldType: WasmCodec.WellKnown("google.protobuf.Value"), repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid Metadata")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
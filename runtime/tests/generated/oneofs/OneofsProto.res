@@ -0,0 +1,207 @@
+// Generated from oneofs.proto by protoc-gen-rescript
+// SPDX-License-Identifier: MPL-2.0
+// DO NOT EDIT - regenerate from .proto source
+
+// Package: fixtures.oneofs
+
+module Color = {
+  type t = [
+    | #ColorUnspecified
+    | #ColorRed
+  ]
+
+  let toInt = (v: t): int => {
+    switch v {
+    | #ColorUnspecified => 0
+    | #ColorRed => 1
+    }
+  }
+
+  let fromInt = (i: int): option<t> => {
+    switch i {
+    | 0 => Some(#ColorUnspecified)
+    | 1 => Some(#ColorRed)
+    | _ => None
+    }
+  }
+}
+
+
+module Circle = {
+  type t = {
+    radius: float,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~radius,
+    ~unknownFields=?
+  ): t => {
+    radius,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("radius", msg.radius, Json.Encode.float),
+      ],
+      [
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let radius = Json.Decode.required(obj, "radius", Json.Decode.float)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        switch (radius) {
+        | (Ok(radius)) =>
+          Some({
+            radius,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "radius", fieldType: WasmCodec.Double, repeated: false, optional: false},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid Circle")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+
+module Shape = {
+  /** What is drawn */
+  type kind =
+    | Circle(Circle.t)
+    | Side(float)
+    | Color(Color.t)
+    | Label(string)
+
+  type t = {
+    id: string,
+    /** proto3 optional fields use synthetic oneofs */
+    zIndex: option<int>,
+    /** What is drawn */
+    kind: option<kind>,
+    unknownFields?: UnknownFields.t,
+  }
+
+  let make = (
+    ~id,
+    ~zIndex=?,
+    ~kind=?,
+    ~unknownFields=?
+  ): t => {
+    id,
+    zIndex,
+    kind,
+    unknownFields: ?unknownFields,
+  }
+
+  // JSON serialization
+  // Unknown fields are kept under "$unknown" only for the WASM codec,
+  // proto3 JSON has no representation for them
+  let toJsonWith = (msg: t, ~unknownFields: bool): Js.Json.t => {
+    let kindFields: array<(string, Js.Json.t)> = switch msg.kind {
+    | None => []
+    | Some(Circle(v)) => [("circle", Circle.toJsonWith(v, ~unknownFields))]
+    | Some(Side(v)) => [("side", Json.Encode.float(v))]
+    | Some(Color(v)) => [("color", Json.Encode.int(Color.toInt(v)))]
+    | Some(Label(v)) => [("label", Json.Encode.string(v))]
+    }
+    Json.Encode.object(Json.Encode.fields(
+      [
+        Json.Encode.required("id", msg.id, Json.Encode.string),
+      ],
+      [
+        Json.Encode.optional("z_index", msg.zIndex, Json.Encode.int),
+        kindFields,
+        unknownFields ? UnknownFields.toJsonFields(msg.unknownFields) : [],
+      ],
+    ))
+  }
+
+  let toJson = (msg: t): Js.Json.t => toJsonWith(msg, ~unknownFields=false)
+
+  // JSON deserialization
+  let fromJson = (json: Js.Json.t): option<t> => {
+    switch Json.Decode.object(json) {
+    | Some(obj) =>
+        let id = Json.Decode.required(obj, "id", Json.Decode.string)
+        let zIndex = Json.Decode.optional(obj, "z_index", Json.Decode.int)->Result.getOr(None)
+        let unknownFields = UnknownFields.fromJsonObject(obj)
+        let kind = switch [
+          Json.Decode.optional(obj, "circle", Circle.fromJson)->Result.map(v => Option.map(v, v => Circle(v))),
+          Json.Decode.optional(obj, "side", Json.Decode.float)->Result.map(v => Option.map(v, v => Side(v))),
+          Json.Decode.optional(obj, "color", json => Json.Decode.int(json)->Option.flatMap(Color.fromInt))->Result.map(v => Option.map(v, v => Color(v))),
+          Json.Decode.optional(obj, "label", Json.Decode.string)->Result.map(v => Option.map(v, v => Label(v))),
+        ]->Array.filter(member => Result.mapOr(member, true, Option.isSome)) {
+        | [] => Ok(None)
+        | [member] => member
+        | _ => Error(Json.Decode.InvalidValue("more than one member of kind is set"))
+        }
+        switch (id, kind) {
+        | (Ok(id), Ok(kind)) =>
+          Some({
+            id,
+            zIndex,
+            kind,
+            unknownFields: ?unknownFields,
+          })
+        | _ => None
+        }
+    | None => None
+    }
+  }
+
+  // Protobuf binary through the WASM codec (after WasmCodec.initialize)
+  let wasmSchema: array<WasmCodec.fieldDescriptor> = [
+    {n: 1, name: "id", fieldType: WasmCodec.String, repeated: false, optional: false},
+    {n: 2, name: "circle", fieldType: WasmCodec.Message([{n: 1, name: "radius", fieldType: WasmCodec.Double, repeated: false, optional: false}]), repeated: false, optional: false, oneof: "kind", variant: "Circle"},
+    {n: 3, name: "side", fieldType: WasmCodec.Double, repeated: false, optional: false, oneof: "kind", variant: "Side"},
+    {n: 4, name: "color", fieldType: WasmCodec.Enum, repeated: false, optional: false, oneof: "kind", variant: "Color"},
+    {n: 5, name: "label", fieldType: WasmCodec.String, repeated: false, optional: false, oneof: "kind", variant: "Label"},
+    {n: 6, name: "z_index", fieldType: WasmCodec.Int32, repeated: false, optional: true},
+  ]
+
+  let encode = (msg: t): result<Js.TypedArray2.Uint8Array.t, string> =>
+    WasmCodec.encodeBytes(~schema=wasmSchema, ~data=toJsonWith(msg, ~unknownFields=true))
+
+  let decode = (bytes: Js.TypedArray2.Uint8Array.t): result<t, string> =>
+    switch WasmCodec.decodeBytes(~schema=wasmSchema, ~data=bytes) {
+    | Ok(json) =>
+      switch fromJson(WasmCodec.withDefaults(json, wasmSchema)) {
+      | Some(msg) => Ok(msg)
+      | None => Error("Decoded message is not a valid Shape")
+      }
+    | Error(err) => Error(err)
+    }
+}
+
+